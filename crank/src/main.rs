@@ -10,6 +10,8 @@ use solana_client::rpc_config::{
     RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSendTransactionConfig,
 };
 use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_client::rpc_request::MAX_MULTIPLE_ACCOUNTS;
+use solana_sdk::clock::Clock;
 use solana_sdk::commitment_config::CommitmentConfig;
 use solana_sdk::instruction::{AccountMeta, Instruction};
 use solana_sdk::pubkey::Pubkey;
@@ -17,8 +19,11 @@ use solana_sdk::signature::Signer;
 use solana_sdk::signer::EncodableKey;
 use solana_sdk::transaction::Transaction;
 
-use savings_program::SavingsManager;
 use savings_program::{accounts, instruction};
+use savings_program::{
+    Campaign, InterestDistributor, RateHistory, RegistryPage, SavingsManager, SavingsPlan,
+    SECONDS_IN_MONTHS,
+};
 
 #[derive(Debug, Parser)]
 pub struct Cli {
@@ -47,6 +52,55 @@ pub enum Command {
         #[clap(long, short)]
         user_pubkeys: Vec<Pubkey>,
 
+        #[clap(long, short)]
+        program_id: Pubkey,
+    },
+    /// Deposits interest into every due savings vault of a distributor, found by walking the
+    /// distributor's on-chain registry rather than scanning program accounts.
+    CrankRegistry {
+        #[clap(long, short)]
+        keypair: String,
+
+        #[clap(long, short)]
+        distributor: Pubkey,
+
+        #[clap(long, short)]
+        program_id: Pubkey,
+    },
+    /// Pulls every due recurring contribution into its savings vault. Restricted to a single
+    /// user's plans if `user_pubkey` is given.
+    CrankSavingsPlans {
+        #[clap(long, short)]
+        keypair: String,
+
+        #[clap(long, short)]
+        user_pubkey: Option<Pubkey>,
+
+        #[clap(long, short)]
+        program_id: Pubkey,
+    },
+    /// Builds a bonus campaign's merkle tree from a CSV of `user,amount` rows and writes each
+    /// row's index and proof to `output`, ready for `claim-campaign`. Prints the tree's root and
+    /// claim count, which the campaign is created with.
+    BuildCampaignTree {
+        #[clap(long, short)]
+        csv: String,
+
+        #[clap(long, short)]
+        output: String,
+    },
+    /// Claims every unclaimed allocation in a proofs file written by `build-campaign-tree` into
+    /// its user's savings vault.
+    ClaimCampaign {
+        #[clap(long, short)]
+        keypair: String,
+
+        #[clap(long, short)]
+        campaign: Pubkey,
+
+        #[clap(long)]
+        proofs: String,
+
         #[clap(long, short)]
         program_id: Pubkey,
     },
@@ -68,7 +122,8 @@ impl Cli {
     }
 }
 
-/// Fetches all the savings accounts for a particular user and runs the crank on them.
+/// Fetches all the savings accounts for a particular user, including every sub-account they hold
+/// with each distributor, and runs the crank on them.
 async fn get_user_accounts(
     user: &Pubkey,
     rpc: &RpcClient,
@@ -102,6 +157,171 @@ async fn get_user_accounts(
         .collect())
 }
 
+/// Fetches all the savings accounts listed in `distributor`'s registry. Unlike
+/// `get_user_accounts`, this only needs `getMultipleAccounts`, which RPC providers rarely restrict.
+async fn get_registered_accounts(
+    distributor: &Pubkey,
+    rpc: &RpcClient,
+    program: &Pubkey,
+) -> Result<Vec<(Pubkey, SavingsManager)>> {
+    let account = rpc.get_account(distributor).await?;
+    let registry_pages =
+        InterestDistributor::try_deserialize(&mut account.data.as_ref())?.registry_pages;
+    let pages: Vec<Pubkey> = (0..registry_pages)
+        .map(|page| registry_page(distributor, page, program))
+        .collect();
+
+    let mut managers = Vec::new();
+    for chunk in pages.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        for account in rpc
+            .get_multiple_accounts(chunk)
+            .await?
+            .into_iter()
+            .flatten()
+        {
+            let page = RegistryPage::try_deserialize(&mut account.data.as_ref())?;
+            managers.extend(page.managers);
+        }
+    }
+
+    let mut accounts = Vec::with_capacity(managers.len());
+    for chunk in managers.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let fetched = rpc.get_multiple_accounts(chunk).await?;
+        for (key, account) in chunk.iter().zip(fetched) {
+            // Skip vaults closed since the page was fetched.
+            let Some(account) = account else {
+                continue;
+            };
+            let manager = SavingsManager::try_deserialize(&mut account.data.as_ref())?;
+            accounts.push((*key, manager));
+        }
+    }
+    Ok(accounts)
+}
+
+/// Page `page` of `distributor`'s registry of savings-managers.
+fn registry_page(distributor: &Pubkey, page: u32, program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            savings_program::REGISTRY_PAGE_SEED_PREFIX,
+            distributor.as_ref(),
+            &page.to_le_bytes(),
+        ],
+        program,
+    )
+    .0
+}
+
+/// The rate history of `distributor`, which interest deposits are computed from.
+fn rate_history(distributor: &Pubkey, program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            savings_program::RATE_HISTORY_SEED_PREFIX,
+            distributor.as_ref(),
+        ],
+        program,
+    )
+    .0
+}
+
+/// `user`'s allowlist entry with `distributor`'s state, if the distributor is allowlist-gated.
+fn allowlist_entry(
+    distributor: &InterestDistributor,
+    user: &Pubkey,
+    program: &Pubkey,
+) -> Option<Pubkey> {
+    distributor.allowlist_required.then(|| {
+        Pubkey::find_program_address(
+            &[
+                savings_program::ALLOWLIST_ENTRY_SEED_PREFIX,
+                distributor.state.as_ref(),
+                user.as_ref(),
+            ],
+            program,
+        )
+        .0
+    })
+}
+
+/// The token account `distributor` pays its performance fees to, if it has one.
+async fn fee_destination(distributor: &Pubkey, rpc: &RpcClient) -> Result<Option<Pubkey>> {
+    let account = rpc.get_account(distributor).await?;
+    let distributor = InterestDistributor::try_deserialize(&mut account.data.as_ref())?;
+    Ok(distributor.fee_destination)
+}
+
+/// The rate feed `distributor`'s interest rate follows, if it follows one.
+async fn rate_feed(
+    distributor: &Pubkey,
+    rpc: &RpcClient,
+    program: &Pubkey,
+) -> Result<Option<Pubkey>> {
+    let account = rpc.get_account(&rate_history(distributor, program)).await?;
+    let rate_history = RateHistory::try_deserialize(&mut account.data.as_ref())?;
+    Ok(rate_history.rate_feed)
+}
+
+/// The savings-manager and savings-vault a referral bonus on `manager`'s interest would be paid to,
+/// if it has a referrer. Bonuses are always paid to the referrer's sub-account zero.
+fn referrer_savings_accounts(
+    manager: &SavingsManager,
+    program: &Pubkey,
+) -> Option<(Pubkey, Pubkey)> {
+    manager.referrer.map(|referrer| {
+        let (referrer_manager, _) = Pubkey::find_program_address(
+            &[
+                savings_program::SAVINGS_MANAGER_SEED_PREFIX,
+                referrer.as_ref(),
+                manager.distributor.as_ref(),
+            ],
+            program,
+        );
+        (
+            referrer_manager,
+            get_associated_token_address(&referrer_manager, &manager.mint),
+        )
+    })
+}
+
+/// Fetches all the savings plans, optionally restricted to those belonging to a particular user.
+async fn get_savings_plans(
+    user: Option<&Pubkey>,
+    rpc: &RpcClient,
+    program: &Pubkey,
+) -> Result<Vec<(Pubkey, SavingsPlan)>> {
+    let mut filters = vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+        0,
+        SavingsPlan::DISCRIMINATOR.to_vec(),
+    ))];
+    if let Some(user) = user {
+        filters.push(RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+            8,
+            user.to_bytes().to_vec(),
+        )));
+    }
+
+    let config = RpcProgramAccountsConfig {
+        filters: Some(filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(rpc.commitment()),
+            ..RpcAccountInfoConfig::default()
+        },
+        with_context: Some(true),
+    };
+
+    let accounts = rpc
+        .get_program_accounts_with_config(program, config)
+        .await?;
+    Ok(accounts
+        .iter()
+        .map(|(key, account)| {
+            let plan = SavingsPlan::try_deserialize(&mut account.data.as_ref()).unwrap();
+            (*key, plan)
+        })
+        .collect())
+}
+
 async fn crank(
     keypair_path: String,
     user_pubkey: Pubkey,
@@ -113,25 +333,10 @@ async fn crank(
     let accounts = get_user_accounts(&user_pubkey, client, program).await?;
 
     let mut instructions = Vec::with_capacity(accounts.len());
-    for (manager, manager_account) in accounts {
-        let data = instruction::DepositInterest {}.data();
-        let accounts = accounts::DepositInterestToUser {
-            user: user_pubkey,
-            user_savings_manager: manager,
-            user_savings_vault: get_associated_token_address(&manager, &manager_account.mint),
-            interest_distributor: manager_account.distributor,
-            interest_vault: get_associated_token_address(
-                &manager_account.distributor,
-                &manager_account.mint,
-            ),
-            token_program: anchor_spl::token::ID,
-        };
-        let instruction = Instruction {
-            program_id: *program,
-            accounts: accounts.to_account_metas(None),
-            data,
-        };
-        instructions.push(instruction);
+    // Frozen vaults don't earn interest until they are unfrozen.
+    for (manager, manager_account) in accounts.into_iter().filter(|(_, m)| !m.frozen) {
+        instructions
+            .push(deposit_interest_instruction(&manager, &manager_account, client, program).await?);
     }
 
     for instruction in instructions {
@@ -159,6 +364,86 @@ async fn crank(
     Ok(())
 }
 
+/// Walks `distributor`'s registry and deposits interest into each savings vault that is due,
+/// sending one transaction per vault.
+async fn crank_registry(
+    keypair_path: String,
+    distributor: Pubkey,
+    client: &RpcClient,
+    program: &Pubkey,
+) -> Result<()> {
+    let payer = solana_sdk::signature::Keypair::read_from_file(keypair_path)
+        .map_err(|_| anyhow::anyhow!("failed reading keypair from path"))?;
+
+    // Only send transactions for vaults that are due according to the cluster's clock.
+    let clock_account = client.get_account(&solana_sdk::sysvar::clock::ID).await?;
+    let clock: Clock = solana_sdk::account::from_account(&clock_account)
+        .ok_or_else(|| anyhow::anyhow!("failed deserializing clock sysvar"))?;
+
+    let accounts = get_registered_accounts(&distributor, client, program).await?;
+    for (manager, manager_account) in accounts {
+        let due_at = manager_account.last_interest_deposit_ts + SECONDS_IN_MONTHS;
+        if manager_account.frozen || due_at > clock.unix_timestamp {
+            continue;
+        }
+
+        let instruction =
+            deposit_interest_instruction(&manager, &manager_account, client, program).await?;
+        let recent_hash = client.get_latest_blockhash().await?;
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_hash,
+        );
+
+        client
+            .send_and_confirm_transaction_with_spinner_and_config(
+                &tx,
+                CommitmentConfig::confirmed(),
+                RpcSendTransactionConfig {
+                    skip_preflight: true,
+                    ..RpcSendTransactionConfig::default()
+                },
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// The `deposit_interest` instruction paying `manager` its interest.
+async fn deposit_interest_instruction(
+    manager: &Pubkey,
+    manager_account: &SavingsManager,
+    client: &RpcClient,
+    program: &Pubkey,
+) -> Result<Instruction> {
+    let data = instruction::DepositInterest {}.data();
+    let referrer = referrer_savings_accounts(manager_account, program);
+    let accounts = accounts::DepositInterestToUser {
+        user: manager_account.user,
+        user_savings_manager: *manager,
+        user_savings_vault: get_associated_token_address(manager, &manager_account.mint),
+        interest_distributor: manager_account.distributor,
+        interest_vault: get_associated_token_address(
+            &manager_account.distributor,
+            &manager_account.mint,
+        ),
+        rate_history: rate_history(&manager_account.distributor, program),
+        rate_feed: rate_feed(&manager_account.distributor, client, program).await?,
+        referrer_savings_manager: referrer.map(|(manager, _)| manager),
+        referrer_savings_vault: referrer.map(|(_, vault)| vault),
+        fee_destination: fee_destination(&manager_account.distributor, client).await?,
+        token_program: anchor_spl::token::ID,
+    };
+    Ok(Instruction {
+        program_id: *program,
+        accounts: accounts.to_account_metas(None),
+        data,
+    })
+}
+
 async fn crank_multiple(
     keypair_path: String,
     user_pubkeys: Vec<Pubkey>,
@@ -182,6 +467,7 @@ async fn crank_multiple(
         let accounts = get_user_accounts(&user, client, program).await?;
         accounts
             .into_iter()
+            .filter(|(_, savings_manager)| !savings_manager.frozen)
             .for_each(|(savings_manager_key, savings_manager)| {
                 let savings_vault =
                     get_associated_token_address(&savings_manager_key, &savings_manager.mint);
@@ -200,7 +486,25 @@ async fn crank_multiple(
                     is_signer: false,
                     is_writable: true,
                 };
-                let extend_with = &[user_account_meta, savings_manager_meta, savings_vault_meta];
+                // The program ID stands in for the referrer's accounts when the user has no referrer.
+                let (referrer_manager_meta, referrer_vault_meta) =
+                    match referrer_savings_accounts(&savings_manager, program) {
+                        Some((manager, vault)) => (
+                            AccountMeta::new_readonly(manager, false),
+                            AccountMeta::new(vault, false),
+                        ),
+                        None => (
+                            AccountMeta::new_readonly(*program, false),
+                            AccountMeta::new_readonly(*program, false),
+                        ),
+                    };
+                let extend_with = &[
+                    user_account_meta,
+                    savings_manager_meta,
+                    savings_vault_meta,
+                    referrer_manager_meta,
+                    referrer_vault_meta,
+                ];
                 match map.entry(savings_manager.distributor) {
                     Entry::Occupied(mut entry) => {
                         entry.get_mut().1.extend_from_slice(extend_with);
@@ -219,6 +523,9 @@ async fn crank_multiple(
         let accounts = accounts::DepositInterestToMultipleUsers {
             interest_distributor: distributor,
             interest_vault: get_associated_token_address(&distributor, &mint),
+            rate_history: rate_history(&distributor, program),
+            rate_feed: rate_feed(&distributor, client, program).await?,
+            fee_destination: fee_destination(&distributor, client).await?,
             token_program: anchor_spl::token::ID,
         };
         let instruction = Instruction {
@@ -255,6 +562,286 @@ async fn crank_multiple(
     Ok(())
 }
 
+async fn crank_savings_plans(
+    keypair_path: String,
+    user_pubkey: Option<Pubkey>,
+    client: &RpcClient,
+    program: &Pubkey,
+) -> Result<()> {
+    let payer = solana_sdk::signature::Keypair::read_from_file(keypair_path)
+        .map_err(|_| anyhow::anyhow!("failed reading keypair from path"))?;
+
+    // Only send transactions for plans that are due according to the cluster's clock.
+    let clock_account = client.get_account(&solana_sdk::sysvar::clock::ID).await?;
+    let clock: Clock = solana_sdk::account::from_account(&clock_account)
+        .ok_or_else(|| anyhow::anyhow!("failed deserializing clock sysvar"))?;
+
+    let plans = get_savings_plans(user_pubkey.as_ref(), client, program).await?;
+    for (plan_key, plan) in plans {
+        if plan.next_contribution_ts > clock.unix_timestamp {
+            continue;
+        }
+
+        let manager_account = client.get_account(&plan.savings_manager).await?;
+        let manager = SavingsManager::try_deserialize(&mut manager_account.data.as_ref())?;
+        let distributor_account = client.get_account(&manager.distributor).await?;
+        let distributor =
+            InterestDistributor::try_deserialize(&mut distributor_account.data.as_ref())?;
+
+        // Distributors with a receipt mint issue receipts for contributions to the user's
+        // associated receipt token account.
+        let receipt_pool = distributor.receipt_mint.map(|_| {
+            Pubkey::find_program_address(
+                &[
+                    savings_program::RECEIPT_POOL_SEED_PREFIX,
+                    manager.distributor.as_ref(),
+                ],
+                program,
+            )
+            .0
+        });
+
+        let data = instruction::ExecuteSavingsPlan {}.data();
+        let accounts = accounts::ExecuteSavingsPlan {
+            savings_plan: plan_key,
+            savings_manager: plan.savings_manager,
+            source_token_account: plan.source_token_account,
+            savings_vault: get_associated_token_address(&plan.savings_manager, &manager.mint),
+            interest_distributor: manager.distributor,
+            allowlist_entry: allowlist_entry(&distributor, &manager.user, program),
+            receipt_mint: distributor.receipt_mint,
+            receipt_token_account: distributor
+                .receipt_mint
+                .map(|receipt_mint| get_associated_token_address(&manager.user, &receipt_mint)),
+            receipt_pool,
+            token_program: anchor_spl::token::ID,
+        };
+        let instruction = Instruction {
+            program_id: *program,
+            accounts: accounts.to_account_metas(None),
+            data,
+        };
+
+        let recent_hash = client.get_latest_blockhash().await?;
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_hash,
+        );
+
+        client
+            .send_and_confirm_transaction_with_spinner_and_config(
+                &tx,
+                CommitmentConfig::confirmed(),
+                RpcSendTransactionConfig {
+                    skip_preflight: true,
+                    ..RpcSendTransactionConfig::default()
+                },
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// A single allocation of a bonus campaign, along with its place in the campaign's merkle tree.
+struct CampaignClaim {
+    index: u32,
+    user: Pubkey,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn node_from_hex(hex: &str) -> Result<[u8; 32]> {
+    let mut node = [0; 32];
+    anyhow::ensure!(hex.len() == 64, "invalid merkle node: {hex}");
+    for (i, byte) in node.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)?;
+    }
+    Ok(node)
+}
+
+/// Reads `user,amount` rows from `csv_path`, skipping blank lines and an optional header, and
+/// returns the merkle root of the allocations along with each one's proof. Nodes without a sibling
+/// are carried up to the next level unchanged, and so add nothing to the proofs beneath them.
+fn build_campaign_tree(csv_path: &str) -> Result<([u8; 32], Vec<CampaignClaim>)> {
+    let csv = std::fs::read_to_string(csv_path)?;
+    let mut claims = Vec::new();
+    for (line_number, line) in csv.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || (line_number == 0 && line.starts_with("user")) {
+            continue;
+        }
+        let (user, amount) = line
+            .split_once(',')
+            .ok_or_else(|| anyhow::anyhow!("line {}: expected `user,amount`", line_number + 1))?;
+        claims.push(CampaignClaim {
+            index: claims.len() as u32,
+            user: user.trim().parse()?,
+            amount: amount.trim().parse()?,
+            proof: Vec::new(),
+        });
+    }
+    anyhow::ensure!(!claims.is_empty(), "no allocations in {csv_path}");
+    anyhow::ensure!(
+        claims.len() <= savings_program::MAX_CAMPAIGN_CLAIMS as usize,
+        "a campaign holds at most {} allocations",
+        savings_program::MAX_CAMPAIGN_CLAIMS
+    );
+
+    let mut level: Vec<[u8; 32]> = claims
+        .iter()
+        .map(|claim| Campaign::leaf(claim.index, &claim.user, claim.amount))
+        .collect();
+    let mut positions: Vec<usize> = (0..claims.len()).collect();
+    while level.len() > 1 {
+        for (claim, position) in claims.iter_mut().zip(positions.iter_mut()) {
+            if let Some(sibling) = level.get(*position ^ 1) {
+                claim.proof.push(*sibling);
+            }
+            *position /= 2;
+        }
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => Campaign::hash_nodes(a, b),
+                [node] => *node,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+
+    Ok((level[0], claims))
+}
+
+fn write_campaign_proofs(output_path: &str, claims: &[CampaignClaim]) -> Result<()> {
+    let mut output = String::from("index,user,amount,proof\n");
+    for claim in claims {
+        let proof: Vec<String> = claim.proof.iter().map(|node| to_hex(node)).collect();
+        output.push_str(&format!(
+            "{},{},{},{}\n",
+            claim.index,
+            claim.user,
+            claim.amount,
+            proof.join(":")
+        ));
+    }
+    std::fs::write(output_path, output)?;
+    Ok(())
+}
+
+fn read_campaign_proofs(proofs_path: &str) -> Result<Vec<CampaignClaim>> {
+    let proofs = std::fs::read_to_string(proofs_path)?;
+    proofs
+        .lines()
+        .skip(1)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let fields: Vec<&str> = line.trim().split(',').collect();
+            let [index, user, amount, proof] = fields[..] else {
+                anyhow::bail!("expected `index,user,amount,proof`: {line}");
+            };
+            Ok(CampaignClaim {
+                index: index.parse()?,
+                user: user.parse()?,
+                amount: amount.parse()?,
+                proof: proof
+                    .split(':')
+                    .filter(|node| !node.is_empty())
+                    .map(node_from_hex)
+                    .collect::<Result<_>>()?,
+            })
+        })
+        .collect()
+}
+
+async fn claim_campaign(
+    keypair_path: String,
+    campaign_key: Pubkey,
+    proofs_path: String,
+    client: &RpcClient,
+    program: &Pubkey,
+) -> Result<()> {
+    let payer = solana_sdk::signature::Keypair::read_from_file(keypair_path)
+        .map_err(|_| anyhow::anyhow!("failed reading keypair from path"))?;
+
+    let campaign_account = client.get_account(&campaign_key).await?;
+    let campaign = Campaign::try_deserialize(&mut campaign_account.data.as_ref())?;
+    let distributor_account = client.get_account(&campaign.distributor).await?;
+    let distributor = InterestDistributor::try_deserialize(&mut distributor_account.data.as_ref())?;
+
+    for claim in read_campaign_proofs(&proofs_path)? {
+        let claimed = campaign.claimed[claim.index as usize / 8] & (1 << (claim.index % 8)) != 0;
+        if claimed {
+            continue;
+        }
+
+        // Allocations are paid into the user's sub-account zero.
+        let (savings_manager, _) = Pubkey::find_program_address(
+            &[
+                savings_program::SAVINGS_MANAGER_SEED_PREFIX,
+                claim.user.as_ref(),
+                campaign.distributor.as_ref(),
+            ],
+            program,
+        );
+
+        let data = instruction::ClaimCampaign {
+            index: claim.index,
+            amount: claim.amount,
+            proof: claim.proof,
+        }
+        .data();
+        let accounts = accounts::ClaimCampaign {
+            campaign: campaign_key,
+            campaign_vault: get_associated_token_address(&campaign_key, &distributor.mint),
+            savings_manager,
+            savings_vault: get_associated_token_address(&savings_manager, &distributor.mint),
+            interest_distributor: campaign.distributor,
+            allowlist_entry: allowlist_entry(&distributor, &claim.user, program),
+            token_program: anchor_spl::token::ID,
+        };
+        let instruction = Instruction {
+            program_id: *program,
+            accounts: accounts.to_account_metas(None),
+            data,
+        };
+
+        let recent_hash = client.get_latest_blockhash().await?;
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_hash,
+        );
+
+        // Users without a savings vault can't be paid yet, so carry on with the others.
+        if let Err(err) = client
+            .send_and_confirm_transaction_with_spinner_and_config(
+                &tx,
+                CommitmentConfig::confirmed(),
+                RpcSendTransactionConfig {
+                    skip_preflight: true,
+                    ..RpcSendTransactionConfig::default()
+                },
+            )
+            .await
+        {
+            println!(
+                "Failed claiming allocation {} for {}: {err}",
+                claim.index, claim.user
+            );
+        }
+    }
+
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
@@ -270,6 +857,28 @@ async fn main() -> Result<()> {
             user_pubkeys,
             program_id,
         } => crank_multiple(keypair, user_pubkeys, &client, &program_id).await?,
+        Command::CrankRegistry {
+            keypair,
+            distributor,
+            program_id,
+        } => crank_registry(keypair, distributor, &client, &program_id).await?,
+        Command::CrankSavingsPlans {
+            keypair,
+            user_pubkey,
+            program_id,
+        } => crank_savings_plans(keypair, user_pubkey, &client, &program_id).await?,
+        Command::BuildCampaignTree { csv, output } => {
+            let (root, claims) = build_campaign_tree(&csv)?;
+            write_campaign_proofs(&output, &claims)?;
+            println!("Merkle root: {}", to_hex(&root));
+            println!("Claims: {}", claims.len());
+        }
+        Command::ClaimCampaign {
+            keypair,
+            campaign,
+            proofs,
+            program_id,
+        } => claim_campaign(keypair, campaign, proofs, &client, &program_id).await?,
     }
 
     Ok(())
@@ -0,0 +1,71 @@
+//! A minimal stand-in for an external yield program. Each depositor gets a pool, a PDA whose
+//! associated token account holds their deposits. The pool pays no yield by itself; tests simulate
+//! it by minting tokens into the pool's token account.
+#![allow(clippy::result_large_err)]
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount, Transfer};
+
+declare_id!("Ud1dSZ1neZLuLBboeT2jpNXuZHKqPMqRgVEisjtf8HG");
+
+pub const POOL_SEED_PREFIX: &[u8] = b"pool";
+
+#[program]
+pub mod mock_yield {
+    use super::*;
+
+    // Move `amount` tokens from the depositor's token account into their pool.
+    pub fn deposit(ctx: Context<MovePoolFunds>, amount: u64) -> Result<()> {
+        anchor_spl::token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.depositor_token_account.to_account_info(),
+                    to: ctx.accounts.pool_vault.to_account_info(),
+                    authority: ctx.accounts.depositor.to_account_info(),
+                },
+            ),
+            amount,
+        )
+    }
+
+    // Move `amount` tokens out of the depositor's pool back to their token account.
+    pub fn redeem(ctx: Context<MovePoolFunds>, amount: u64) -> Result<()> {
+        let depositor = ctx.accounts.depositor.key();
+        let pool_seeds = [
+            POOL_SEED_PREFIX,
+            depositor.as_ref(),
+            &[*ctx.bumps.get("pool").unwrap()],
+        ];
+
+        anchor_spl::token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.pool_vault.to_account_info(),
+                    to: ctx.accounts.depositor_token_account.to_account_info(),
+                    authority: ctx.accounts.pool.to_account_info(),
+                },
+            )
+            .with_signer(&[&pool_seeds[..]]),
+            amount,
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct MovePoolFunds<'info> {
+    pub depositor: Signer<'info>,
+    #[account(mut, token::authority = depositor)]
+    pub depositor_token_account: Account<'info, TokenAccount>,
+    /// CHECK: Only used as the authority of the pool's token account.
+    #[account(seeds = [POOL_SEED_PREFIX, depositor.key().as_ref()], bump)]
+    pub pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        associated_token::mint = depositor_token_account.mint,
+        associated_token::authority = pool,
+    )]
+    pub pool_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
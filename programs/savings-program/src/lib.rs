@@ -1,16 +1,56 @@
 #![allow(clippy::result_large_err)]
 
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::Discriminator;
 use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::spl_token::native_mint;
 use anchor_spl::token::{Mint, Token, TokenAccount, Transfer};
 
 declare_id!("BYDhC79wks4E3P5Fi5Ez4oKwS8fM1PQFVnRQLZsa4YdP");
 
 pub const SAVINGS_MANAGER_SEED_PREFIX: &[u8] = b"savings-manager";
 pub const INTEREST_DISTRIBUTOR_SEED_PREFIX: &[u8] = b"interest-distributor";
+pub const SAVINGS_PLAN_SEED_PREFIX: &[u8] = b"savings-plan";
+pub const NATIVE_WITHDRAWAL_SEED_PREFIX: &[u8] = b"native-withdrawal";
+pub const ALLOWLIST_ENTRY_SEED_PREFIX: &[u8] = b"allowlist-entry";
+pub const CONFIG_CHANGE_SEED_PREFIX: &[u8] = b"config-change";
+pub const RATE_HISTORY_SEED_PREFIX: &[u8] = b"rate-history";
+pub const RECEIPT_MINT_SEED_PREFIX: &[u8] = b"receipt-mint";
+pub const RECEIPT_POOL_SEED_PREFIX: &[u8] = b"receipt-pool";
+pub const YIELD_ADAPTER_SEED_PREFIX: &[u8] = b"yield-adapter";
+pub const APPROVED_MINT_SEED_PREFIX: &[u8] = b"approved-mint";
+pub const CAMPAIGN_SEED_PREFIX: &[u8] = b"campaign";
+pub const REGISTRY_PAGE_SEED_PREFIX: &[u8] = b"registry-page";
 
-pub const INTEREST_PERCENTAGE: u64 = 1;
+/// Layout version written to every account this program creates or migrates. Accounts created
+/// before versioning was introduced have no version byte and must be migrated before use.
+pub const ACCOUNT_VERSION: u8 = 4;
+
+/// The monthly interest rate, in basis points, that distributors start out with.
+pub const DEFAULT_INTEREST_RATE_BPS: u16 = 100;
+/// The number of rate changes a distributor's rate history remembers.
+pub const RATE_HISTORY_CAPACITY: usize = 32;
+/// The number of basis points in 100%.
+pub const MAX_BPS: u16 = 10_000;
 pub const SECONDS_IN_MONTHS: i64 = 30 * 24 * 60 * 60;
+/// The timelock delay, in seconds, that states start out with, and that states migrated from before
+/// configuration changes were timelocked are given.
+pub const DEFAULT_CONFIG_TIMELOCK_DELAY: i64 = 2 * 24 * 60 * 60;
+/// The largest number of claims a bonus campaign can hold, bounded by the size of its claimed
+/// bitmap.
+pub const MAX_CAMPAIGN_CLAIMS: u32 = 64 * 1024;
+/// The length, in seconds, of the rolling window per-user withdrawal limits apply to.
+pub const USER_WITHDRAWAL_LIMIT_WINDOW: i64 = 24 * 60 * 60;
+/// The number of buckets the amounts withdrawn during a rolling withdrawal limit window are kept
+/// in. Each bucket spans a fifth of the window, so a withdrawal counts against the limit for at
+/// least a full window and at most a fifth of a window longer.
+pub const WITHDRAWAL_WINDOW_BUCKETS: usize = 6;
+/// The number of savings-managers a single registry page holds.
+pub const REGISTRY_PAGE_CAPACITY: usize = 64;
 
 pub fn current_time() -> Result<i64> {
     Ok(anchor_lang::solana_program::sysvar::clock::Clock::get()?.unix_timestamp)
@@ -25,19 +65,47 @@ pub mod savings_program {
     //////////////////////////////////////////////////////////////////////////////////////
 
     // Initialize a state account. This state will be responsible for distributing interest tokens
-    // to (**ONLY**) user vaults that are registered to it.
+    // to (**ONLY**) user vaults that are registered to it. Configuration changes to the state are
+    // timelocked by `DEFAULT_CONFIG_TIMELOCK_DELAY` until the authority changes the delay.
     pub fn initialize_state(ctx: Context<InitializeState>, authority: Pubkey) -> Result<()> {
         ctx.accounts.state.authority = authority;
+        ctx.accounts.state.version = ACCOUNT_VERSION;
+        ctx.accounts.state.config_timelock_delay = DEFAULT_CONFIG_TIMELOCK_DELAY;
         Ok(())
     }
 
     // Register an `interest-distributor` for a mint and create an accompanying `interest-vault`.
     // Interest tokens are paid out from the vault permissionlessly at the bequest of the distributor.
-    pub fn create_interest_vault(ctx: Context<CreateInterestVaultForMint>) -> Result<()> {
+    // The mint must satisfy the state's mint policy. A state may run several products for the same
+    // mint side by side, each with its own distributor, told apart by `product_id`. The first page
+    // of the distributor's registry of savings-managers is created along with it.
+    pub fn create_interest_vault(
+        ctx: Context<CreateInterestVaultForMint>,
+        product_id: u16,
+    ) -> Result<()> {
+        require!(
+            !ctx.accounts.state.require_approved_mint || ctx.accounts.approved_mint.is_some(),
+            SavingsError::MintNotApproved
+        );
+
         let distributor = &mut ctx.accounts.interest_distributor;
         distributor.state = ctx.accounts.state.key();
         distributor.mint = ctx.accounts.mint.key();
         distributor.bump = *ctx.bumps.get("interest_distributor").unwrap();
+        distributor.version = ACCOUNT_VERSION;
+        distributor.product_id = product_id;
+        distributor.registry_pages = 1;
+
+        let registry_page = &mut ctx.accounts.registry_page;
+        registry_page.distributor = distributor.key();
+        registry_page.bump = *ctx.bumps.get("registry_page").unwrap();
+        registry_page.version = ACCOUNT_VERSION;
+
+        let rate_history = &mut ctx.accounts.rate_history;
+        rate_history.distributor = distributor.key();
+        rate_history.bump = *ctx.bumps.get("rate_history").unwrap();
+        rate_history.version = ACCOUNT_VERSION;
+        rate_history.push(DEFAULT_INTEREST_RATE_BPS, current_time()?);
 
         Ok(())
     }
@@ -69,10 +137,13 @@ pub mod savings_program {
     ) -> Result<()> {
         let state_key = ctx.accounts.interest_distributor.state;
         let mint_key = ctx.accounts.interest_distributor.mint;
+        let product_seed =
+            InterestDistributor::product_seed(ctx.accounts.interest_distributor.product_id);
         let distributor_seeds = [
             INTEREST_DISTRIBUTOR_SEED_PREFIX,
             state_key.as_ref(),
             mint_key.as_ref(),
+            &product_seed,
             &[ctx.accounts.interest_distributor.bump],
         ];
 
@@ -92,158 +163,1335 @@ pub mod savings_program {
         Ok(())
     }
 
+    // Create the distributor's receipt mint and the pool backing it. From then on, deposits made
+    // with `user_deposit` go into the pool rather than the user's savings vault and mint receipt
+    // tokens one-for-one, which can be transferred and later redeemed from the pool by whoever
+    // holds them. Pooled tokens don't earn savings interest.
+    pub fn create_receipt_mint(ctx: Context<CreateReceiptMint>) -> Result<()> {
+        ctx.accounts.interest_distributor.receipt_mint = Some(ctx.accounts.receipt_mint.key());
+        Ok(())
+    }
+
+    // Create the distributor's yield adapter, which starts out without a yield source, along with
+    // its yield vault. A source is configured through a `ConfigChange::YieldSource` change.
+    pub fn create_yield_adapter(ctx: Context<CreateYieldAdapter>) -> Result<()> {
+        let adapter = &mut ctx.accounts.yield_adapter;
+        adapter.distributor = ctx.accounts.interest_distributor.key();
+        adapter.bump = *ctx.bumps.get("yield_adapter").unwrap();
+        adapter.version = ACCOUNT_VERSION;
+
+        Ok(())
+    }
+
+    // Move `amount` tokens from the interest vault into the distributor's yield source. The tokens
+    // are staged in the adapter's yield vault, and the yield source's deposit instruction is called
+    // with the adapter as signer, so the yield program never acts for the distributor itself. The
+    // remaining accounts are passed on to the yield program as-is. Anything it leaves in the yield
+    // vault is returned to the interest vault.
+    pub fn allocate_to_yield_source<'info>(
+        ctx: Context<'_, '_, '_, 'info, ManageYieldFunds<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        let distributor = &ctx.accounts.interest_distributor;
+        let product_seed = InterestDistributor::product_seed(distributor.product_id);
+        let distributor_seeds = [
+            INTEREST_DISTRIBUTOR_SEED_PREFIX,
+            distributor.state.as_ref(),
+            distributor.mint.as_ref(),
+            &product_seed,
+            &[distributor.bump],
+        ];
+        anchor_spl::token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.interest_vault.to_account_info(),
+                    to: ctx.accounts.yield_vault.to_account_info(),
+                    authority: distributor.to_account_info(),
+                },
+            )
+            .with_signer(&[&distributor_seeds[..]]),
+            amount,
+        )?;
+
+        let deposit_ix = ctx.accounts.yield_adapter.deposit_ix;
+        invoke_yield_source(&ctx, deposit_ix, amount)?;
+        ctx.accounts.yield_vault.reload()?;
+        let leftover = ctx.accounts.yield_vault.amount;
+        return_yield_vault_funds(&ctx, leftover)?;
+
+        let moved = amount.saturating_sub(leftover);
+        let adapter = &mut ctx.accounts.yield_adapter;
+        adapter.allocated = adapter.allocated.checked_add(moved).unwrap();
+        emit!(YieldFundsMoved {
+            interest_distributor: adapter.distributor,
+            yield_program: ctx.accounts.yield_program.key(),
+            allocated: moved,
+            recalled: 0,
+            total_allocated: adapter.allocated,
+        });
+
+        Ok(())
+    }
+
+    // Pull `amount` tokens back from the distributor's yield source into the interest vault, by
+    // calling its redeem instruction with the adapter as signer. The tokens are received in the
+    // adapter's yield vault and then moved on to the interest vault. Anything received beyond the
+    // allocated principal is yield.
+    pub fn recall_from_yield_source<'info>(
+        ctx: Context<'_, '_, '_, 'info, ManageYieldFunds<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        let redeem_ix = ctx.accounts.yield_adapter.redeem_ix;
+        invoke_yield_source(&ctx, redeem_ix, amount)?;
+        ctx.accounts.yield_vault.reload()?;
+        let received = ctx.accounts.yield_vault.amount;
+        return_yield_vault_funds(&ctx, received)?;
+
+        let adapter = &mut ctx.accounts.yield_adapter;
+        adapter.allocated = adapter.allocated.saturating_sub(received);
+        emit!(YieldFundsMoved {
+            interest_distributor: adapter.distributor,
+            yield_program: ctx.accounts.yield_program.key(),
+            allocated: 0,
+            recalled: received,
+            total_allocated: adapter.allocated,
+        });
+
+        Ok(())
+    }
+
+    // Create a rate feed hosted by this program, which `publisher` keeps up to date. Distributors
+    // are pointed at it through a `ConfigChange::RateFeed` change.
+    pub fn create_rate_feed(ctx: Context<CreateRateFeed>, publisher: Pubkey) -> Result<()> {
+        let feed = &mut ctx.accounts.rate_feed;
+        feed.publisher = publisher;
+        feed.last_update_ts = current_time()?;
+        feed.version = ACCOUNT_VERSION;
+        Ok(())
+    }
+
+    // Publish the latest benchmark rate, in monthly basis points, to a hosted rate feed. Only the
+    // feed's publisher may call this.
+    pub fn publish_rate(ctx: Context<PublishRate>, rate_bps: u16) -> Result<()> {
+        require!(rate_bps <= MAX_BPS, SavingsError::InvalidBasisPoints);
+
+        let feed = &mut ctx.accounts.rate_feed;
+        feed.rate_bps = rate_bps;
+        feed.last_update_ts = current_time()?;
+
+        emit!(RatePublished {
+            rate_feed: feed.key(),
+            rate_bps,
+            timestamp: feed.last_update_ts,
+        });
+
+        Ok(())
+    }
+
+    // Queue a change to the configuration of the state or one of its distributors. The change can
+    // be executed once the state's timelock delay has passed, giving users time to react to it.
+    pub fn queue_config_change(
+        ctx: Context<QueueConfigChange>,
+        change: ConfigChange,
+    ) -> Result<()> {
+        change.validate()?;
+        require!(
+            change.targets_distributor() == ctx.accounts.interest_distributor.is_some(),
+            SavingsError::ConfigChangeTargetMismatch
+        );
+
+        let current_time = current_time()?;
+        let state = &mut ctx.accounts.state;
+        let pending_change = &mut ctx.accounts.pending_change;
+        pending_change.state = state.key();
+        pending_change.interest_distributor =
+            ctx.accounts.interest_distributor.as_ref().map(|d| d.key());
+        pending_change.id = state.next_config_change_id;
+        pending_change.change = change.clone();
+        pending_change.queued_ts = current_time;
+        pending_change.eta = current_time
+            .checked_add(state.config_timelock_delay)
+            .unwrap();
+        pending_change.bump = *ctx.bumps.get("pending_change").unwrap();
+        pending_change.version = ACCOUNT_VERSION;
+        state.next_config_change_id = state.next_config_change_id.checked_add(1).unwrap();
+
+        emit!(ConfigChangeQueued {
+            pending_change: pending_change.key(),
+            state: pending_change.state,
+            interest_distributor: pending_change.interest_distributor,
+            change,
+            eta: pending_change.eta,
+        });
+
+        Ok(())
+    }
+
+    // Apply a queued configuration change whose timelock has elapsed.
+    pub fn execute_config_change(ctx: Context<ExecuteConfigChange>) -> Result<()> {
+        let pending_change = &ctx.accounts.pending_change;
+        require!(
+            current_time()? >= pending_change.eta,
+            SavingsError::TimelockNotElapsed
+        );
+        require!(
+            pending_change.interest_distributor
+                == ctx.accounts.interest_distributor.as_ref().map(|d| d.key()),
+            SavingsError::ConfigChangeTargetMismatch
+        );
+
+        if let Some(rate_history) = &ctx.accounts.rate_history {
+            require!(
+                pending_change.interest_distributor == Some(rate_history.distributor),
+                SavingsError::ConfigChangeTargetMismatch
+            );
+        }
+        if let Some(yield_adapter) = &ctx.accounts.yield_adapter {
+            require!(
+                pending_change.interest_distributor == Some(yield_adapter.distributor),
+                SavingsError::ConfigChangeTargetMismatch
+            );
+        }
+
+        let change = pending_change.change.clone();
+        change.apply(
+            &mut ctx.accounts.state,
+            ctx.accounts.interest_distributor.as_deref_mut(),
+            ctx.accounts.rate_history.as_deref_mut(),
+            ctx.accounts.yield_adapter.as_deref_mut(),
+            current_time()?,
+        )?;
+
+        emit!(ConfigChangeExecuted {
+            pending_change: pending_change.key(),
+            state: pending_change.state,
+            interest_distributor: pending_change.interest_distributor,
+            change,
+        });
+
+        Ok(())
+    }
+
+    // Discard a queued configuration change.
+    pub fn cancel_config_change(ctx: Context<CancelConfigChange>) -> Result<()> {
+        let pending_change = &ctx.accounts.pending_change;
+        emit!(ConfigChangeCancelled {
+            pending_change: pending_change.key(),
+            state: pending_change.state,
+            interest_distributor: pending_change.interest_distributor,
+            change: pending_change.change.clone(),
+        });
+
+        Ok(())
+    }
+
+    // Freeze a user's savings vault, blocking deposits, withdrawals and interest until it is
+    // unfrozen. `reason_code` is recorded on the vault and in the emitted event for audit.
+    pub fn freeze_savings_manager(
+        ctx: Context<UpdateSavingsManagerFreeze>,
+        reason_code: u16,
+    ) -> Result<()> {
+        let manager = &mut ctx.accounts.savings_manager;
+        require!(!manager.frozen, SavingsError::SavingsManagerFrozen);
+        manager.frozen = true;
+        manager.freeze_reason_code = reason_code;
+
+        emit!(SavingsManagerFrozen {
+            savings_manager: manager.key(),
+            user: manager.user,
+            authority: ctx.accounts.authority.key(),
+            reason_code,
+            timestamp: current_time()?,
+        });
+
+        Ok(())
+    }
+
+    // Lift a freeze placed on a user's savings vault. `reason_code` is emitted for audit.
+    pub fn unfreeze_savings_manager(
+        ctx: Context<UpdateSavingsManagerFreeze>,
+        reason_code: u16,
+    ) -> Result<()> {
+        let manager = &mut ctx.accounts.savings_manager;
+        require!(manager.frozen, SavingsError::SavingsManagerNotFrozen);
+        manager.frozen = false;
+        manager.freeze_reason_code = 0;
+
+        emit!(SavingsManagerUnfrozen {
+            savings_manager: manager.key(),
+            user: manager.user,
+            authority: ctx.accounts.authority.key(),
+            reason_code,
+            timestamp: current_time()?,
+        });
+
+        Ok(())
+    }
+
+    // Allowlist a user for every gated distributor of a state until `expires_at`, or indefinitely
+    // if `expires_at` is zero.
+    pub fn add_allowlist_entry(
+        ctx: Context<AddAllowlistEntry>,
+        user: Pubkey,
+        expires_at: i64,
+    ) -> Result<()> {
+        let entry = &mut ctx.accounts.allowlist_entry;
+        entry.state = ctx.accounts.state.key();
+        entry.user = user;
+        entry.expires_at = expires_at;
+        entry.bump = *ctx.bumps.get("allowlist_entry").unwrap();
+        entry.version = ACCOUNT_VERSION;
+        Ok(())
+    }
+
+    // Remove a user from a state's allowlist, returning the entry's rent to the verifier.
+    pub fn remove_allowlist_entry(_ctx: Context<RemoveAllowlistEntry>) -> Result<()> {
+        Ok(())
+    }
+
+    // Add a mint to the list of mints interest vaults may be created for, which is enforced when
+    // the state's mint policy requires approved mints.
+    pub fn approve_mint(ctx: Context<ApproveMint>) -> Result<()> {
+        let approved_mint = &mut ctx.accounts.approved_mint;
+        approved_mint.state = ctx.accounts.state.key();
+        approved_mint.mint = ctx.accounts.mint.key();
+        approved_mint.bump = *ctx.bumps.get("approved_mint").unwrap();
+        approved_mint.version = ACCOUNT_VERSION;
+        Ok(())
+    }
+
+    // Remove a mint from the approved list, returning the entry's rent to the authority. Existing
+    // distributors for the mint are unaffected.
+    pub fn revoke_mint_approval(_ctx: Context<RevokeMintApproval>) -> Result<()> {
+        Ok(())
+    }
+
+    // Create a one-off bonus interest campaign under a distributor. Each of the campaign's
+    // `max_claims` allocations is a leaf of the merkle tree with root `merkle_root`, and is paid
+    // from the campaign's vault, which must be funded separately, into the user's savings vault.
+    // Allocations can be claimed until `expires_ts`, after which the campaign can be closed.
+    pub fn create_campaign(
+        ctx: Context<CreateCampaign>,
+        campaign_id: u64,
+        merkle_root: [u8; 32],
+        max_claims: u32,
+        expires_ts: i64,
+    ) -> Result<()> {
+        require!(
+            max_claims > 0 && max_claims <= MAX_CAMPAIGN_CLAIMS,
+            SavingsError::InvalidCampaignSize
+        );
+        require!(
+            expires_ts > current_time()?,
+            SavingsError::InvalidCampaignExpiry
+        );
+
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.distributor = ctx.accounts.interest_distributor.key();
+        campaign.campaign_id = campaign_id;
+        campaign.merkle_root = merkle_root;
+        campaign.bump = *ctx.bumps.get("campaign").unwrap();
+        campaign.version = ACCOUNT_VERSION;
+        campaign.max_claims = max_claims;
+        campaign.expires_ts = expires_ts;
+        campaign.claimed = vec![0; Campaign::bitmap_len(max_claims)];
+
+        Ok(())
+    }
+
+    // Close an expired bonus campaign. Whatever is left unclaimed in the campaign's vault is swept
+    // back to the distributor's interest vault, and the rent of the campaign and its vault is
+    // returned to the authority.
+    pub fn close_campaign(ctx: Context<CloseCampaign>) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        require!(
+            campaign.expires_ts != 0 && current_time()? >= campaign.expires_ts,
+            SavingsError::CampaignNotExpired
+        );
+
+        let campaign_seeds = &[
+            CAMPAIGN_SEED_PREFIX,
+            campaign.distributor.as_ref(),
+            &campaign.campaign_id.to_le_bytes(),
+            &[campaign.bump],
+        ];
+        let swept = ctx.accounts.campaign_vault.amount;
+        if swept > 0 {
+            anchor_spl::token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.campaign_vault.to_account_info(),
+                        to: ctx.accounts.interest_vault.to_account_info(),
+                        authority: campaign.to_account_info(),
+                    },
+                )
+                .with_signer(&[&campaign_seeds[..]]),
+                swept,
+            )?;
+        }
+        anchor_spl::token::close_account(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token::CloseAccount {
+                    account: ctx.accounts.campaign_vault.to_account_info(),
+                    destination: ctx.accounts.authority.to_account_info(),
+                    authority: campaign.to_account_info(),
+                },
+            )
+            .with_signer(&[&campaign_seeds[..]]),
+        )?;
+
+        emit!(CampaignClosed {
+            campaign: campaign.key(),
+            total_claimed: campaign.total_claimed,
+            swept,
+        });
+
+        Ok(())
+    }
+
     //////////////////////////////////////////////////////////////////////////////////////
     // USER INSTRUCTIONS.
     //////////////////////////////////////////////////////////////////////////////////////
 
     // Create a savings vault for a particular user, registered to an existing interest distributor.
-    pub fn user_create_vault(ctx: Context<UserCreateVault>) -> Result<()> {
+    // The user may name the `referrer` who brought them in, to have a share of their interest paid
+    // to the referrer's savings vault under the same distributor. Users can keep several separate
+    // vaults with a distributor as sub-accounts, each with its own `index` and optional `label`.
+    // The vault is added to `registry_page`, which may be any of the distributor's registry pages
+    // with room left.
+    pub fn user_create_vault(
+        ctx: Context<UserCreateVault>,
+        referrer: Option<Pubkey>,
+        index: u16,
+        label: [u8; 32],
+    ) -> Result<()> {
+        let current_time = current_time()?;
+        check_allowlist(
+            &ctx.accounts.interest_distributor,
+            ctx.accounts.allowlist_entry.as_ref(),
+            ctx.accounts.user.key(),
+            current_time,
+        )?;
+
         let manager = &mut ctx.accounts.savings_manager;
+        require!(
+            referrer != Some(ctx.accounts.user.key()),
+            SavingsError::SelfReferral
+        );
+
         manager.user = ctx.accounts.user.key();
         manager.mint = ctx.accounts.mint.key();
         manager.distributor = ctx.accounts.interest_distributor.key();
-        manager.last_interest_deposit_ts = current_time()?;
+        manager.last_interest_deposit_ts = current_time;
         manager.bump = *ctx.bumps.get("savings_manager").unwrap();
+        manager.version = ACCOUNT_VERSION;
+        manager.index = index;
+        manager.label = label;
+        if referrer.is_some() {
+            manager.referrer = referrer;
+            manager.referral_start_ts = current_time;
+        }
+
+        let registry_page = &mut ctx.accounts.registry_page;
+        registry_page.push(manager.key())?;
+        manager.registered = true;
+        manager.registry_page = registry_page.page;
         Ok(())
     }
 
     // Deposit tokens to a user's savings vault.
     pub fn user_deposit(ctx: Context<UserDeposit>, amount: u64) -> Result<()> {
+        let current_time = current_time()?;
+        check_allowlist(
+            &ctx.accounts.interest_distributor,
+            ctx.accounts.allowlist_entry.as_ref(),
+            ctx.accounts.user.key(),
+            current_time,
+        )?;
+
+        let receipts = receipt_accounts(
+            &ctx.accounts.interest_distributor,
+            ctx.accounts.receipt_mint.as_ref(),
+            ctx.accounts.receipt_token_account.as_ref(),
+            ctx.accounts.receipt_pool.as_ref(),
+        )?;
+        let destination = match receipts {
+            Some((_, _, pool)) => pool.to_account_info(),
+            None => ctx.accounts.savings_vault.to_account_info(),
+        };
         anchor_spl::token::transfer(
             CpiContext::new(
                 ctx.accounts.token_program.to_account_info(),
                 Transfer {
                     from: ctx.accounts.user_token_account.to_account_info(),
-                    to: ctx.accounts.savings_vault.to_account_info(),
+                    to: destination,
                     authority: ctx.accounts.user.to_account_info(),
                 },
             ),
             amount,
         )?;
 
+        if let Some((receipt_mint, receipt_token_account, _)) = receipts {
+            return mint_receipts(
+                &ctx.accounts.token_program,
+                &ctx.accounts.interest_distributor,
+                receipt_mint,
+                receipt_token_account,
+                amount,
+            );
+        }
+
+        ctx.accounts.savings_manager.last_deposit_ts = current_time;
+        ctx.accounts.savings_vault.reload()?;
+        update_savings_goal(
+            &mut ctx.accounts.savings_manager,
+            ctx.accounts.savings_vault.amount,
+            current_time,
+        );
+
         Ok(())
     }
 
-    // Withdraw tokens from a user's savings vault.
-    pub fn user_withdraw(ctx: Context<UserWithdraw>, amount: u64) -> Result<()> {
-        let manager_seeds = &[
-            SAVINGS_MANAGER_SEED_PREFIX,
-            ctx.accounts.savings_manager.user.as_ref(),
-            ctx.accounts.savings_manager.distributor.as_ref(),
-            &[ctx.accounts.savings_manager.bump],
-        ];
+    // Deposit tokens from the depositor's own token account into another user's savings vault, as
+    // an employer, parent or payroll service would. Recipients can opt out of these deposits.
+    pub fn deposit_for(ctx: Context<DepositFor>, amount: u64) -> Result<()> {
+        let current_time = current_time()?;
+        check_allowlist(
+            &ctx.accounts.interest_distributor,
+            ctx.accounts.allowlist_entry.as_ref(),
+            ctx.accounts.savings_manager.user,
+            current_time,
+        )?;
 
         anchor_spl::token::transfer(
             CpiContext::new(
                 ctx.accounts.token_program.to_account_info(),
                 Transfer {
-                    from: ctx.accounts.savings_vault.to_account_info(),
-                    to: ctx.accounts.destination_token_account.to_account_info(),
-                    authority: ctx.accounts.savings_manager.to_account_info(),
+                    from: ctx.accounts.depositor_token_account.to_account_info(),
+                    to: ctx.accounts.savings_vault.to_account_info(),
+                    authority: ctx.accounts.depositor.to_account_info(),
                 },
-            )
-            .with_signer(&[&manager_seeds[..]]),
+            ),
             amount,
         )?;
 
+        ctx.accounts.savings_manager.last_deposit_ts = current_time;
+        ctx.accounts.savings_vault.reload()?;
+        update_savings_goal(
+            &mut ctx.accounts.savings_manager,
+            ctx.accounts.savings_vault.amount,
+            current_time,
+        );
+
+        emit!(ThirdPartyDeposit {
+            savings_manager: ctx.accounts.savings_manager.key(),
+            user: ctx.accounts.savings_manager.user,
+            depositor: ctx.accounts.depositor.key(),
+            amount,
+        });
+
         Ok(())
     }
 
-    //////////////////////////////////////////////////////////////////////////////////////
-    // PERMISSIONLESS INSTRUCTIONS.
-    //////////////////////////////////////////////////////////////////////////////////////
+    // Allow or refuse deposits into a user's savings vault by anyone but the user.
+    pub fn user_set_third_party_deposits(
+        ctx: Context<UserSetThirdPartyDeposits>,
+        allowed: bool,
+    ) -> Result<()> {
+        ctx.accounts.savings_manager.third_party_deposits_disabled = !allowed;
+        Ok(())
+    }
 
-    // Permissionless instruction, intended to be called by a crank to deposit 1% interest
-    // to a user's savings account every month.
-    pub fn deposit_interest(ctx: Context<DepositInterestToUser>) -> Result<()> {
+    // Withdraw tokens from a user's savings vault. The signing authority may be the vault's owner,
+    // or its delegate as long as the withdrawal fits within the delegate's current allowance.
+    pub fn user_withdraw(ctx: Context<UserWithdraw>, amount: u64) -> Result<()> {
         let current_time = current_time()?;
-        let seconds_elapsed = current_time
-            .checked_sub(ctx.accounts.user_savings_manager.last_interest_deposit_ts)
-            .unwrap();
+        authorize_immediate_withdrawal(
+            ctx.accounts.authority.key(),
+            &mut ctx.accounts.savings_manager,
+            &ctx.accounts.interest_distributor,
+            ctx.accounts.savings_vault.amount,
+            amount,
+            current_time,
+        )?;
 
-        if seconds_elapsed < SECONDS_IN_MONTHS {
-            msg!(
-                "Crank Error: Last deposit timestamp: {}. Current timestamp: {}",
-                ctx.accounts.user_savings_manager.last_interest_deposit_ts,
-                current_time
-            );
-            return Err(SavingsError::CrankTurnedTooSoon.into());
-        }
+        withdraw_from_savings_vault(
+            &ctx.accounts.token_program,
+            &mut ctx.accounts.interest_distributor,
+            &mut ctx.accounts.savings_manager,
+            &ctx.accounts.savings_vault,
+            &ctx.accounts.interest_vault,
+            &ctx.accounts.destination_token_account,
+            ctx.accounts.authority.key(),
+            amount,
+            current_time,
+        )
+    }
 
-        let vault = &ctx.accounts.user_savings_vault;
-        let interest_amount = INTEREST_PERCENTAGE
-            .checked_mul(vault.amount)
-            .unwrap()
-            .checked_div(100)
-            .unwrap();
+    // Deposit `amount` lamports of SOL to a user's savings vault, for distributors of the native
+    // mint. The lamports are wrapped into the vault's wSOL balance.
+    pub fn user_deposit_native(ctx: Context<UserDepositNative>, amount: u64) -> Result<()> {
+        let current_time = current_time()?;
+        check_allowlist(
+            &ctx.accounts.interest_distributor,
+            ctx.accounts.allowlist_entry.as_ref(),
+            ctx.accounts.user.key(),
+            current_time,
+        )?;
 
-        if ctx.accounts.interest_vault.amount < interest_amount {
-            return Err(SavingsError::InadequateFunds.into());
-        }
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.user.to_account_info(),
+                    to: ctx.accounts.savings_vault.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        anchor_spl::token::sync_native(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            anchor_spl::token::SyncNative {
+                account: ctx.accounts.savings_vault.to_account_info(),
+            },
+        ))?;
 
-        let state_key = ctx.accounts.interest_distributor.state;
-        let mint_key = ctx.accounts.interest_distributor.mint;
-        let distributor_seeds = [
-            INTEREST_DISTRIBUTOR_SEED_PREFIX,
-            state_key.as_ref(),
-            mint_key.as_ref(),
-            &[ctx.accounts.interest_distributor.bump],
+        ctx.accounts.savings_manager.last_deposit_ts = current_time;
+        ctx.accounts.savings_vault.reload()?;
+        update_savings_goal(
+            &mut ctx.accounts.savings_manager,
+            ctx.accounts.savings_vault.amount,
+            current_time,
+        );
+
+        Ok(())
+    }
+
+    // Withdraw `amount` lamports of SOL from a user's savings vault, for distributors of the native
+    // mint. The wSOL is moved to a temporary account which is then closed, unwrapping it into
+    // lamports for the signing authority. Subject to the same rules as `user_withdraw`.
+    pub fn user_withdraw_native(ctx: Context<UserWithdrawNative>, amount: u64) -> Result<()> {
+        let current_time = current_time()?;
+        authorize_immediate_withdrawal(
+            ctx.accounts.authority.key(),
+            &mut ctx.accounts.savings_manager,
+            &ctx.accounts.interest_distributor,
+            ctx.accounts.savings_vault.amount,
+            amount,
+            current_time,
+        )?;
+
+        withdraw_from_savings_vault(
+            &ctx.accounts.token_program,
+            &mut ctx.accounts.interest_distributor,
+            &mut ctx.accounts.savings_manager,
+            &ctx.accounts.savings_vault,
+            &ctx.accounts.interest_vault,
+            &ctx.accounts.unwrap_account.to_account_info(),
+            ctx.accounts.authority.key(),
+            amount,
+            current_time,
+        )?;
+
+        let manager = &ctx.accounts.savings_manager;
+        let index_seed = SavingsManager::index_seed(manager.index);
+        let manager_seeds = &[
+            SAVINGS_MANAGER_SEED_PREFIX,
+            manager.user.as_ref(),
+            manager.distributor.as_ref(),
+            &index_seed,
+            &[manager.bump],
         ];
-        anchor_spl::token::transfer(
+        anchor_spl::token::close_account(
             CpiContext::new(
                 ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.interest_vault.to_account_info(),
-                    to: ctx.accounts.user_savings_vault.to_account_info(),
-                    authority: ctx.accounts.interest_distributor.to_account_info(),
+                anchor_spl::token::CloseAccount {
+                    account: ctx.accounts.unwrap_account.to_account_info(),
+                    destination: ctx.accounts.authority.to_account_info(),
+                    authority: manager.to_account_info(),
                 },
             )
-            .with_signer(&[&distributor_seeds[..]]),
-            interest_amount,
+            .with_signer(&[&manager_seeds[..]]),
+        )
+    }
+
+    // Move a user's whole position to a new savings vault owned by `new_user` under the same
+    // distributor, e.g. when rotating wallets. The balance, accrual timing, goal and referral carry
+    // over; the delegate does not. The new vault keeps the sub-account index and label of the old
+    // one. The old vault and savings-manager are closed and their rent returned to the old owner.
+    // The new savings-manager takes the old one's place in the distributor's registry, so
+    // `registry_page` must be the page the old one is listed on, if it is listed.
+    pub fn user_transfer_position(ctx: Context<UserTransferPosition>) -> Result<()> {
+        let current_time = current_time()?;
+        check_allowlist(
+            &ctx.accounts.interest_distributor,
+            ctx.accounts.allowlist_entry.as_ref(),
+            ctx.accounts.new_user.key(),
+            current_time,
         )?;
 
-        // Reset the last-interest-deposit-timestamp.
-        ctx.accounts.user_savings_manager.last_interest_deposit_ts = current_time;
+        let old = &ctx.accounts.savings_manager;
+        require!(
+            old.pending_withdrawal_amount == 0,
+            SavingsError::PendingWithdrawalExists
+        );
 
-        Ok(())
-    }
+        let amount = ctx.accounts.savings_vault.amount;
+        transfer_from_savings_vault(
+            &ctx.accounts.token_program,
+            old,
+            &ctx.accounts.savings_vault,
+            &ctx.accounts.new_savings_vault.to_account_info(),
+            amount,
+        )?;
 
-    // Similar to `deposit_interest`, but can deposit to multiple users in the same instruction.
-    pub fn deposit_interest_multiple<'info>(
-        ctx: Context<'_, '_, '_, 'info, DepositInterestToMultipleUsers<'info>>,
-    ) -> Result<()> {
-        // This instruction requires that the requisite accounts for each user be passed in trios
-        // from `ctx.remaining_accounts`:
-        // 1. The user's wallet,
-        // 2. The user's savings-manager account, and
-        // 3. The user's savings-vault account.
+        let index_seed = SavingsManager::index_seed(old.index);
+        let manager_seeds = &[
+            SAVINGS_MANAGER_SEED_PREFIX,
+            old.user.as_ref(),
+            old.distributor.as_ref(),
+            &index_seed,
+            &[old.bump],
+        ];
+        anchor_spl::token::close_account(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token::CloseAccount {
+                    account: ctx.accounts.savings_vault.to_account_info(),
+                    destination: ctx.accounts.user.to_account_info(),
+                    authority: old.to_account_info(),
+                },
+            )
+            .with_signer(&[&manager_seeds[..]]),
+        )?;
 
-        let distributor = &mut ctx.accounts.interest_distributor;
+        let new = &mut ctx.accounts.new_savings_manager;
+        new.user = ctx.accounts.new_user.key();
+        new.mint = old.mint;
+        new.distributor = old.distributor;
+        new.last_interest_deposit_ts = old.last_interest_deposit_ts;
+        new.bump = *ctx.bumps.get("new_savings_manager").unwrap();
+        new.version = ACCOUNT_VERSION;
+        new.goal_amount = old.goal_amount;
+        new.goal_ts = old.goal_ts;
+        new.goal_reached = old.goal_reached;
+        if old.referrer != Some(new.user) {
+            new.referrer = old.referrer;
+            new.referral_start_ts = old.referral_start_ts;
+            new.referral_bonus_paid = old.referral_bonus_paid;
+        }
+        new.last_deposit_ts = old.last_deposit_ts;
+        new.third_party_deposits_disabled = old.third_party_deposits_disabled;
+        new.withdrawal_window_start = old.withdrawal_window_start;
+        new.withdrawn_in_window = old.withdrawn_in_window;
+        new.withdrawal_buckets = old.withdrawal_buckets;
+        new.index = old.index;
+        new.label = old.label;
 
-        if ctx.remaining_accounts.len() < 3 {
-            return Err(SavingsError::ZeroRecipientsForInterestDeposit.into());
+        let registry_page = &mut ctx.accounts.registry_page;
+        if old.registered {
+            require!(
+                registry_page.page == old.registry_page,
+                SavingsError::RegistryPageMismatch
+            );
+            registry_page.remove(&old.key());
         }
+        registry_page.push(new.key())?;
+        new.registered = true;
+        new.registry_page = registry_page.page;
 
-        for chunk in ctx.remaining_accounts.chunks_exact(3) {
-            let user_wallet = &chunk[0];
-            let unchecked_savings_manager = &chunk[1];
-            let unchecked_savings_vault = &chunk[2];
+        emit!(PositionTransferred {
+            old_savings_manager: old.key(),
+            new_savings_manager: new.key(),
+            old_user: old.user,
+            new_user: new.user,
+            amount,
+            timestamp: current_time,
+        });
 
-            // Check that invariants are held for the unvalidated savings-manager account:
+        Ok(())
+    }
+
+    // Burn `amount` receipt tokens held by the signer and release as many underlying tokens from
+    // the distributor's receipt pool. Receipts are fungible, so they are only ever redeemed from the
+    // pool and never from any one user's savings vault. Redemptions count against the
+    // distributor's outflow limit.
+    pub fn redeem_receipts(ctx: Context<RedeemReceipts>, amount: u64) -> Result<()> {
+        require!(amount > 0, SavingsError::ZeroWithdrawalAmount);
+        ctx.accounts
+            .interest_distributor
+            .record_outflow(amount, current_time()?)?;
+
+        anchor_spl::token::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token::Burn {
+                    mint: ctx.accounts.receipt_mint.to_account_info(),
+                    from: ctx.accounts.holder_receipt_account.to_account_info(),
+                    authority: ctx.accounts.holder.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let distributor = &ctx.accounts.interest_distributor;
+        let product_seed = InterestDistributor::product_seed(distributor.product_id);
+        let distributor_seeds = [
+            INTEREST_DISTRIBUTOR_SEED_PREFIX,
+            distributor.state.as_ref(),
+            distributor.mint.as_ref(),
+            &product_seed,
+            &[distributor.bump],
+        ];
+        anchor_spl::token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.receipt_pool.to_account_info(),
+                    to: ctx.accounts.destination_token_account.to_account_info(),
+                    authority: distributor.to_account_info(),
+                },
+            )
+            .with_signer(&[&distributor_seeds[..]]),
+            amount,
+        )
+    }
+
+    // Give notice of a withdrawal of `amount` tokens, which can be executed once the distributor's
+    // notice period has passed. Requested tokens stop earning interest. Replaces any pending
+    // request and restarts the notice period.
+    pub fn request_withdrawal(ctx: Context<RequestWithdrawal>, amount: u64) -> Result<()> {
+        require!(amount > 0, SavingsError::ZeroWithdrawalAmount);
+
+        let manager = &mut ctx.accounts.savings_manager;
+        manager.pending_withdrawal_amount = amount;
+        manager.pending_withdrawal_ts = current_time()?;
+
+        Ok(())
+    }
+
+    // Release the tokens from a pending withdrawal request once its notice period has passed.
+    pub fn execute_withdrawal(ctx: Context<ExecuteWithdrawal>) -> Result<()> {
+        let current_time = current_time()?;
+        update_savings_goal(
+            &mut ctx.accounts.savings_manager,
+            ctx.accounts.savings_vault.amount,
+            current_time,
+        );
+        require!(
+            !ctx.accounts.savings_manager.has_active_goal(),
+            SavingsError::SavingsGoalNotReached
+        );
+
+        let manager = &mut ctx.accounts.savings_manager;
+        let amount = manager.pending_withdrawal_amount;
+        require!(amount > 0, SavingsError::NoPendingWithdrawal);
+
+        let available_at = manager
+            .pending_withdrawal_ts
+            .checked_add(ctx.accounts.interest_distributor.withdrawal_notice_period)
+            .unwrap();
+        if current_time < available_at {
+            msg!(
+                "Withdrawal available at: {}. Current timestamp: {}",
+                available_at,
+                current_time
+            );
+            return Err(SavingsError::NoticePeriodNotElapsed.into());
+        }
+
+        if ctx.accounts.authority.key() != manager.user {
+            manager.consume_delegate_allowance(amount, current_time)?;
+        }
+        manager.pending_withdrawal_amount = 0;
+        manager.pending_withdrawal_ts = 0;
+
+        withdraw_from_savings_vault(
+            &ctx.accounts.token_program,
+            &mut ctx.accounts.interest_distributor,
+            &mut ctx.accounts.savings_manager,
+            &ctx.accounts.savings_vault,
+            &ctx.accounts.interest_vault,
+            &ctx.accounts.destination_token_account,
+            ctx.accounts.authority.key(),
+            amount,
+            current_time,
+        )
+    }
+
+    // Lock a user's savings vault until it holds `target_amount` tokens or `target_ts` has passed,
+    // whichever comes first. Either target may be zero to leave it unset, but not both. A goal
+    // can't be replaced until it is reached or cancelled.
+    pub fn user_set_goal(
+        ctx: Context<UserSetGoal>,
+        target_amount: u64,
+        target_ts: i64,
+    ) -> Result<()> {
+        require!(
+            target_amount > 0 || target_ts > 0,
+            SavingsError::InvalidSavingsGoal
+        );
+        require!(
+            !ctx.accounts.savings_manager.has_active_goal(),
+            SavingsError::SavingsGoalAlreadyActive
+        );
+
+        let manager = &mut ctx.accounts.savings_manager;
+        manager.goal_amount = target_amount;
+        manager.goal_ts = target_ts;
+        manager.goal_reached = false;
+
+        Ok(())
+    }
+
+    // Remove a user's savings goal, unlocking their vault. Cancelling a goal that hasn't been
+    // reached forfeits the distributor's goal-cancellation penalty to its interest vault.
+    pub fn user_cancel_goal(ctx: Context<UserCancelGoal>) -> Result<()> {
+        update_savings_goal(
+            &mut ctx.accounts.savings_manager,
+            ctx.accounts.savings_vault.amount,
+            current_time()?,
+        );
+
+        if ctx.accounts.savings_manager.has_active_goal() {
+            let penalty = (ctx
+                .accounts
+                .interest_distributor
+                .goal_cancellation_penalty_bps as u64)
+                .checked_mul(ctx.accounts.savings_vault.amount)
+                .unwrap()
+                .checked_div(MAX_BPS as u64)
+                .unwrap();
+
+            if penalty > 0 {
+                transfer_from_savings_vault(
+                    &ctx.accounts.token_program,
+                    &ctx.accounts.savings_manager,
+                    &ctx.accounts.savings_vault,
+                    &ctx.accounts.interest_vault.to_account_info(),
+                    penalty,
+                )?;
+            }
+            msg!("Savings goal cancelled with a penalty of {}", penalty);
+        }
+
+        let manager = &mut ctx.accounts.savings_manager;
+        manager.goal_amount = 0;
+        manager.goal_ts = 0;
+        manager.goal_reached = false;
+
+        Ok(())
+    }
+
+    // Appoint a delegate that may withdraw up to `allowance` tokens from the user's savings vault
+    // every `period` seconds. Replaces any existing delegate and starts a fresh period.
+    pub fn user_set_delegate(
+        ctx: Context<UserManageDelegate>,
+        delegate: Pubkey,
+        allowance: u64,
+        period: i64,
+    ) -> Result<()> {
+        require!(period > 0, SavingsError::InvalidDelegatePeriod);
+
+        let manager = &mut ctx.accounts.savings_manager;
+        manager.delegate = Some(delegate);
+        manager.delegate_allowance = allowance;
+        manager.delegate_period = period;
+        manager.delegate_period_start = current_time()?;
+        manager.delegate_withdrawn = 0;
+
+        Ok(())
+    }
+
+    // Remove the delegate from a user's savings vault.
+    pub fn user_revoke_delegate(ctx: Context<UserManageDelegate>) -> Result<()> {
+        let manager = &mut ctx.accounts.savings_manager;
+        manager.delegate = None;
+        manager.delegate_allowance = 0;
+        manager.delegate_period = 0;
+        manager.delegate_period_start = 0;
+        manager.delegate_withdrawn = 0;
+
+        Ok(())
+    }
+
+    // Set up a recurring contribution of `amount` tokens into the user's savings vault every
+    // `interval` seconds, starting one interval from now. Contributions are pulled from
+    // `source_token_account`, which must approve the savings-manager as its delegate.
+    pub fn user_create_savings_plan(
+        ctx: Context<UserCreateSavingsPlan>,
+        amount: u64,
+        interval: i64,
+    ) -> Result<()> {
+        require!(amount > 0, SavingsError::ZeroSavingsPlanAmount);
+        require!(interval > 0, SavingsError::InvalidSavingsPlanInterval);
+
+        let plan = &mut ctx.accounts.savings_plan;
+        plan.user = ctx.accounts.user.key();
+        plan.savings_manager = ctx.accounts.savings_manager.key();
+        plan.source_token_account = ctx.accounts.source_token_account.key();
+        plan.amount = amount;
+        plan.interval = interval;
+        plan.next_contribution_ts = current_time()?.checked_add(interval).unwrap();
+        plan.bump = *ctx.bumps.get("savings_plan").unwrap();
+        plan.version = ACCOUNT_VERSION;
+
+        Ok(())
+    }
+
+    // Stop a user's recurring contributions and reclaim the savings plan's rent.
+    pub fn user_close_savings_plan(_ctx: Context<UserCloseSavingsPlan>) -> Result<()> {
+        Ok(())
+    }
+
+    //////////////////////////////////////////////////////////////////////////////////////
+    // PERMISSIONLESS INSTRUCTIONS.
+    //////////////////////////////////////////////////////////////////////////////////////
+
+    // Append a page to a distributor's registry of savings-managers, for when its existing pages
+    // are full. Distributors created before registries were introduced start with no pages, and
+    // get their first one through this instruction.
+    pub fn create_registry_page(ctx: Context<CreateRegistryPage>) -> Result<()> {
+        let distributor = &mut ctx.accounts.interest_distributor;
+        let registry_page = &mut ctx.accounts.registry_page;
+        registry_page.distributor = distributor.key();
+        registry_page.page = distributor.registry_pages;
+        registry_page.bump = *ctx.bumps.get("registry_page").unwrap();
+        registry_page.version = ACCOUNT_VERSION;
+        distributor.registry_pages = distributor.registry_pages.checked_add(1).unwrap();
+
+        Ok(())
+    }
+
+    // Permissionless instruction, intended to be called by a crank to pull a due contribution
+    // into a user's savings vault. If several intervals were missed, only a single contribution is
+    // made and the plan is rescheduled for the next interval boundary after the current time.
+    // Contributions are deposits like any other: the user must be allowlisted where required, and
+    // distributors with a receipt mint take them into their receipt pool and issue receipts to the
+    // user's associated receipt token account.
+    pub fn execute_savings_plan(ctx: Context<ExecuteSavingsPlan>) -> Result<()> {
+        let current_time = current_time()?;
+        check_allowlist(
+            &ctx.accounts.interest_distributor,
+            ctx.accounts.allowlist_entry.as_ref(),
+            ctx.accounts.savings_manager.user,
+            current_time,
+        )?;
+        let plan = &mut ctx.accounts.savings_plan;
+
+        if current_time < plan.next_contribution_ts {
+            msg!(
+                "Crank Error: Next contribution timestamp: {}. Current timestamp: {}",
+                plan.next_contribution_ts,
+                current_time
+            );
+            return Err(SavingsError::CrankTurnedTooSoon.into());
+        }
+
+        let intervals_elapsed = current_time
+            .checked_sub(plan.next_contribution_ts)
+            .unwrap()
+            .checked_div(plan.interval)
+            .unwrap()
+            .checked_add(1)
+            .unwrap();
+        plan.next_contribution_ts = plan
+            .next_contribution_ts
+            .checked_add(intervals_elapsed.checked_mul(plan.interval).unwrap())
+            .unwrap();
+
+        let receipts = receipt_accounts(
+            &ctx.accounts.interest_distributor,
+            ctx.accounts.receipt_mint.as_ref(),
+            ctx.accounts.receipt_token_account.as_ref(),
+            ctx.accounts.receipt_pool.as_ref(),
+        )?;
+        let manager = &ctx.accounts.savings_manager;
+        let destination = match receipts {
+            Some((receipt_mint, receipt_token_account, pool)) => {
+                require_keys_eq!(
+                    receipt_token_account.key(),
+                    anchor_spl::associated_token::get_associated_token_address(
+                        &manager.user,
+                        &receipt_mint.key()
+                    ),
+                    SavingsError::MissingReceiptMint
+                );
+                pool.to_account_info()
+            }
+            None => ctx.accounts.savings_vault.to_account_info(),
+        };
+
+        let index_seed = SavingsManager::index_seed(manager.index);
+        let manager_seeds = &[
+            SAVINGS_MANAGER_SEED_PREFIX,
+            manager.user.as_ref(),
+            manager.distributor.as_ref(),
+            &index_seed,
+            &[manager.bump],
+        ];
+        anchor_spl::token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.source_token_account.to_account_info(),
+                    to: destination,
+                    authority: manager.to_account_info(),
+                },
+            )
+            .with_signer(&[&manager_seeds[..]]),
+            plan.amount,
+        )?;
+
+        if let Some((receipt_mint, receipt_token_account, _)) = receipts {
+            return mint_receipts(
+                &ctx.accounts.token_program,
+                &ctx.accounts.interest_distributor,
+                receipt_mint,
+                receipt_token_account,
+                plan.amount,
+            );
+        }
+
+        ctx.accounts.savings_manager.last_deposit_ts = current_time;
+        ctx.accounts.savings_vault.reload()?;
+        update_savings_goal(
+            &mut ctx.accounts.savings_manager,
+            ctx.accounts.savings_vault.amount,
+            current_time,
+        );
+
+        Ok(())
+    }
+
+    // Permissionless instruction to pay a user's bonus campaign allocation into their savings vault.
+    // The allocation is leaf `index` of the campaign's merkle tree, proven by `proof`, and can only
+    // be claimed once, before the campaign expires. The user must be allowlisted if the distributor
+    // requires it.
+    pub fn claim_campaign(
+        ctx: Context<ClaimCampaign>,
+        index: u32,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let current_time = current_time()?;
+        check_allowlist(
+            &ctx.accounts.interest_distributor,
+            ctx.accounts.allowlist_entry.as_ref(),
+            ctx.accounts.savings_manager.user,
+            current_time,
+        )?;
+        let campaign = &mut ctx.accounts.campaign;
+        require!(
+            campaign.expires_ts == 0 || current_time < campaign.expires_ts,
+            SavingsError::CampaignExpired
+        );
+        let leaf = Campaign::leaf(index, &ctx.accounts.savings_manager.user, amount);
+        require!(
+            campaign.verify(leaf, &proof),
+            SavingsError::InvalidCampaignProof
+        );
+        campaign.set_claimed(index)?;
+        campaign.total_claimed = campaign.total_claimed.checked_add(amount).unwrap();
+
+        let campaign_seeds = &[
+            CAMPAIGN_SEED_PREFIX,
+            campaign.distributor.as_ref(),
+            &campaign.campaign_id.to_le_bytes(),
+            &[campaign.bump],
+        ];
+        anchor_spl::token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.campaign_vault.to_account_info(),
+                    to: ctx.accounts.savings_vault.to_account_info(),
+                    authority: campaign.to_account_info(),
+                },
+            )
+            .with_signer(&[&campaign_seeds[..]]),
+            amount,
+        )?;
+
+        emit!(CampaignClaimed {
+            campaign: campaign.key(),
+            savings_manager: ctx.accounts.savings_manager.key(),
+            index,
+            amount,
+        });
+
+        ctx.accounts.savings_vault.reload()?;
+        update_savings_goal(
+            &mut ctx.accounts.savings_manager,
+            ctx.accounts.savings_vault.amount,
+            current_time,
+        );
+
+        Ok(())
+    }
+
+    // Permissionless instruction, intended to be called by a crank to deposit the interest a
+    // user's savings account has accrued, at most once a month.
+    pub fn deposit_interest(ctx: Context<DepositInterestToUser>) -> Result<()> {
+        let current_time = current_time()?;
+        let seconds_elapsed = current_time
+            .checked_sub(ctx.accounts.user_savings_manager.last_interest_deposit_ts)
+            .unwrap();
+
+        if seconds_elapsed < SECONDS_IN_MONTHS {
+            msg!(
+                "Crank Error: Last deposit timestamp: {}. Current timestamp: {}",
+                ctx.accounts.user_savings_manager.last_interest_deposit_ts,
+                current_time
+            );
+            return Err(SavingsError::CrankTurnedTooSoon.into());
+        }
+
+        sync_rate_feed(
+            &mut ctx.accounts.rate_history,
+            ctx.accounts.rate_feed.as_ref(),
+            current_time,
+        )?;
+
+        let vault = &ctx.accounts.user_savings_vault;
+        let interest_amount = ctx.accounts.rate_history.accrued_interest(
+            ctx.accounts
+                .user_savings_manager
+                .interest_bearing_balance(vault.amount),
+            ctx.accounts.user_savings_manager.last_interest_deposit_ts,
+            current_time,
+        )?;
+
+        if ctx.accounts.interest_vault.amount < interest_amount {
+            return Err(SavingsError::InadequateFunds.into());
+        }
+
+        let fee = pay_performance_fee(
+            &ctx.accounts.token_program,
+            &mut ctx.accounts.interest_distributor,
+            &ctx.accounts.interest_vault,
+            ctx.accounts
+                .fee_destination
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            ctx.accounts.user_savings_manager.key(),
+            interest_amount,
+        )?;
+        let interest_amount = interest_amount.checked_sub(fee).unwrap();
+
+        let state_key = ctx.accounts.interest_distributor.state;
+        let mint_key = ctx.accounts.interest_distributor.mint;
+        let product_seed =
+            InterestDistributor::product_seed(ctx.accounts.interest_distributor.product_id);
+        let distributor_seeds = [
+            INTEREST_DISTRIBUTOR_SEED_PREFIX,
+            state_key.as_ref(),
+            mint_key.as_ref(),
+            &product_seed,
+            &[ctx.accounts.interest_distributor.bump],
+        ];
+        anchor_spl::token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.interest_vault.to_account_info(),
+                    to: ctx.accounts.user_savings_vault.to_account_info(),
+                    authority: ctx.accounts.interest_distributor.to_account_info(),
+                },
+            )
+            .with_signer(&[&distributor_seeds[..]]),
+            interest_amount,
+        )?;
+
+        // Reset the last-interest-deposit-timestamp.
+        ctx.accounts.user_savings_manager.last_interest_deposit_ts = current_time;
+
+        pay_referral_bonus(
+            &ctx.accounts.token_program,
+            &ctx.accounts.interest_distributor,
+            &mut ctx.accounts.interest_vault,
+            &mut ctx.accounts.user_savings_manager,
+            ctx.accounts
+                .referrer_savings_manager
+                .as_ref()
+                .map(|manager| manager.to_account_info()),
+            ctx.accounts
+                .referrer_savings_vault
+                .as_ref()
+                .map(|vault| vault.to_account_info()),
+            interest_amount,
+            current_time,
+        )?;
+
+        ctx.accounts.user_savings_vault.reload()?;
+        update_savings_goal(
+            &mut ctx.accounts.user_savings_manager,
+            ctx.accounts.user_savings_vault.amount,
+            current_time,
+        );
+
+        Ok(())
+    }
+
+    // Similar to `deposit_interest`, but can deposit to multiple users in the same instruction.
+    // Frozen savings vaults in the batch are skipped rather than failing it.
+    pub fn deposit_interest_multiple<'info>(
+        ctx: Context<'_, '_, '_, 'info, DepositInterestToMultipleUsers<'info>>,
+    ) -> Result<()> {
+        // This instruction requires that the requisite accounts for each user be passed in groups
+        // of five from `ctx.remaining_accounts`:
+        // 1. The user's wallet,
+        // 2. The user's savings-manager account,
+        // 3. The user's savings-vault account,
+        // 4. The savings-manager of the user's referrer, or this program's ID if they have none, and
+        // 5. The savings-vault of the user's referrer, or this program's ID if they have none.
+
+        let distributor = &mut ctx.accounts.interest_distributor;
+
+        if ctx.remaining_accounts.len() < 5 {
+            return Err(SavingsError::ZeroRecipientsForInterestDeposit.into());
+        }
+
+        sync_rate_feed(
+            &mut ctx.accounts.rate_history,
+            ctx.accounts.rate_feed.as_ref(),
+            current_time()?,
+        )?;
+
+        for chunk in ctx.remaining_accounts.chunks_exact(5) {
+            let user_wallet = &chunk[0];
+            let unchecked_savings_manager = &chunk[1];
+            let unchecked_savings_vault = &chunk[2];
+            let referrer_savings_manager = (*chunk[3].key != crate::ID).then(|| chunk[3].clone());
+            let referrer_savings_vault = (*chunk[4].key != crate::ID).then(|| chunk[4].clone());
+
+            // Check that invariants are held for the unvalidated savings-manager account:
+            let mut savings_manager =
+                Account::<'info, SavingsManager>::try_from(unchecked_savings_manager)?;
             let (derived_savings_manager, _) = Pubkey::find_program_address(
                 &[
                     SAVINGS_MANAGER_SEED_PREFIX,
                     user_wallet.key().as_ref(),
                     distributor.key().as_ref(),
+                    &SavingsManager::index_seed(savings_manager.index),
                 ],
                 &crate::ID,
             );
             require_keys_eq!(derived_savings_manager, *unchecked_savings_manager.key);
-            let mut savings_manager =
-                Account::<'info, SavingsManager>::try_from(unchecked_savings_manager)?;
+            // A frozen vault is passed over rather than failing the rest of the batch.
+            if savings_manager.frozen {
+                emit!(FrozenSavingsManagerSkipped {
+                    savings_manager: savings_manager.key(),
+                    user: savings_manager.user,
+                });
+                continue;
+            }
 
             // Check that invariants are held for the unvalidated savings-vault account.
-            let savings_vault = Account::<'info, TokenAccount>::try_from(&chunk[2])?;
+            let mut savings_vault = Account::<'info, TokenAccount>::try_from(&chunk[2])?;
             let associated_token_address =
                 anchor_spl::associated_token::get_associated_token_address(
                     &savings_manager.key(),
@@ -267,22 +1515,37 @@ pub mod savings_program {
                 return Err(SavingsError::CrankTurnedTooSoon.into());
             }
 
-            let interest_amount = INTEREST_PERCENTAGE
-                .checked_mul(savings_vault.amount)
-                .unwrap()
-                .checked_div(100)
-                .unwrap();
+            let interest_amount = ctx.accounts.rate_history.accrued_interest(
+                savings_manager.interest_bearing_balance(savings_vault.amount),
+                savings_manager.last_interest_deposit_ts,
+                current_time,
+            )?;
 
             if ctx.accounts.interest_vault.amount < interest_amount {
                 return Err(SavingsError::InadequateFunds.into());
             }
 
+            let fee = pay_performance_fee(
+                &ctx.accounts.token_program,
+                distributor,
+                &ctx.accounts.interest_vault,
+                ctx.accounts
+                    .fee_destination
+                    .as_ref()
+                    .map(|account| account.to_account_info()),
+                savings_manager.key(),
+                interest_amount,
+            )?;
+            let interest_amount = interest_amount.checked_sub(fee).unwrap();
+
             let state_key = distributor.state;
             let mint_key = distributor.mint;
+            let product_seed = InterestDistributor::product_seed(distributor.product_id);
             let distributor_seeds = [
                 INTEREST_DISTRIBUTOR_SEED_PREFIX,
                 state_key.as_ref(),
                 mint_key.as_ref(),
+                &product_seed,
                 &[distributor.bump],
             ];
 
@@ -301,19 +1564,713 @@ pub mod savings_program {
 
             // Reset the last-interest-deposit-timestamp.
             savings_manager.last_interest_deposit_ts = current_time;
+
+            pay_referral_bonus(
+                &ctx.accounts.token_program,
+                distributor,
+                &mut ctx.accounts.interest_vault,
+                &mut savings_manager,
+                referrer_savings_manager,
+                referrer_savings_vault,
+                interest_amount,
+                current_time,
+            )?;
+
+            savings_vault.reload()?;
+            update_savings_goal(&mut savings_manager, savings_vault.amount, current_time);
+
+            // Accounts deserialized from `remaining_accounts` aren't persisted automatically.
+            savings_manager.exit(&crate::ID)?;
         }
 
         Ok(())
     }
-}
 
-//////////////////////////////////////////
-// CONTEXT FOR USER INSTRUCTIONS:
-/////////////////////////////////////////
+    //////////////////////////////////////////////////////////////////////////////////////
+    // VIEW INSTRUCTIONS.
+    //////////////////////////////////////////////////////////////////////////////////////
 
-#[derive(Accounts)]
-pub struct UserCreateVault<'info> {
-    #[account(mut)]
+    // Read-only instruction, meant to be simulated rather than sent, returning what a savings
+    // vault's next interest payout will look like. The payout is projected at the current rate,
+    // including any pending rate feed update, from the vault's current balance.
+    pub fn preview_interest(ctx: Context<PreviewInterest>) -> Result<InterestPreview> {
+        let current_time = current_time()?;
+        let manager = &ctx.accounts.savings_manager;
+        let distributor = &ctx.accounts.interest_distributor;
+
+        let mut rate_history = (*ctx.accounts.rate_history).clone();
+        sync_rate_feed(
+            &mut rate_history,
+            ctx.accounts.rate_feed.as_ref(),
+            current_time,
+        )?;
+
+        let next_payout_ts = manager
+            .last_interest_deposit_ts
+            .checked_add(SECONDS_IN_MONTHS)
+            .unwrap();
+        let gross_interest = rate_history.accrued_interest(
+            manager.interest_bearing_balance(ctx.accounts.savings_vault.amount),
+            manager.last_interest_deposit_ts,
+            next_payout_ts.max(current_time),
+        )?;
+        let interest_amount = gross_interest
+            .checked_sub(distributor.performance_fee(gross_interest))
+            .unwrap();
+        let referral_bonus =
+            distributor.referral_bonus(manager, interest_amount, next_payout_ts.max(current_time));
+
+        Ok(InterestPreview {
+            next_payout_ts,
+            projected_interest: interest_amount,
+            interest_vault_sufficient: ctx.accounts.interest_vault.amount
+                >= gross_interest.checked_add(referral_bonus).unwrap(),
+            current_rate_bps: rate_history.current_rate(),
+        })
+    }
+
+    //////////////////////////////////////////////////////////////////////////////////////
+    // MIGRATION INSTRUCTIONS.
+    //////////////////////////////////////////////////////////////////////////////////////
+
+    // Create the rate history of a distributor registered before rate histories were introduced.
+    // Its interest rate is taken to have been the default one since the distributor's creation.
+    pub fn create_rate_history(ctx: Context<CreateRateHistory>) -> Result<()> {
+        let rate_history = &mut ctx.accounts.rate_history;
+        rate_history.distributor = ctx.accounts.interest_distributor.key();
+        rate_history.bump = *ctx.bumps.get("rate_history").unwrap();
+        rate_history.version = ACCOUNT_VERSION;
+        rate_history.push(DEFAULT_INTEREST_RATE_BPS, 0);
+
+        Ok(())
+    }
+
+    // Add a savings-manager created before registries were introduced to its distributor's
+    // registry, so cranks walking the registry find it.
+    pub fn register_savings_manager(ctx: Context<RegisterSavingsManager>) -> Result<()> {
+        let manager = &mut ctx.accounts.savings_manager;
+        let registry_page = &mut ctx.accounts.registry_page;
+        registry_page.push(manager.key())?;
+        manager.registered = true;
+        manager.registry_page = registry_page.page;
+
+        Ok(())
+    }
+
+    // Permissionless instructions that grow an account created under an older layout to the
+    // current one and stamp it with `ACCOUNT_VERSION`. Any added space is zero-filled and the
+    // payer covers the extra rent. States without a timelock delay are given the default one.
+    pub fn migrate_state(ctx: Context<MigrateState>) -> Result<()> {
+        realloc_account::<State>(
+            &ctx.accounts.state,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            State::SPACE,
+        )?;
+
+        let mut state = Account::<State>::try_from(&ctx.accounts.state)?;
+        require!(
+            state.version < ACCOUNT_VERSION,
+            SavingsError::AccountAlreadyMigrated
+        );
+        if state.config_timelock_delay == 0 {
+            state.config_timelock_delay = DEFAULT_CONFIG_TIMELOCK_DELAY;
+        }
+        state.version = ACCOUNT_VERSION;
+        state.exit(&crate::ID)?;
+
+        Ok(())
+    }
+
+    pub fn migrate_interest_distributor(ctx: Context<MigrateInterestDistributor>) -> Result<()> {
+        realloc_account::<InterestDistributor>(
+            &ctx.accounts.interest_distributor,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            InterestDistributor::SPACE,
+        )?;
+
+        let mut distributor =
+            Account::<InterestDistributor>::try_from(&ctx.accounts.interest_distributor)?;
+        require!(
+            distributor.version < ACCOUNT_VERSION,
+            SavingsError::AccountAlreadyMigrated
+        );
+        distributor.version = ACCOUNT_VERSION;
+        distributor.exit(&crate::ID)?;
+
+        Ok(())
+    }
+
+    pub fn migrate_savings_manager(ctx: Context<MigrateSavingsManager>) -> Result<()> {
+        realloc_account::<SavingsManager>(
+            &ctx.accounts.savings_manager,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            SavingsManager::SPACE,
+        )?;
+
+        let mut manager = Account::<SavingsManager>::try_from(&ctx.accounts.savings_manager)?;
+        require!(
+            manager.version < ACCOUNT_VERSION,
+            SavingsError::AccountAlreadyMigrated
+        );
+        manager.version = ACCOUNT_VERSION;
+        manager.exit(&crate::ID)?;
+
+        Ok(())
+    }
+}
+
+/// Marks a savings-manager's goal as reached if `balance` or `now` meets its target, emitting
+/// a [`GoalReached`] event the first time it does.
+fn update_savings_goal(manager: &mut Account<SavingsManager>, balance: u64, now: i64) {
+    if manager.has_active_goal() && manager.goal_met(balance, now) {
+        manager.goal_reached = true;
+        emit!(GoalReached {
+            savings_manager: manager.key(),
+            user: manager.user,
+            balance,
+            timestamp: now,
+        });
+    }
+}
+
+/// Sends the distributor's performance fee on `gross_interest` from the interest vault to its fee
+/// destination, returning the fee. `fee_destination` is only required when a fee is due.
+fn pay_performance_fee<'info>(
+    token_program: &Program<'info, Token>,
+    distributor: &mut Account<'info, InterestDistributor>,
+    interest_vault: &Account<'info, TokenAccount>,
+    fee_destination: Option<AccountInfo<'info>>,
+    savings_manager: Pubkey,
+    gross_interest: u64,
+) -> Result<u64> {
+    let fee = distributor.performance_fee(gross_interest);
+    if fee == 0 {
+        return Ok(0);
+    }
+
+    let fee_destination = fee_destination
+        .filter(|account| Some(account.key()) == distributor.fee_destination)
+        .ok_or(SavingsError::MissingFeeDestination)?;
+
+    let product_seed = InterestDistributor::product_seed(distributor.product_id);
+    let distributor_seeds = [
+        INTEREST_DISTRIBUTOR_SEED_PREFIX,
+        distributor.state.as_ref(),
+        distributor.mint.as_ref(),
+        &product_seed,
+        &[distributor.bump],
+    ];
+    anchor_spl::token::transfer(
+        CpiContext::new(
+            token_program.to_account_info(),
+            Transfer {
+                from: interest_vault.to_account_info(),
+                to: fee_destination,
+                authority: distributor.to_account_info(),
+            },
+        )
+        .with_signer(&[&distributor_seeds[..]]),
+        fee,
+    )?;
+
+    distributor.total_performance_fees =
+        distributor.total_performance_fees.checked_add(fee).unwrap();
+
+    emit!(PerformanceFeePaid {
+        interest_distributor: distributor.key(),
+        savings_manager,
+        gross_interest,
+        fee,
+        total_fees: distributor.total_performance_fees,
+    });
+
+    Ok(fee)
+}
+
+/// Pays the referrer of `manager` their share of an `interest_amount` payout, out of the interest
+/// vault and on top of the interest itself, into the referrer's sub-account zero. Nothing is paid if
+/// the user has no referrer, or the referral has expired or reached its cap. The referrer's
+/// savings-manager and vault are only required when a bonus is due. If the referrer has closed or
+/// never opened that vault, or it is frozen, the bonus is skipped rather than failing the payout.
+#[allow(clippy::too_many_arguments)]
+fn pay_referral_bonus<'info>(
+    token_program: &Program<'info, Token>,
+    distributor: &Account<'info, InterestDistributor>,
+    interest_vault: &mut Account<'info, TokenAccount>,
+    manager: &mut Account<'info, SavingsManager>,
+    referrer_savings_manager: Option<AccountInfo<'info>>,
+    referrer_savings_vault: Option<AccountInfo<'info>>,
+    interest_amount: u64,
+    now: i64,
+) -> Result<()> {
+    let Some(referrer) = manager.referrer else {
+        return Ok(());
+    };
+    let bonus = distributor.referral_bonus(manager, interest_amount, now);
+    if bonus == 0 {
+        return Ok(());
+    }
+
+    let (Some(referrer_savings_manager), Some(referrer_savings_vault)) =
+        (referrer_savings_manager, referrer_savings_vault)
+    else {
+        return Err(SavingsError::MissingReferrerSavingsVault.into());
+    };
+    let (derived_referrer_savings_manager, _) = Pubkey::find_program_address(
+        &[
+            SAVINGS_MANAGER_SEED_PREFIX,
+            referrer.as_ref(),
+            distributor.key().as_ref(),
+        ],
+        &crate::ID,
+    );
+    require_keys_eq!(
+        derived_referrer_savings_manager,
+        *referrer_savings_manager.key,
+        SavingsError::MissingReferrerSavingsVault
+    );
+    require_keys_eq!(
+        anchor_spl::associated_token::get_associated_token_address(
+            &derived_referrer_savings_manager,
+            &distributor.mint
+        ),
+        *referrer_savings_vault.key,
+        SavingsError::MissingReferrerSavingsVault
+    );
+
+    // Closed accounts are left with no data and owned by the system program.
+    let referrer_unavailable = match Account::<SavingsManager>::try_from(&referrer_savings_manager)
+    {
+        Ok(referrer_manager) => referrer_manager.frozen,
+        Err(_) => true,
+    };
+    if referrer_unavailable || *referrer_savings_vault.owner != anchor_spl::token::ID {
+        emit!(ReferralBonusSkipped {
+            savings_manager: manager.key(),
+            referrer,
+            amount: bonus,
+        });
+        return Ok(());
+    }
+
+    interest_vault.reload()?;
+    if interest_vault.amount < bonus {
+        return Err(SavingsError::InadequateFunds.into());
+    }
+
+    let product_seed = InterestDistributor::product_seed(distributor.product_id);
+    let distributor_seeds = [
+        INTEREST_DISTRIBUTOR_SEED_PREFIX,
+        distributor.state.as_ref(),
+        distributor.mint.as_ref(),
+        &product_seed,
+        &[distributor.bump],
+    ];
+    anchor_spl::token::transfer(
+        CpiContext::new(
+            token_program.to_account_info(),
+            Transfer {
+                from: interest_vault.to_account_info(),
+                to: referrer_savings_vault,
+                authority: distributor.to_account_info(),
+            },
+        )
+        .with_signer(&[&distributor_seeds[..]]),
+        bonus,
+    )?;
+
+    manager.referral_bonus_paid = manager.referral_bonus_paid.checked_add(bonus).unwrap();
+    emit!(ReferralBonusPaid {
+        savings_manager: manager.key(),
+        referrer,
+        amount: bonus,
+    });
+
+    Ok(())
+}
+
+/// Moves a rolling withdrawal limit window of `window` seconds, kept as `buckets`, forward to
+/// `now`. Buckets that have fallen out of the window are cleared, and `newest_bucket_start` becomes
+/// the start of the bucket `now` falls in. Returns the index of that bucket and the total amount
+/// still inside the window.
+fn roll_withdrawal_window(
+    buckets: &mut [u64; WITHDRAWAL_WINDOW_BUCKETS],
+    newest_bucket_start: &mut i64,
+    window: i64,
+    now: i64,
+) -> (usize, u64) {
+    // The window is covered by all but one of the buckets, rounding up, so that the oldest bucket
+    // only expires once all of it is more than a window old.
+    let span = WITHDRAWAL_WINDOW_BUCKETS as i64 - 1;
+    let bucket_len = window.checked_sub(1).unwrap().checked_div(span).unwrap() + 1;
+    let newest = newest_bucket_start.div_euclid(bucket_len);
+    let current = now.div_euclid(bucket_len);
+
+    let expired = current
+        .checked_sub(newest)
+        .unwrap()
+        .clamp(0, WITHDRAWAL_WINDOW_BUCKETS as i64);
+    for bucket in newest + 1..=newest + expired {
+        buckets[bucket.rem_euclid(WITHDRAWAL_WINDOW_BUCKETS as i64) as usize] = 0;
+    }
+    *newest_bucket_start = current.checked_mul(bucket_len).unwrap();
+
+    let total = buckets
+        .iter()
+        .fold(0u64, |total, amount| total.checked_add(*amount).unwrap());
+    (
+        current.rem_euclid(WITHDRAWAL_WINDOW_BUCKETS as i64) as usize,
+        total,
+    )
+}
+
+/// The receipt mint, receipt token account and receipt pool of a deposit that is issued receipts.
+type ReceiptAccounts<'a, 'info> = (
+    &'a Account<'info, Mint>,
+    &'a UncheckedAccount<'info>,
+    &'a Account<'info, TokenAccount>,
+);
+
+/// The receipt accounts a deposit into one of `distributor`'s savings vaults goes through, or
+/// `None` if the distributor doesn't issue receipts. Fails if the distributor issues receipts but
+/// they weren't all provided.
+fn receipt_accounts<'a, 'info>(
+    distributor: &InterestDistributor,
+    receipt_mint: Option<&'a Account<'info, Mint>>,
+    receipt_token_account: Option<&'a UncheckedAccount<'info>>,
+    receipt_pool: Option<&'a Account<'info, TokenAccount>>,
+) -> Result<Option<ReceiptAccounts<'a, 'info>>> {
+    let Some(expected_mint) = distributor.receipt_mint else {
+        return Ok(None);
+    };
+    let (Some(receipt_mint), Some(receipt_token_account), Some(receipt_pool)) =
+        (receipt_mint, receipt_token_account, receipt_pool)
+    else {
+        return Err(SavingsError::MissingReceiptMint.into());
+    };
+    require_keys_eq!(
+        receipt_mint.key(),
+        expected_mint,
+        SavingsError::MissingReceiptMint
+    );
+    Ok(Some((receipt_mint, receipt_token_account, receipt_pool)))
+}
+
+/// Mints `amount` of `distributor`'s receipt tokens to `destination`, for a deposit just taken
+/// into its receipt pool.
+fn mint_receipts<'info>(
+    token_program: &Program<'info, Token>,
+    distributor: &Account<'info, InterestDistributor>,
+    receipt_mint: &Account<'info, Mint>,
+    destination: &UncheckedAccount<'info>,
+    amount: u64,
+) -> Result<()> {
+    let product_seed = InterestDistributor::product_seed(distributor.product_id);
+    let distributor_seeds = [
+        INTEREST_DISTRIBUTOR_SEED_PREFIX,
+        distributor.state.as_ref(),
+        distributor.mint.as_ref(),
+        &product_seed,
+        &[distributor.bump],
+    ];
+    anchor_spl::token::mint_to(
+        CpiContext::new(
+            token_program.to_account_info(),
+            anchor_spl::token::MintTo {
+                mint: receipt_mint.to_account_info(),
+                to: destination.to_account_info(),
+                authority: distributor.to_account_info(),
+            },
+        )
+        .with_signer(&[&distributor_seeds[..]]),
+        amount,
+    )
+}
+
+/// Checks that `user` holds a valid, unexpired allowlist entry if `distributor` requires one.
+fn check_allowlist(
+    distributor: &InterestDistributor,
+    entry: Option<&Account<AllowlistEntry>>,
+    user: Pubkey,
+    now: i64,
+) -> Result<()> {
+    if !distributor.allowlist_required {
+        return Ok(());
+    }
+
+    let entry = entry.ok_or(SavingsError::NotAllowlisted)?;
+    require!(
+        entry.state == distributor.state && entry.user == user,
+        SavingsError::NotAllowlisted
+    );
+    if entry.expires_at > 0 && now >= entry.expires_at {
+        return Err(SavingsError::AllowlistEntryExpired.into());
+    }
+
+    Ok(())
+}
+
+/// Checks that `authority` may immediately withdraw `amount` tokens from a savings vault holding
+/// `vault_amount`, charging the withdrawal against the delegate's allowance if needed.
+fn authorize_immediate_withdrawal(
+    authority: Pubkey,
+    manager: &mut Account<SavingsManager>,
+    distributor: &InterestDistributor,
+    vault_amount: u64,
+    amount: u64,
+    now: i64,
+) -> Result<()> {
+    if distributor.withdrawal_notice_period > 0 {
+        return Err(SavingsError::WithdrawalNoticeRequired.into());
+    }
+
+    update_savings_goal(manager, vault_amount, now);
+    require!(
+        !manager.has_active_goal(),
+        SavingsError::SavingsGoalNotReached
+    );
+
+    if authority != manager.user {
+        manager.consume_delegate_allowance(amount, now)?;
+    }
+
+    Ok(())
+}
+
+/// Withdraws `amount` tokens from a savings vault to `destination`, less any withdrawal fee, which
+/// is paid into the distributor's interest vault. The withdrawal counts against both the user's and
+/// the distributor's rolling withdrawal limits, and fails if it would exceed either; a pending
+/// withdrawal request stays queued until the limits leave room for it.
+#[allow(clippy::too_many_arguments)]
+fn withdraw_from_savings_vault<'info>(
+    token_program: &Program<'info, Token>,
+    distributor: &mut InterestDistributor,
+    savings_manager: &mut Account<'info, SavingsManager>,
+    savings_vault: &Account<'info, TokenAccount>,
+    interest_vault: &Account<'info, TokenAccount>,
+    destination: &AccountInfo<'info>,
+    authority: Pubkey,
+    amount: u64,
+    now: i64,
+) -> Result<()> {
+    savings_manager.record_withdrawal(amount, distributor.user_withdrawal_limit, now)?;
+    distributor.record_outflow(amount, now)?;
+
+    let fee = distributor.withdrawal_fee(amount, savings_manager.last_deposit_ts, now);
+    if fee > 0 {
+        transfer_from_savings_vault(
+            token_program,
+            savings_manager,
+            savings_vault,
+            &interest_vault.to_account_info(),
+            fee,
+        )?;
+    }
+
+    transfer_from_savings_vault(
+        token_program,
+        savings_manager,
+        savings_vault,
+        destination,
+        amount.checked_sub(fee).unwrap(),
+    )?;
+
+    emit!(Withdrawal {
+        savings_manager: savings_manager.key(),
+        authority,
+        destination: destination.key(),
+        amount,
+        fee,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+/// Transfers `amount` tokens out of a savings vault, signed for by its savings-manager.
+fn transfer_from_savings_vault<'info>(
+    token_program: &Program<'info, Token>,
+    savings_manager: &Account<'info, SavingsManager>,
+    savings_vault: &Account<'info, TokenAccount>,
+    destination: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let index_seed = SavingsManager::index_seed(savings_manager.index);
+    let manager_seeds = &[
+        SAVINGS_MANAGER_SEED_PREFIX,
+        savings_manager.user.as_ref(),
+        savings_manager.distributor.as_ref(),
+        &index_seed,
+        &[savings_manager.bump],
+    ];
+
+    anchor_spl::token::transfer(
+        CpiContext::new(
+            token_program.to_account_info(),
+            Transfer {
+                from: savings_vault.to_account_info(),
+                to: destination.clone(),
+                authority: savings_manager.to_account_info(),
+            },
+        )
+        .with_signer(&[&manager_seeds[..]]),
+        amount,
+    )
+}
+
+/// Calls `instruction` of the distributor's yield program with `amount` as its only argument,
+/// signed for by the distributor's yield adapter. The remaining accounts are forwarded in order, and
+/// the adapter is marked as a signer wherever it appears among them.
+fn invoke_yield_source<'info>(
+    ctx: &Context<'_, '_, '_, 'info, ManageYieldFunds<'info>>,
+    instruction: [u8; 8],
+    amount: u64,
+) -> Result<()> {
+    let adapter = &ctx.accounts.yield_adapter;
+    let accounts = ctx
+        .remaining_accounts
+        .iter()
+        .map(|account| AccountMeta {
+            pubkey: account.key(),
+            is_signer: account.is_signer || account.key() == adapter.key(),
+            is_writable: account.is_writable,
+        })
+        .collect();
+    let mut data = instruction.to_vec();
+    data.extend_from_slice(&amount.to_le_bytes());
+
+    let mut account_infos = ctx.remaining_accounts.to_vec();
+    account_infos.push(ctx.accounts.yield_program.to_account_info());
+
+    let adapter_seeds = [
+        YIELD_ADAPTER_SEED_PREFIX,
+        adapter.distributor.as_ref(),
+        &[adapter.bump],
+    ];
+    invoke_signed(
+        &Instruction {
+            program_id: ctx.accounts.yield_program.key(),
+            accounts,
+            data,
+        },
+        &account_infos,
+        &[&adapter_seeds[..]],
+    )?;
+
+    Ok(())
+}
+
+/// Moves `amount` tokens from the yield adapter's yield vault back to the interest vault.
+fn return_yield_vault_funds(ctx: &Context<ManageYieldFunds>, amount: u64) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let adapter = &ctx.accounts.yield_adapter;
+    let adapter_seeds = [
+        YIELD_ADAPTER_SEED_PREFIX,
+        adapter.distributor.as_ref(),
+        &[adapter.bump],
+    ];
+    anchor_spl::token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.yield_vault.to_account_info(),
+                to: ctx.accounts.interest_vault.to_account_info(),
+                authority: adapter.to_account_info(),
+            },
+        )
+        .with_signer(&[&adapter_seeds[..]]),
+        amount,
+    )
+}
+
+/// Records the rate published by the distributor's rate feed, if it follows one, in its rate
+/// history. The rate is clamped to the history's bounds and backdated to the feed's last update,
+/// though never before the latest recorded change. Fails if the feed is missing or stale.
+fn sync_rate_feed(
+    rate_history: &mut RateHistory,
+    rate_feed: Option<&UncheckedAccount>,
+    now: i64,
+) -> Result<()> {
+    let Some(feed_key) = rate_history.rate_feed else {
+        return Ok(());
+    };
+    let rate_feed = rate_feed
+        .filter(|feed| feed.key() == feed_key)
+        .ok_or(SavingsError::MissingRateFeed)?;
+    let (rate_bps, last_update_ts) = RateFeed::read_published(&rate_feed.try_borrow_data()?)?;
+
+    let age = now.checked_sub(last_update_ts).unwrap();
+    if age > rate_history.max_feed_staleness {
+        msg!("Rate feed last updated at: {}", last_update_ts);
+        return Err(SavingsError::StaleRateFeed.into());
+    }
+
+    let rate_bps = rate_bps.clamp(rate_history.min_rate_bps, rate_history.max_rate_bps);
+    if rate_bps != rate_history.current_rate() {
+        let latest_change = rate_history.entries[rate_history.head as usize].effective_ts;
+        rate_history.push(rate_bps, last_update_ts.max(latest_change));
+    }
+
+    Ok(())
+}
+
+/// Grows a program-owned account of type `T` to `space` bytes, topping up its lamports from
+/// `payer` so it stays rent-exempt. Accounts that are already large enough are left untouched.
+fn realloc_account<'info, T: Discriminator>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+) -> Result<()> {
+    require_keys_eq!(
+        *account.owner,
+        crate::ID,
+        ErrorCode::AccountOwnedByWrongProgram
+    );
+    {
+        let data = account.try_borrow_data()?;
+        if data.len() < 8 || data[..8] != T::DISCRIMINATOR {
+            return Err(ErrorCode::AccountDiscriminatorMismatch.into());
+        }
+    }
+
+    if account.data_len() >= space {
+        return Ok(());
+    }
+
+    let required_lamports = Rent::get()?.minimum_balance(space);
+    let top_up = required_lamports.saturating_sub(account.lamports());
+    if top_up > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                anchor_lang::system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    account.realloc(space, true)?;
+
+    Ok(())
+}
+
+//////////////////////////////////////////
+// CONTEXT FOR USER INSTRUCTIONS:
+/////////////////////////////////////////
+
+#[derive(Accounts)]
+#[instruction(referrer: Option<Pubkey>, index: u16)]
+pub struct UserCreateVault<'info> {
+    #[account(mut)]
     pub payer: Signer<'info>,
     pub user: Signer<'info>,
     pub mint: Account<'info, Mint>,
@@ -323,7 +2280,12 @@ pub struct UserCreateVault<'info> {
     pub interest_distributor: Account<'info, InterestDistributor>,
     #[account(
         init,
-        seeds = [SAVINGS_MANAGER_SEED_PREFIX, user.key().as_ref(), interest_distributor.key().as_ref()],
+        seeds = [
+            SAVINGS_MANAGER_SEED_PREFIX,
+            user.key().as_ref(),
+            interest_distributor.key().as_ref(),
+            &SavingsManager::index_seed(index),
+        ],
         bump,
         payer = payer,
         space = SavingsManager::SPACE,
@@ -336,165 +2298,367 @@ pub struct UserCreateVault<'info> {
         associated_token::authority = savings_manager
     )]
     pub savings_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [
+            REGISTRY_PAGE_SEED_PREFIX,
+            interest_distributor.key().as_ref(),
+            &registry_page.page.to_le_bytes(),
+        ],
+        bump = registry_page.bump,
+    )]
+    pub registry_page: Account<'info, RegistryPage>,
+    /// The user's allowlist entry, required if the distributor is allowlist-gated.
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
 #[derive(Accounts)]
-pub struct UserDeposit<'info> {
-    pub user: Signer<'info>,
-    #[account(has_one = user)]
-    pub savings_manager: Account<'info, SavingsManager>,
-    /// CHECK: Checked by SPL-token Transfer Instruction.
+pub struct UserTransferPosition<'info> {
     #[account(mut)]
-    pub user_token_account: UncheckedAccount<'info>,
+    pub payer: Signer<'info>,
+    /// The current owner of the position. Receives the rent of the closed accounts.
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub new_user: Signer<'info>,
+    #[account(address = savings_manager.distributor)]
+    pub interest_distributor: Account<'info, InterestDistributor>,
     #[account(
         mut,
-        associated_token::mint = savings_manager.mint,
-        associated_token::authority = savings_manager
+        has_one = user,
+        close = user,
+        constraint = !savings_manager.frozen @ SavingsError::SavingsManagerFrozen
     )]
-    pub savings_vault: Account<'info, TokenAccount>,
-    pub token_program: Program<'info, Token>,
-}
-
-#[derive(Accounts)]
-#[instruction(amount: u64)]
-pub struct UserWithdraw<'info> {
-    pub user: Signer<'info>,
-    #[account(has_one = user)]
     pub savings_manager: Account<'info, SavingsManager>,
     #[account(
         mut,
         associated_token::mint = savings_manager.mint,
-        associated_token::authority = savings_manager,
-        constraint = savings_vault.amount >= amount @ SavingsError::InadequateFunds
+        associated_token::authority = savings_manager
     )]
     pub savings_vault: Account<'info, TokenAccount>,
-    /// CHECK: Checked by SPL-token Transfer Instruction.
-    #[account(mut)]
-    pub destination_token_account: UncheckedAccount<'info>,
-    pub token_program: Program<'info, Token>,
-}
-
-//////////////////////////////////////////
-// CONTEXT FOR ADMIN INSTRUCTIONS:
-/////////////////////////////////////////
-
-#[derive(Accounts)]
-pub struct InitializeState<'info> {
-    #[account(mut)]
-    pub initializer: Signer<'info>,
-    #[account(
-        init,
-        payer = initializer,
-        space = 8 + 32,
-        //space = State::SPACE
-    )]
-    pub state: Account<'info, State>,
-    pub system_program: Program<'info, System>,
-}
-
-#[derive(Accounts)]
-pub struct CreateInterestVaultForMint<'info> {
-    pub authority: Signer<'info>,
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    #[account(has_one = authority)]
-    pub state: Account<'info, State>,
+    #[account(address = savings_manager.mint)]
     pub mint: Account<'info, Mint>,
     #[account(
         init,
-        seeds = [INTEREST_DISTRIBUTOR_SEED_PREFIX, state.key().as_ref(), mint.key().as_ref()],
+        seeds = [
+            SAVINGS_MANAGER_SEED_PREFIX,
+            new_user.key().as_ref(),
+            interest_distributor.key().as_ref(),
+            &SavingsManager::index_seed(savings_manager.index),
+        ],
         bump,
         payer = payer,
-        space = InterestDistributor::SPACE,
+        space = SavingsManager::SPACE,
     )]
-    pub interest_distributor: Account<'info, InterestDistributor>,
+    pub new_savings_manager: Account<'info, SavingsManager>,
     #[account(
         init,
-        associated_token::mint = mint,
-        associated_token::authority = interest_distributor,
         payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = new_savings_manager
     )]
-    pub interest_vault: Account<'info, TokenAccount>,
+    pub new_savings_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [
+            REGISTRY_PAGE_SEED_PREFIX,
+            interest_distributor.key().as_ref(),
+            &registry_page.page.to_le_bytes(),
+        ],
+        bump = registry_page.bump,
+    )]
+    pub registry_page: Account<'info, RegistryPage>,
+    /// The new owner's allowlist entry, required if the distributor is allowlist-gated.
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
 #[derive(Accounts)]
-pub struct DepositToInterestVault<'info> {
-    pub authority: Signer<'info>,
-    #[account(has_one = authority)]
-    pub state: Account<'info, State>,
-    pub depositor: Signer<'info>,
+pub struct UserDeposit<'info> {
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        has_one = user,
+        constraint = !savings_manager.frozen @ SavingsError::SavingsManagerFrozen
+    )]
+    pub savings_manager: Account<'info, SavingsManager>,
+    #[account(address = savings_manager.distributor)]
+    pub interest_distributor: Account<'info, InterestDistributor>,
+    /// The user's allowlist entry, required if the distributor is allowlist-gated.
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
     /// CHECK: Checked by SPL-token Transfer Instruction.
     #[account(mut)]
-    pub depositor_token_account: UncheckedAccount<'info>,
-    #[account(has_one = state)]
-    pub interest_distributor: Account<'info, InterestDistributor>,
+    pub user_token_account: UncheckedAccount<'info>,
     #[account(
         mut,
-        associated_token::mint = interest_distributor.mint,
-        associated_token::authority = interest_distributor,
+        associated_token::mint = savings_manager.mint,
+        associated_token::authority = savings_manager
     )]
-    pub interest_vault: Account<'info, TokenAccount>,
+    pub savings_vault: Account<'info, TokenAccount>,
+    /// The distributor's receipt mint, required if it has one.
+    #[account(mut)]
+    pub receipt_mint: Option<Account<'info, Mint>>,
+    /// CHECK: Checked by SPL-token MintTo Instruction.
+    #[account(mut)]
+    pub receipt_token_account: Option<UncheckedAccount<'info>>,
+    /// The distributor's receipt pool, required if it has a receipt mint.
+    #[account(
+        mut,
+        seeds = [RECEIPT_POOL_SEED_PREFIX, interest_distributor.key().as_ref()],
+        bump,
+    )]
+    pub receipt_pool: Option<Account<'info, TokenAccount>>,
     pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct WithdrawFromInterestVault<'info> {
-    pub authority: Signer<'info>,
+pub struct DepositFor<'info> {
+    pub depositor: Signer<'info>,
+    #[account(
+        mut,
+        constraint = !savings_manager.frozen @ SavingsError::SavingsManagerFrozen,
+        constraint = savings_manager.user == depositor.key()
+            || !savings_manager.third_party_deposits_disabled
+            @ SavingsError::ThirdPartyDepositsDisabled
+    )]
+    pub savings_manager: Account<'info, SavingsManager>,
+    #[account(address = savings_manager.distributor)]
+    pub interest_distributor: Account<'info, InterestDistributor>,
+    /// The recipient's allowlist entry, required if the distributor is allowlist-gated.
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
     /// CHECK: Checked by SPL-token Transfer Instruction.
     #[account(mut)]
-    pub destination_token_account: UncheckedAccount<'info>,
-    #[account(has_one = authority)]
-    pub state: Account<'info, State>,
-    #[account(has_one = state)]
-    pub interest_distributor: Account<'info, InterestDistributor>,
+    pub depositor_token_account: UncheckedAccount<'info>,
     #[account(
         mut,
-        associated_token::mint = interest_distributor.mint,
-        associated_token::authority = interest_distributor,
+        associated_token::mint = savings_manager.mint,
+        associated_token::authority = savings_manager
     )]
-    pub interest_vault: Account<'info, TokenAccount>,
+    pub savings_vault: Account<'info, TokenAccount>,
     pub token_program: Program<'info, Token>,
 }
 
-///////////////////////////////////////////
-// CONTEXT FOR CRANK INSTRUCTION:
-/////////////////////////////////////////
-
 #[derive(Accounts)]
-pub struct DepositInterestToUser<'info> {
-    /// CHECK: The user the crank is being called for.
-    pub user: UncheckedAccount<'info>,
+pub struct UserSetThirdPartyDeposits<'info> {
+    pub user: Signer<'info>,
+    #[account(mut, has_one = user)]
+    pub savings_manager: Account<'info, SavingsManager>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64)]
+pub struct UserWithdraw<'info> {
+    /// Either the owner of the savings vault or its delegate.
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = savings_manager.user == authority.key()
+            || savings_manager.delegate == Some(authority.key())
+            @ SavingsError::UnauthorizedWithdrawal,
+        constraint = !savings_manager.frozen @ SavingsError::SavingsManagerFrozen
+    )]
+    pub savings_manager: Account<'info, SavingsManager>,
+    #[account(mut, address = savings_manager.distributor)]
+    pub interest_distributor: Account<'info, InterestDistributor>,
+    #[account(
+        mut,
+        associated_token::mint = savings_manager.mint,
+        associated_token::authority = savings_manager,
+        constraint = savings_vault.amount >= amount @ SavingsError::InadequateFunds
+    )]
+    pub savings_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = interest_distributor.mint,
+        associated_token::authority = interest_distributor,
+    )]
+    pub interest_vault: Account<'info, TokenAccount>,
+    /// CHECK: Checked by SPL-token Transfer Instruction.
+    #[account(mut)]
+    pub destination_token_account: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct UserDepositNative<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        has_one = user,
+        constraint = savings_manager.mint == native_mint::ID @ SavingsError::NotNativeMint,
+        constraint = !savings_manager.frozen @ SavingsError::SavingsManagerFrozen
+    )]
+    pub savings_manager: Account<'info, SavingsManager>,
+    #[account(address = savings_manager.distributor)]
+    pub interest_distributor: Account<'info, InterestDistributor>,
+    /// The user's allowlist entry, required if the distributor is allowlist-gated.
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+    #[account(
+        mut,
+        associated_token::mint = savings_manager.mint,
+        associated_token::authority = savings_manager
+    )]
+    pub savings_vault: Account<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64)]
+pub struct UserWithdrawNative<'info> {
+    /// Either the owner of the savings vault or its delegate. Receives the unwrapped lamports.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = savings_manager.user == authority.key()
+            || savings_manager.delegate == Some(authority.key())
+            @ SavingsError::UnauthorizedWithdrawal,
+        constraint = savings_manager.mint == native_mint::ID @ SavingsError::NotNativeMint,
+        constraint = !savings_manager.frozen @ SavingsError::SavingsManagerFrozen
+    )]
+    pub savings_manager: Account<'info, SavingsManager>,
+    #[account(mut, address = savings_manager.distributor)]
+    pub interest_distributor: Account<'info, InterestDistributor>,
+    #[account(
+        mut,
+        associated_token::mint = savings_manager.mint,
+        associated_token::authority = savings_manager,
+        constraint = savings_vault.amount >= amount @ SavingsError::InadequateFunds
+    )]
+    pub savings_vault: Account<'info, TokenAccount>,
     #[account(
         mut,
-        seeds = [SAVINGS_MANAGER_SEED_PREFIX, user.key().as_ref(), interest_distributor.key().as_ref()],
+        associated_token::mint = interest_distributor.mint,
+        associated_token::authority = interest_distributor,
+    )]
+    pub interest_vault: Account<'info, TokenAccount>,
+    #[account(address = native_mint::ID)]
+    pub native_mint: Account<'info, Mint>,
+    /// Temporary wSOL account, closed before the instruction returns.
+    #[account(
+        init,
+        seeds = [NATIVE_WITHDRAWAL_SEED_PREFIX, savings_manager.key().as_ref()],
         bump,
+        payer = authority,
+        token::mint = native_mint,
+        token::authority = savings_manager,
     )]
-    pub user_savings_manager: Account<'info, SavingsManager>,
+    pub unwrap_account: Account<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RedeemReceipts<'info> {
+    pub holder: Signer<'info>,
+    #[account(mut)]
+    pub interest_distributor: Account<'info, InterestDistributor>,
     #[account(
         mut,
-        associated_token::mint = user_savings_manager.mint,
-        associated_token::authority = user_savings_manager
+        constraint = interest_distributor.receipt_mint == Some(receipt_mint.key())
+            @ SavingsError::MissingReceiptMint
     )]
-    pub user_savings_vault: Account<'info, TokenAccount>,
+    pub receipt_mint: Account<'info, Mint>,
+    /// CHECK: Checked by SPL-token Burn Instruction.
+    #[account(mut)]
+    pub holder_receipt_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [RECEIPT_POOL_SEED_PREFIX, interest_distributor.key().as_ref()],
+        bump,
+    )]
+    pub receipt_pool: Account<'info, TokenAccount>,
+    /// CHECK: Checked by SPL-token Transfer Instruction.
+    #[account(mut)]
+    pub destination_token_account: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64)]
+pub struct RequestWithdrawal<'info> {
+    /// Either the owner of the savings vault or its delegate.
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = savings_manager.user == authority.key()
+            || savings_manager.delegate == Some(authority.key())
+            @ SavingsError::UnauthorizedWithdrawal,
+        constraint = !savings_manager.frozen @ SavingsError::SavingsManagerFrozen
+    )]
+    pub savings_manager: Account<'info, SavingsManager>,
+    #[account(
+        associated_token::mint = savings_manager.mint,
+        associated_token::authority = savings_manager,
+        constraint = savings_vault.amount >= amount @ SavingsError::InadequateFunds
+    )]
+    pub savings_vault: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteWithdrawal<'info> {
+    /// Either the owner of the savings vault or its delegate.
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = savings_manager.user == authority.key()
+            || savings_manager.delegate == Some(authority.key())
+            @ SavingsError::UnauthorizedWithdrawal,
+        constraint = !savings_manager.frozen @ SavingsError::SavingsManagerFrozen
+    )]
+    pub savings_manager: Account<'info, SavingsManager>,
+    #[account(mut, address = savings_manager.distributor)]
     pub interest_distributor: Account<'info, InterestDistributor>,
+    #[account(
+        mut,
+        associated_token::mint = savings_manager.mint,
+        associated_token::authority = savings_manager,
+        constraint = savings_vault.amount >= savings_manager.pending_withdrawal_amount
+            @ SavingsError::InadequateFunds
+    )]
+    pub savings_vault: Account<'info, TokenAccount>,
     #[account(
         mut,
         associated_token::mint = interest_distributor.mint,
         associated_token::authority = interest_distributor,
     )]
     pub interest_vault: Account<'info, TokenAccount>,
+    /// CHECK: Checked by SPL-token Transfer Instruction.
+    #[account(mut)]
+    pub destination_token_account: UncheckedAccount<'info>,
     pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct DepositInterestToMultipleUsers<'info> {
+pub struct UserSetGoal<'info> {
+    pub user: Signer<'info>,
+    #[account(mut, has_one = user)]
+    pub savings_manager: Account<'info, SavingsManager>,
+}
+
+#[derive(Accounts)]
+pub struct UserCancelGoal<'info> {
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        has_one = user,
+        constraint = !savings_manager.frozen @ SavingsError::SavingsManagerFrozen
+    )]
+    pub savings_manager: Account<'info, SavingsManager>,
+    #[account(address = savings_manager.distributor)]
     pub interest_distributor: Account<'info, InterestDistributor>,
+    #[account(
+        mut,
+        associated_token::mint = savings_manager.mint,
+        associated_token::authority = savings_manager
+    )]
+    pub savings_vault: Account<'info, TokenAccount>,
     #[account(
         mut,
         associated_token::mint = interest_distributor.mint,
@@ -504,70 +2668,2240 @@ pub struct DepositInterestToMultipleUsers<'info> {
     pub token_program: Program<'info, Token>,
 }
 
-#[account]
-/// The Application State.
-pub struct State {
-    /// The authority that's allowed to deposit and withdraw
-    /// from the interest vault.
-    pub authority: Pubkey,
+#[derive(Accounts)]
+pub struct UserManageDelegate<'info> {
+    pub user: Signer<'info>,
+    #[account(mut, has_one = user)]
+    pub savings_manager: Account<'info, SavingsManager>,
 }
 
-impl State {
-    pub const SPACE: usize = 8/*anchor account discriminator*/ + 32/*authority*/;
+#[derive(Accounts)]
+pub struct UserCreateSavingsPlan<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub user: Signer<'info>,
+    #[account(has_one = user)]
+    pub savings_manager: Account<'info, SavingsManager>,
+    #[account(
+        token::mint = savings_manager.mint,
+        token::authority = user,
+    )]
+    pub source_token_account: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        seeds = [SAVINGS_PLAN_SEED_PREFIX, savings_manager.key().as_ref()],
+        bump,
+        payer = payer,
+        space = SavingsPlan::SPACE,
+    )]
+    pub savings_plan: Account<'info, SavingsPlan>,
+    pub system_program: Program<'info, System>,
 }
 
-#[account]
-/// This account is a PDA unique to a single (state, mint) pair, and is authority
-/// of the vault from which interest tokens are paid.
-pub struct InterestDistributor {
-    /// The state's public key.
-    pub state: Pubkey,
-    /// The mint of the token account this distributor serves as authority for.
-    pub mint: Pubkey,
-    /// Bump of this account's PDA. Stored to avoid deriving it everytime a signature
-    /// is required.
-    pub bump: u8,
+#[derive(Accounts)]
+pub struct UserCloseSavingsPlan<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(mut, has_one = user, close = user)]
+    pub savings_plan: Account<'info, SavingsPlan>,
 }
 
-impl InterestDistributor {
-    pub const SPACE: usize = 8 +   // anchor account discriminator
-        32 +   // state
-        32 +   // mint
-        1; // bump
+//////////////////////////////////////////
+// CONTEXT FOR ADMIN INSTRUCTIONS:
+/////////////////////////////////////////
+
+#[derive(Accounts)]
+pub struct InitializeState<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        init,
+        payer = initializer,
+        space = State::SPACE,
+    )]
+    pub state: Account<'info, State>,
+    pub system_program: Program<'info, System>,
 }
 
-#[account]
-/// Account holding information for a user-owned vault. This is a PDA unique to a
-/// (user, interest-distributor) pair.
-pub struct SavingsManager {
-    /// The owner of the vault.
-    pub user: Pubkey,
-    /// The mint of the vault.
-    pub mint: Pubkey,
-    /// The distributor for this user's vault, responsible for disbursing its interest payments.
-    pub distributor: Pubkey,
-    /// The unix timestamp of the last interest deposit.
-    pub last_interest_deposit_ts: i64,
-    /// Bump of this account's PDA. Stored to avoid deriving it everytime a signature
-    /// is required.
-    pub bump: u8,
+#[derive(Accounts)]
+pub struct CreateRateFeed<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(has_one = authority)]
+    pub state: Account<'info, State>,
+    #[account(
+        init,
+        payer = payer,
+        space = RateFeed::SPACE,
+    )]
+    pub rate_feed: Account<'info, RateFeed>,
+    pub system_program: Program<'info, System>,
 }
 
-impl SavingsManager {
-    pub const SPACE: usize = 8 +   // anchor account discriminator
-        32 +   // user
-        32 +   // mint
-        32 +   // distributor
-        8 +    // last_interest_deposit_ts
-        1; // bump
+#[derive(Accounts)]
+pub struct PublishRate<'info> {
+    pub publisher: Signer<'info>,
+    #[account(mut, has_one = publisher)]
+    pub rate_feed: Account<'info, RateFeed>,
 }
 
-#[error_code]
-pub enum SavingsError {
-    #[msg("not enough funds in vault token account")]
-    InadequateFunds,
-    #[msg("not enough time has elapsed since the last interest deposit")]
-    CrankTurnedTooSoon,
-    #[msg("did not specify any recipient for the interest transfer")]
-    ZeroRecipientsForInterestDeposit,
+#[derive(Accounts)]
+#[instruction(product_id: u16)]
+pub struct CreateInterestVaultForMint<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(has_one = authority)]
+    pub state: Account<'info, State>,
+    #[account(
+        constraint = !state.reject_freeze_authority || mint.freeze_authority.is_none()
+            @ SavingsError::MintHasFreezeAuthority,
+        constraint = mint.decimals >= state.min_mint_decimals @ SavingsError::MintDecimalsTooLow,
+    )]
+    pub mint: Account<'info, Mint>,
+    /// The mint's approval, required if the state only allows approved mints.
+    #[account(has_one = state, has_one = mint)]
+    pub approved_mint: Option<Account<'info, ApprovedMint>>,
+    #[account(
+        init,
+        seeds = [
+            INTEREST_DISTRIBUTOR_SEED_PREFIX,
+            state.key().as_ref(),
+            mint.key().as_ref(),
+            &InterestDistributor::product_seed(product_id),
+        ],
+        bump,
+        payer = payer,
+        space = InterestDistributor::SPACE,
+    )]
+    pub interest_distributor: Account<'info, InterestDistributor>,
+    #[account(
+        init,
+        associated_token::mint = mint,
+        associated_token::authority = interest_distributor,
+        payer = payer,
+    )]
+    pub interest_vault: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        seeds = [RATE_HISTORY_SEED_PREFIX, interest_distributor.key().as_ref()],
+        bump,
+        payer = payer,
+        space = RateHistory::SPACE,
+    )]
+    pub rate_history: Account<'info, RateHistory>,
+    #[account(
+        init,
+        seeds = [
+            REGISTRY_PAGE_SEED_PREFIX,
+            interest_distributor.key().as_ref(),
+            &0u32.to_le_bytes(),
+        ],
+        bump,
+        payer = payer,
+        space = RegistryPage::SPACE,
+    )]
+    pub registry_page: Account<'info, RegistryPage>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct CreateReceiptMint<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(has_one = authority)]
+    pub state: Account<'info, State>,
+    pub mint: Account<'info, Mint>,
+    #[account(mut, has_one = state, has_one = mint)]
+    pub interest_distributor: Account<'info, InterestDistributor>,
+    #[account(
+        init,
+        seeds = [RECEIPT_MINT_SEED_PREFIX, interest_distributor.key().as_ref()],
+        bump,
+        payer = payer,
+        mint::decimals = mint.decimals,
+        mint::authority = interest_distributor,
+    )]
+    pub receipt_mint: Account<'info, Mint>,
+    #[account(
+        init,
+        seeds = [RECEIPT_POOL_SEED_PREFIX, interest_distributor.key().as_ref()],
+        bump,
+        payer = payer,
+        token::mint = mint,
+        token::authority = interest_distributor,
+    )]
+    pub receipt_pool: Account<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CreateYieldAdapter<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(has_one = authority)]
+    pub state: Account<'info, State>,
+    #[account(has_one = state)]
+    pub interest_distributor: Account<'info, InterestDistributor>,
+    #[account(
+        init,
+        seeds = [YIELD_ADAPTER_SEED_PREFIX, interest_distributor.key().as_ref()],
+        bump,
+        payer = payer,
+        space = YieldAdapter::SPACE,
+    )]
+    pub yield_adapter: Account<'info, YieldAdapter>,
+    #[account(address = interest_distributor.mint)]
+    pub mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = yield_adapter,
+    )]
+    pub yield_vault: Account<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct ManageYieldFunds<'info> {
+    pub authority: Signer<'info>,
+    #[account(has_one = authority)]
+    pub state: Account<'info, State>,
+    #[account(has_one = state)]
+    pub interest_distributor: Account<'info, InterestDistributor>,
+    #[account(
+        mut,
+        seeds = [YIELD_ADAPTER_SEED_PREFIX, interest_distributor.key().as_ref()],
+        bump = yield_adapter.bump,
+    )]
+    pub yield_adapter: Account<'info, YieldAdapter>,
+    #[account(
+        mut,
+        associated_token::mint = interest_distributor.mint,
+        associated_token::authority = interest_distributor,
+    )]
+    pub interest_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = interest_distributor.mint,
+        associated_token::authority = yield_adapter,
+    )]
+    pub yield_vault: Account<'info, TokenAccount>,
+    /// CHECK: Must be the yield program configured on the adapter.
+    #[account(
+        executable,
+        constraint = yield_adapter.program == Some(yield_program.key())
+            @ SavingsError::YieldSourceNotConfigured
+    )]
+    pub yield_program: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSavingsManagerFreeze<'info> {
+    pub authority: Signer<'info>,
+    #[account(has_one = authority)]
+    pub state: Account<'info, State>,
+    #[account(address = savings_manager.distributor, has_one = state)]
+    pub interest_distributor: Account<'info, InterestDistributor>,
+    #[account(mut)]
+    pub savings_manager: Account<'info, SavingsManager>,
+}
+
+#[derive(Accounts)]
+pub struct QueueConfigChange<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    /// The distributor the change applies to. Omitted for changes to the state itself.
+    #[account(has_one = state)]
+    pub interest_distributor: Option<Account<'info, InterestDistributor>>,
+    #[account(
+        init,
+        seeds = [
+            CONFIG_CHANGE_SEED_PREFIX,
+            state.key().as_ref(),
+            state.next_config_change_id.to_le_bytes().as_ref()
+        ],
+        bump,
+        payer = authority,
+        space = PendingConfigChange::SPACE,
+    )]
+    pub pending_change: Account<'info, PendingConfigChange>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteConfigChange<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    /// The distributor the change applies to. Omitted for changes to the state itself.
+    #[account(mut, has_one = state)]
+    pub interest_distributor: Option<Account<'info, InterestDistributor>>,
+    /// The distributor's rate history. Only required for interest rate changes.
+    #[account(mut)]
+    pub rate_history: Option<Account<'info, RateHistory>>,
+    /// The distributor's yield adapter. Only required for yield source changes.
+    #[account(mut)]
+    pub yield_adapter: Option<Account<'info, YieldAdapter>>,
+    #[account(mut, has_one = state, close = authority)]
+    pub pending_change: Account<'info, PendingConfigChange>,
+}
+
+#[derive(Accounts)]
+pub struct CancelConfigChange<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(has_one = authority)]
+    pub state: Account<'info, State>,
+    #[account(mut, has_one = state, close = authority)]
+    pub pending_change: Account<'info, PendingConfigChange>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct AddAllowlistEntry<'info> {
+    /// The state's authority or its designated allowlist verifier.
+    #[account(mut)]
+    pub verifier: Signer<'info>,
+    #[account(
+        constraint = state.authority == verifier.key()
+            || state.allowlist_verifier == Some(verifier.key())
+            @ SavingsError::UnauthorizedVerifier
+    )]
+    pub state: Account<'info, State>,
+    #[account(
+        init,
+        seeds = [ALLOWLIST_ENTRY_SEED_PREFIX, state.key().as_ref(), user.as_ref()],
+        bump,
+        payer = verifier,
+        space = AllowlistEntry::SPACE,
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveAllowlistEntry<'info> {
+    /// The state's authority or its designated allowlist verifier.
+    #[account(mut)]
+    pub verifier: Signer<'info>,
+    #[account(
+        constraint = state.authority == verifier.key()
+            || state.allowlist_verifier == Some(verifier.key())
+            @ SavingsError::UnauthorizedVerifier
+    )]
+    pub state: Account<'info, State>,
+    #[account(mut, has_one = state, close = verifier)]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveMint<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(has_one = authority)]
+    pub state: Account<'info, State>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        init,
+        seeds = [APPROVED_MINT_SEED_PREFIX, state.key().as_ref(), mint.key().as_ref()],
+        bump,
+        payer = authority,
+        space = ApprovedMint::SPACE,
+    )]
+    pub approved_mint: Account<'info, ApprovedMint>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeMintApproval<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(has_one = authority)]
+    pub state: Account<'info, State>,
+    #[account(mut, has_one = state, close = authority)]
+    pub approved_mint: Account<'info, ApprovedMint>,
+}
+
+#[derive(Accounts)]
+#[instruction(campaign_id: u64, merkle_root: [u8; 32], max_claims: u32, expires_ts: i64)]
+pub struct CreateCampaign<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(has_one = authority)]
+    pub state: Account<'info, State>,
+    pub mint: Account<'info, Mint>,
+    #[account(has_one = state, has_one = mint)]
+    pub interest_distributor: Account<'info, InterestDistributor>,
+    #[account(
+        init,
+        seeds = [
+            CAMPAIGN_SEED_PREFIX,
+            interest_distributor.key().as_ref(),
+            &campaign_id.to_le_bytes(),
+        ],
+        bump,
+        payer = payer,
+        space = Campaign::space(max_claims),
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = campaign,
+    )]
+    pub campaign_vault: Account<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct CloseCampaign<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(has_one = authority)]
+    pub state: Account<'info, State>,
+    #[account(has_one = state)]
+    pub interest_distributor: Account<'info, InterestDistributor>,
+    #[account(
+        mut,
+        constraint = campaign.distributor == interest_distributor.key()
+            @ SavingsError::CampaignDistributorMismatch,
+        close = authority,
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        associated_token::mint = interest_distributor.mint,
+        associated_token::authority = campaign
+    )]
+    pub campaign_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = interest_distributor.mint,
+        associated_token::authority = interest_distributor
+    )]
+    pub interest_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct DepositToInterestVault<'info> {
+    pub authority: Signer<'info>,
+    #[account(has_one = authority)]
+    pub state: Account<'info, State>,
+    pub depositor: Signer<'info>,
+    /// CHECK: Checked by SPL-token Transfer Instruction.
+    #[account(mut)]
+    pub depositor_token_account: UncheckedAccount<'info>,
+    #[account(has_one = state)]
+    pub interest_distributor: Account<'info, InterestDistributor>,
+    #[account(
+        mut,
+        associated_token::mint = interest_distributor.mint,
+        associated_token::authority = interest_distributor,
+    )]
+    pub interest_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFromInterestVault<'info> {
+    pub authority: Signer<'info>,
+    /// CHECK: Checked by SPL-token Transfer Instruction.
+    #[account(mut)]
+    pub destination_token_account: UncheckedAccount<'info>,
+    #[account(has_one = authority)]
+    pub state: Account<'info, State>,
+    #[account(has_one = state)]
+    pub interest_distributor: Account<'info, InterestDistributor>,
+    #[account(
+        mut,
+        associated_token::mint = interest_distributor.mint,
+        associated_token::authority = interest_distributor,
+    )]
+    pub interest_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+///////////////////////////////////////////
+// CONTEXT FOR CRANK INSTRUCTION:
+/////////////////////////////////////////
+
+#[derive(Accounts)]
+pub struct DepositInterestToUser<'info> {
+    /// CHECK: The user the crank is being called for.
+    pub user: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [
+            SAVINGS_MANAGER_SEED_PREFIX,
+            user.key().as_ref(),
+            interest_distributor.key().as_ref(),
+            &SavingsManager::index_seed(user_savings_manager.index),
+        ],
+        bump,
+        constraint = !user_savings_manager.frozen @ SavingsError::SavingsManagerFrozen,
+    )]
+    pub user_savings_manager: Account<'info, SavingsManager>,
+    #[account(
+        mut,
+        associated_token::mint = user_savings_manager.mint,
+        associated_token::authority = user_savings_manager
+    )]
+    pub user_savings_vault: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub interest_distributor: Account<'info, InterestDistributor>,
+    #[account(
+        mut,
+        associated_token::mint = interest_distributor.mint,
+        associated_token::authority = interest_distributor,
+    )]
+    pub interest_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [RATE_HISTORY_SEED_PREFIX, interest_distributor.key().as_ref()],
+        bump = rate_history.bump,
+    )]
+    pub rate_history: Account<'info, RateHistory>,
+    /// CHECK: Required when the distributor follows a rate feed, and checked against the rate
+    /// history's feed in `sync_rate_feed`.
+    pub rate_feed: Option<UncheckedAccount<'info>>,
+    /// CHECK: The savings-manager of the user's referrer, required when a referral bonus is due.
+    /// Checked in `pay_referral_bonus`, and may be closed.
+    pub referrer_savings_manager: Option<UncheckedAccount<'info>>,
+    /// CHECK: The savings-vault of the user's referrer, required when a referral bonus is due.
+    /// Checked in `pay_referral_bonus`, and may be closed.
+    #[account(mut)]
+    pub referrer_savings_vault: Option<UncheckedAccount<'info>>,
+    /// CHECK: Required when a performance fee is due, and checked against the distributor's
+    /// fee destination in `pay_performance_fee`.
+    #[account(mut)]
+    pub fee_destination: Option<UncheckedAccount<'info>>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct DepositInterestToMultipleUsers<'info> {
+    #[account(mut)]
+    pub interest_distributor: Account<'info, InterestDistributor>,
+    #[account(
+        mut,
+        associated_token::mint = interest_distributor.mint,
+        associated_token::authority = interest_distributor,
+    )]
+    pub interest_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [RATE_HISTORY_SEED_PREFIX, interest_distributor.key().as_ref()],
+        bump = rate_history.bump,
+    )]
+    pub rate_history: Account<'info, RateHistory>,
+    /// CHECK: Required when the distributor follows a rate feed, and checked against the rate
+    /// history's feed in `sync_rate_feed`.
+    pub rate_feed: Option<UncheckedAccount<'info>>,
+    /// CHECK: Required when a performance fee is due, and checked against the distributor's
+    /// fee destination in `pay_performance_fee`.
+    #[account(mut)]
+    pub fee_destination: Option<UncheckedAccount<'info>>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CreateRegistryPage<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    pub interest_distributor: Account<'info, InterestDistributor>,
+    #[account(
+        init,
+        seeds = [
+            REGISTRY_PAGE_SEED_PREFIX,
+            interest_distributor.key().as_ref(),
+            &interest_distributor.registry_pages.to_le_bytes(),
+        ],
+        bump,
+        payer = payer,
+        space = RegistryPage::SPACE,
+    )]
+    pub registry_page: Account<'info, RegistryPage>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteSavingsPlan<'info> {
+    #[account(
+        mut,
+        has_one = savings_manager,
+        has_one = source_token_account,
+    )]
+    pub savings_plan: Account<'info, SavingsPlan>,
+    #[account(
+        mut,
+        constraint = !savings_manager.frozen @ SavingsError::SavingsManagerFrozen
+    )]
+    pub savings_manager: Account<'info, SavingsManager>,
+    #[account(
+        mut,
+        constraint = source_token_account.delegate == COption::Some(savings_manager.key())
+            && source_token_account.delegated_amount >= savings_plan.amount
+            @ SavingsError::SavingsPlanNotApproved
+    )]
+    pub source_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = savings_manager.mint,
+        associated_token::authority = savings_manager
+    )]
+    pub savings_vault: Account<'info, TokenAccount>,
+    #[account(address = savings_manager.distributor)]
+    pub interest_distributor: Account<'info, InterestDistributor>,
+    /// The user's allowlist entry, required if the distributor is allowlist-gated.
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+    /// The distributor's receipt mint, required if it has one.
+    #[account(mut)]
+    pub receipt_mint: Option<Account<'info, Mint>>,
+    /// CHECK: The user's associated receipt token account, required if the distributor has a
+    /// receipt mint. Checked in `execute_savings_plan`.
+    #[account(mut)]
+    pub receipt_token_account: Option<UncheckedAccount<'info>>,
+    /// The distributor's receipt pool, required if it has a receipt mint.
+    #[account(
+        mut,
+        seeds = [RECEIPT_POOL_SEED_PREFIX, interest_distributor.key().as_ref()],
+        bump,
+    )]
+    pub receipt_pool: Option<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimCampaign<'info> {
+    #[account(
+        mut,
+        constraint = campaign.distributor == savings_manager.distributor
+            @ SavingsError::CampaignDistributorMismatch
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        associated_token::mint = savings_manager.mint,
+        associated_token::authority = campaign
+    )]
+    pub campaign_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = !savings_manager.frozen @ SavingsError::SavingsManagerFrozen
+    )]
+    pub savings_manager: Account<'info, SavingsManager>,
+    #[account(
+        mut,
+        associated_token::mint = savings_manager.mint,
+        associated_token::authority = savings_manager
+    )]
+    pub savings_vault: Account<'info, TokenAccount>,
+    #[account(address = savings_manager.distributor)]
+    pub interest_distributor: Account<'info, InterestDistributor>,
+    /// The user's allowlist entry, required if the distributor is allowlist-gated.
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+    pub token_program: Program<'info, Token>,
+}
+
+//////////////////////////////////////////
+// CONTEXT FOR VIEW INSTRUCTIONS:
+/////////////////////////////////////////
+
+#[derive(Accounts)]
+pub struct PreviewInterest<'info> {
+    pub savings_manager: Account<'info, SavingsManager>,
+    #[account(
+        associated_token::mint = savings_manager.mint,
+        associated_token::authority = savings_manager
+    )]
+    pub savings_vault: Account<'info, TokenAccount>,
+    #[account(address = savings_manager.distributor)]
+    pub interest_distributor: Account<'info, InterestDistributor>,
+    #[account(
+        associated_token::mint = interest_distributor.mint,
+        associated_token::authority = interest_distributor
+    )]
+    pub interest_vault: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [RATE_HISTORY_SEED_PREFIX, interest_distributor.key().as_ref()],
+        bump = rate_history.bump,
+    )]
+    pub rate_history: Account<'info, RateHistory>,
+    /// CHECK: Required when the distributor follows a rate feed, and checked against the rate
+    /// history's feed in `sync_rate_feed`.
+    pub rate_feed: Option<UncheckedAccount<'info>>,
+}
+
+/// What a savings vault's next interest payout is projected to be, as returned by
+/// `preview_interest`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct InterestPreview {
+    /// The unix timestamp from which the next payout can be cranked.
+    pub next_payout_ts: i64,
+    /// The interest the vault will be paid, net of the performance fee.
+    pub projected_interest: u64,
+    /// Whether the interest vault holds enough to pay the interest, the performance fee and any
+    /// referral bonus.
+    pub interest_vault_sufficient: bool,
+    /// The monthly interest rate currently in effect, in basis points.
+    pub current_rate_bps: u16,
+}
+
+//////////////////////////////////////////
+// CONTEXT FOR MIGRATION INSTRUCTIONS:
+/////////////////////////////////////////
+
+#[derive(Accounts)]
+pub struct MigrateState<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: May still be in a legacy layout, so it is validated by `realloc_account`.
+    #[account(mut)]
+    pub state: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateInterestDistributor<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: May still be in a legacy layout, so it is validated by `realloc_account`.
+    #[account(mut)]
+    pub interest_distributor: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateSavingsManager<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: May still be in a legacy layout, so it is validated by `realloc_account`.
+    #[account(mut)]
+    pub savings_manager: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateRateHistory<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub interest_distributor: Account<'info, InterestDistributor>,
+    #[account(
+        init,
+        seeds = [RATE_HISTORY_SEED_PREFIX, interest_distributor.key().as_ref()],
+        bump,
+        payer = payer,
+        space = RateHistory::SPACE,
+    )]
+    pub rate_history: Account<'info, RateHistory>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterSavingsManager<'info> {
+    #[account(
+        mut,
+        constraint = !savings_manager.registered @ SavingsError::SavingsManagerAlreadyRegistered
+    )]
+    pub savings_manager: Account<'info, SavingsManager>,
+    #[account(
+        mut,
+        seeds = [
+            REGISTRY_PAGE_SEED_PREFIX,
+            savings_manager.distributor.as_ref(),
+            &registry_page.page.to_le_bytes(),
+        ],
+        bump = registry_page.bump,
+    )]
+    pub registry_page: Account<'info, RegistryPage>,
+}
+
+#[account]
+/// The Application State.
+pub struct State {
+    /// The authority that's allowed to deposit and withdraw
+    /// from the interest vault.
+    pub authority: Pubkey,
+    /// Layout version of this account.
+    pub version: u8,
+    /// A key that, besides the authority, may add and remove allowlist entries.
+    pub allowlist_verifier: Option<Pubkey>,
+    /// The minimum number of seconds between queueing a configuration change and executing it.
+    pub config_timelock_delay: i64,
+    /// The id the next queued configuration change will be stored under.
+    pub next_config_change_id: u64,
+    /// Whether interest vaults can only be created for mints without a freeze authority.
+    pub reject_freeze_authority: bool,
+    /// The fewest decimals a mint may have for an interest vault to be created for it.
+    pub min_mint_decimals: u8,
+    /// Whether interest vaults can only be created for mints on the approved list.
+    pub require_approved_mint: bool,
+    /// Zeroed space set aside so new fields can be added without reallocating.
+    pub reserved: [u8; 76],
+}
+
+impl State {
+    pub const SPACE: usize = 8 +   // anchor account discriminator
+        32 +   // authority
+        1 +    // version
+        33 +   // allowlist_verifier
+        8 +    // config_timelock_delay
+        8 +    // next_config_change_id
+        1 +    // reject_freeze_authority
+        1 +    // min_mint_decimals
+        1 +    // require_approved_mint
+        76; // reserved
+}
+
+#[account]
+/// This account is a PDA unique to a single (state, mint) pair, and is authority
+/// of the vault from which interest tokens are paid.
+pub struct InterestDistributor {
+    /// The state's public key.
+    pub state: Pubkey,
+    /// The mint of the token account this distributor serves as authority for.
+    pub mint: Pubkey,
+    /// Bump of this account's PDA. Stored to avoid deriving it everytime a signature
+    /// is required.
+    pub bump: u8,
+    /// Layout version of this account.
+    pub version: u8,
+    /// The number of seconds users must wait between requesting and executing a withdrawal.
+    /// Zero if withdrawals are immediate.
+    pub withdrawal_notice_period: i64,
+    /// The share of their savings, in basis points, users forfeit to the interest vault for
+    /// cancelling a savings goal before reaching it.
+    pub goal_cancellation_penalty_bps: u16,
+    /// The share of a referred user's interest, in basis points, paid to their referrer.
+    pub referral_bonus_bps: u16,
+    /// The maximum total bonus paid out per referred vault, or zero if uncapped.
+    pub referral_bonus_cap: u64,
+    /// The number of seconds after a referred vault's creation during which its referrer earns
+    /// bonuses, or zero if referrals never expire.
+    pub referral_window: i64,
+    /// Whether users need a valid allowlist entry to create vaults and deposit.
+    pub allowlist_required: bool,
+    /// The share of each interest payment, in basis points, taken as a performance fee.
+    pub performance_fee_bps: u16,
+    /// The token account performance fees are paid to.
+    pub fee_destination: Option<Pubkey>,
+    /// The total performance fees paid out by this distributor.
+    pub total_performance_fees: u64,
+    /// The share of each withdrawal, in basis points, paid into the interest vault as a fee.
+    pub withdrawal_fee_bps: u16,
+    /// The number of seconds after a user's last deposit from which their withdrawals are free,
+    /// or zero if the withdrawal fee is never waived.
+    pub withdrawal_fee_waiver_period: i64,
+    /// The mint of the receipt tokens issued for deposits, if the distributor issues any.
+    pub receipt_mint: Option<Pubkey>,
+    /// Tells apart the products a state runs for the same mint. Distributors created before
+    /// product ids were introduced are product zero.
+    pub product_id: u16,
+    /// The maximum amount a single user may withdraw in any `USER_WITHDRAWAL_LIMIT_WINDOW`, or zero
+    /// if unlimited.
+    pub user_withdrawal_limit: u64,
+    /// The maximum amount withdrawn from this distributor's savings vaults during any outflow
+    /// window, or zero if unlimited.
+    pub outflow_limit: u64,
+    /// The length of the rolling outflow window, in seconds.
+    pub outflow_window: i64,
+    /// The unix timestamp at which the newest bucket of the outflow window started.
+    pub outflow_window_start: i64,
+    /// The amount withdrawn during the outflow window, as of the last withdrawal.
+    pub outflow_in_window: u64,
+    /// The number of pages in this distributor's registry of savings-managers.
+    pub registry_pages: u32,
+    /// The amounts withdrawn during each bucket of the outflow window.
+    pub outflow_buckets: [u64; WITHDRAWAL_WINDOW_BUCKETS],
+    /// Zeroed space set aside so new fields can be added without reallocating.
+    pub reserved: [u8; 10],
+}
+
+impl InterestDistributor {
+    pub const SPACE: usize = 8 +   // anchor account discriminator
+        32 +   // state
+        32 +   // mint
+        1 +    // bump
+        1 +    // version
+        8 +    // withdrawal_notice_period
+        2 +    // goal_cancellation_penalty_bps
+        2 +    // referral_bonus_bps
+        8 +    // referral_bonus_cap
+        8 +    // referral_window
+        1 +    // allowlist_required
+        2 +    // performance_fee_bps
+        33 +   // fee_destination
+        8 +    // total_performance_fees
+        2 +    // withdrawal_fee_bps
+        8 +    // withdrawal_fee_waiver_period
+        33 +   // receipt_mint
+        2 +    // product_id
+        8 +    // user_withdrawal_limit
+        8 +    // outflow_limit
+        8 +    // outflow_window
+        8 +    // outflow_window_start
+        8 +    // outflow_in_window
+        4 +    // registry_pages
+        48 +   // outflow_buckets
+        10; // reserved
+
+    /// The PDA seed identifying product `product_id` among a state's distributors for a mint.
+    /// Product zero's seed is empty, so distributors created before product ids were introduced
+    /// keep their addresses.
+    pub fn product_seed(product_id: u16) -> Vec<u8> {
+        if product_id == 0 {
+            Vec::new()
+        } else {
+            product_id.to_le_bytes().to_vec()
+        }
+    }
+
+    /// The fee charged on a withdrawal of `amount` at time `now` from a vault last deposited into
+    /// at `last_deposit_ts`.
+    pub fn withdrawal_fee(&self, amount: u64, last_deposit_ts: i64, now: i64) -> u64 {
+        let waived_at = last_deposit_ts
+            .checked_add(self.withdrawal_fee_waiver_period)
+            .unwrap();
+        if self.withdrawal_fee_waiver_period > 0 && now >= waived_at {
+            return 0;
+        }
+
+        (self.withdrawal_fee_bps as u64)
+            .checked_mul(amount)
+            .unwrap()
+            .checked_div(MAX_BPS as u64)
+            .unwrap()
+    }
+
+    /// The performance fee taken from an interest payment of `gross_interest`.
+    pub fn performance_fee(&self, gross_interest: u64) -> u64 {
+        (self.performance_fee_bps as u64)
+            .checked_mul(gross_interest)
+            .unwrap()
+            .checked_div(MAX_BPS as u64)
+            .unwrap()
+    }
+
+    /// The bonus owed to `manager`'s referrer on an `interest_amount` payout at time `now`, or zero
+    /// if it has no referrer, or the referral has expired or reached its cap.
+    pub fn referral_bonus(&self, manager: &SavingsManager, interest_amount: u64, now: i64) -> u64 {
+        if manager.referrer.is_none() {
+            return 0;
+        }
+
+        let expires_at = manager
+            .referral_start_ts
+            .checked_add(self.referral_window)
+            .unwrap();
+        if self.referral_window > 0 && now > expires_at {
+            return 0;
+        }
+
+        let bonus = (self.referral_bonus_bps as u64)
+            .checked_mul(interest_amount)
+            .unwrap()
+            .checked_div(MAX_BPS as u64)
+            .unwrap();
+        if self.referral_bonus_cap > 0 {
+            let remaining = self
+                .referral_bonus_cap
+                .saturating_sub(manager.referral_bonus_paid);
+            return bonus.min(remaining);
+        }
+        bonus
+    }
+
+    /// Charges a withdrawal of `amount` against the distributor's outflow limit, which applies to
+    /// the rolling outflow window ending at `now`.
+    pub fn record_outflow(&mut self, amount: u64, now: i64) -> Result<()> {
+        if self.outflow_limit == 0 {
+            return Ok(());
+        }
+        let (bucket, outflow_in_window) = roll_withdrawal_window(
+            &mut self.outflow_buckets,
+            &mut self.outflow_window_start,
+            self.outflow_window,
+            now,
+        );
+
+        let outflow = outflow_in_window.checked_add(amount).unwrap();
+        if outflow > self.outflow_limit {
+            msg!(
+                "Outflow limit: {}. Outflow this window: {}. Requested: {}",
+                self.outflow_limit,
+                outflow_in_window,
+                amount
+            );
+            return Err(SavingsError::DistributorOutflowLimitExceeded.into());
+        }
+        self.outflow_buckets[bucket] = self.outflow_buckets[bucket].checked_add(amount).unwrap();
+        self.outflow_in_window = outflow;
+
+        Ok(())
+    }
+}
+
+#[account]
+/// Account holding information for a user-owned vault. This is a PDA unique to a
+/// (user, interest-distributor) pair.
+pub struct SavingsManager {
+    /// The owner of the vault.
+    pub user: Pubkey,
+    /// The mint of the vault.
+    pub mint: Pubkey,
+    /// The distributor for this user's vault, responsible for disbursing its interest payments.
+    pub distributor: Pubkey,
+    /// The unix timestamp of the last interest deposit.
+    pub last_interest_deposit_ts: i64,
+    /// Bump of this account's PDA. Stored to avoid deriving it everytime a signature
+    /// is required.
+    pub bump: u8,
+    /// Layout version of this account.
+    pub version: u8,
+    /// A second key allowed to withdraw from the vault on the user's behalf.
+    pub delegate: Option<Pubkey>,
+    /// The maximum amount the delegate may withdraw per period.
+    pub delegate_allowance: u64,
+    /// The length of a delegate allowance period, in seconds.
+    pub delegate_period: i64,
+    /// The unix timestamp at which the current delegate allowance period started.
+    pub delegate_period_start: i64,
+    /// The amount withdrawn by the delegate during the current period.
+    pub delegate_withdrawn: u64,
+    /// The amount of a pending withdrawal request. These tokens no longer earn interest.
+    pub pending_withdrawal_amount: u64,
+    /// The unix timestamp at which the pending withdrawal was requested.
+    pub pending_withdrawal_ts: i64,
+    /// The savings goal's target balance, or zero if the goal has no target balance.
+    pub goal_amount: u64,
+    /// The savings goal's target unix timestamp, or zero if the goal has no target date.
+    pub goal_ts: i64,
+    /// Whether the savings goal has been reached. Once reached, the vault stays unlocked.
+    pub goal_reached: bool,
+    /// The user who referred the owner of this vault.
+    pub referrer: Option<Pubkey>,
+    /// The unix timestamp from which the referral window is measured.
+    pub referral_start_ts: i64,
+    /// The total referral bonus paid out to the referrer on behalf of this vault.
+    pub referral_bonus_paid: u64,
+    /// Whether the vault has been frozen by the state's authority.
+    pub frozen: bool,
+    /// The reason code given for the current freeze, or zero if the vault isn't frozen.
+    pub freeze_reason_code: u16,
+    /// The unix timestamp of the last deposit into the vault.
+    pub last_deposit_ts: i64,
+    /// Whether the user has opted out of deposits made by anyone else.
+    pub third_party_deposits_disabled: bool,
+    /// The unix timestamp at which the newest bucket of the withdrawal limit window started.
+    pub withdrawal_window_start: i64,
+    /// The amount withdrawn from the vault during the withdrawal limit window, as of the last
+    /// withdrawal.
+    pub withdrawn_in_window: u64,
+    /// Tells apart the sub-accounts a user holds with the same distributor. Vaults created before
+    /// sub-accounts were introduced are sub-account zero.
+    pub index: u16,
+    /// A name the user gave the sub-account, as zero-padded UTF-8, or all zeros if unnamed.
+    pub label: [u8; 32],
+    /// Whether the vault is listed in its distributor's registry of savings-managers.
+    pub registered: bool,
+    /// The registry page the vault is listed on, if it is listed.
+    pub registry_page: u32,
+    /// The amounts withdrawn from the vault during each bucket of the withdrawal limit window.
+    pub withdrawal_buckets: [u64; WITHDRAWAL_WINDOW_BUCKETS],
+    /// Zeroed space set aside so new fields can be added without reallocating.
+    pub reserved: [u8; 19],
+}
+
+impl SavingsManager {
+    pub const SPACE: usize = 8 +   // anchor account discriminator
+        32 +   // user
+        32 +   // mint
+        32 +   // distributor
+        8 +    // last_interest_deposit_ts
+        1 +    // bump
+        1 +    // version
+        33 +   // delegate
+        8 +    // delegate_allowance
+        8 +    // delegate_period
+        8 +    // delegate_period_start
+        8 +    // delegate_withdrawn
+        8 +    // pending_withdrawal_amount
+        8 +    // pending_withdrawal_ts
+        8 +    // goal_amount
+        8 +    // goal_ts
+        1 +    // goal_reached
+        33 +   // referrer
+        8 +    // referral_start_ts
+        8 +    // referral_bonus_paid
+        1 +    // frozen
+        2 +    // freeze_reason_code
+        8 +    // last_deposit_ts
+        1 +    // third_party_deposits_disabled
+        8 +    // withdrawal_window_start
+        8 +    // withdrawn_in_window
+        2 +    // index
+        32 +   // label
+        1 +    // registered
+        4 +    // registry_page
+        48 +   // withdrawal_buckets
+        19; // reserved
+
+    /// The PDA seed identifying sub-account `index` among a user's vaults with a distributor.
+    /// Sub-account zero's seed is empty, so vaults created before sub-accounts were introduced
+    /// keep their addresses.
+    pub fn index_seed(index: u16) -> Vec<u8> {
+        if index == 0 {
+            Vec::new()
+        } else {
+            index.to_le_bytes().to_vec()
+        }
+    }
+
+    /// Whether the vault is locked by a savings goal that hasn't been reached yet.
+    pub fn has_active_goal(&self) -> bool {
+        (self.goal_amount > 0 || self.goal_ts > 0) && !self.goal_reached
+    }
+
+    /// Whether either of the savings goal's targets is met by `balance` at time `now`.
+    pub fn goal_met(&self, balance: u64, now: i64) -> bool {
+        (self.goal_amount > 0 && balance >= self.goal_amount)
+            || (self.goal_ts > 0 && now >= self.goal_ts)
+    }
+
+    /// The part of a vault's balance that earns interest, excluding any amount pending withdrawal.
+    pub fn interest_bearing_balance(&self, vault_amount: u64) -> u64 {
+        vault_amount.saturating_sub(self.pending_withdrawal_amount)
+    }
+
+    /// Charges a delegate withdrawal of `amount` against the delegate's allowance, starting a new
+    /// period first if the current one has elapsed.
+    pub fn consume_delegate_allowance(&mut self, amount: u64, now: i64) -> Result<()> {
+        let elapsed = now.checked_sub(self.delegate_period_start).unwrap();
+        if elapsed >= self.delegate_period {
+            let periods_elapsed = elapsed.checked_div(self.delegate_period).unwrap();
+            self.delegate_period_start = self
+                .delegate_period_start
+                .checked_add(periods_elapsed.checked_mul(self.delegate_period).unwrap())
+                .unwrap();
+            self.delegate_withdrawn = 0;
+        }
+
+        let withdrawn = self.delegate_withdrawn.checked_add(amount).unwrap();
+        if withdrawn > self.delegate_allowance {
+            msg!(
+                "Delegate allowance: {}. Already withdrawn this period: {}. Requested: {}",
+                self.delegate_allowance,
+                self.delegate_withdrawn,
+                amount
+            );
+            return Err(SavingsError::DelegateAllowanceExceeded.into());
+        }
+        self.delegate_withdrawn = withdrawn;
+
+        Ok(())
+    }
+
+    /// Charges a withdrawal of `amount` against the per-user withdrawal `limit`, which applies to
+    /// the rolling `USER_WITHDRAWAL_LIMIT_WINDOW` ending at `now`.
+    pub fn record_withdrawal(&mut self, amount: u64, limit: u64, now: i64) -> Result<()> {
+        if limit == 0 {
+            return Ok(());
+        }
+        let (bucket, withdrawn_in_window) = roll_withdrawal_window(
+            &mut self.withdrawal_buckets,
+            &mut self.withdrawal_window_start,
+            USER_WITHDRAWAL_LIMIT_WINDOW,
+            now,
+        );
+
+        let withdrawn = withdrawn_in_window.checked_add(amount).unwrap();
+        if withdrawn > limit {
+            msg!(
+                "Withdrawal limit: {}. Already withdrawn this window: {}. Requested: {}",
+                limit,
+                withdrawn_in_window,
+                amount
+            );
+            return Err(SavingsError::UserWithdrawalLimitExceeded.into());
+        }
+        self.withdrawal_buckets[bucket] =
+            self.withdrawal_buckets[bucket].checked_add(amount).unwrap();
+        self.withdrawn_in_window = withdrawn;
+
+        Ok(())
+    }
+}
+
+#[account]
+/// A recurring contribution into a user's savings vault. This is a PDA unique to a single
+/// savings-manager.
+pub struct SavingsPlan {
+    /// The owner of the savings vault being contributed to.
+    pub user: Pubkey,
+    /// The savings-manager of the vault being contributed to.
+    pub savings_manager: Pubkey,
+    /// The token account contributions are pulled from. The savings-manager must be approved as
+    /// its delegate for at least `amount` tokens.
+    pub source_token_account: Pubkey,
+    /// The number of tokens contributed each interval.
+    pub amount: u64,
+    /// The number of seconds between contributions.
+    pub interval: i64,
+    /// The unix timestamp from which the next contribution can be pulled.
+    pub next_contribution_ts: i64,
+    /// Bump of this account's PDA.
+    pub bump: u8,
+    /// Layout version of this account.
+    pub version: u8,
+    /// Zeroed space set aside so new fields can be added without reallocating.
+    pub reserved: [u8; 64],
+}
+
+impl SavingsPlan {
+    pub const SPACE: usize = 8 +   // anchor account discriminator
+        32 +   // user
+        32 +   // savings_manager
+        32 +   // source_token_account
+        8 +    // amount
+        8 +    // interval
+        8 +    // next_contribution_ts
+        1 +    // bump
+        1 +    // version
+        64; // reserved
+}
+
+#[account]
+/// Marks a user as verified for the allowlist-gated distributors of a state. This is a PDA unique
+/// to a (state, user) pair.
+pub struct AllowlistEntry {
+    /// The state the user is allowlisted for.
+    pub state: Pubkey,
+    /// The allowlisted user.
+    pub user: Pubkey,
+    /// The unix timestamp at which this entry stops being valid, or zero if it never expires.
+    pub expires_at: i64,
+    /// Bump of this account's PDA.
+    pub bump: u8,
+    /// Layout version of this account.
+    pub version: u8,
+    /// Zeroed space set aside so new fields can be added without reallocating.
+    pub reserved: [u8; 32],
+}
+
+impl AllowlistEntry {
+    pub const SPACE: usize = 8 +   // anchor account discriminator
+        32 +   // state
+        32 +   // user
+        8 +    // expires_at
+        1 +    // bump
+        1 +    // version
+        32; // reserved
+}
+
+#[account]
+/// Marks a mint as approved for interest vaults under a state. This is a PDA unique to a
+/// (state, mint) pair.
+pub struct ApprovedMint {
+    /// The state the mint is approved under.
+    pub state: Pubkey,
+    /// The approved mint.
+    pub mint: Pubkey,
+    /// Bump of this account's PDA.
+    pub bump: u8,
+    /// Layout version of this account.
+    pub version: u8,
+    /// Zeroed space set aside so new fields can be added without reallocating.
+    pub reserved: [u8; 32],
+}
+
+impl ApprovedMint {
+    pub const SPACE: usize = 8 +   // anchor account discriminator
+        32 +   // state
+        32 +   // mint
+        1 +    // bump
+        1 +    // version
+        32; // reserved
+}
+
+#[account]
+/// A one-off bonus interest campaign, paying allocations committed to by a merkle root into users'
+/// savings vaults. This is a PDA unique to a (distributor, campaign id) pair, and is authority of
+/// the vault the allocations are paid from.
+///
+/// Leaves are `sha256(0x00 || index || user || amount)`, with integers little-endian and `index` a
+/// `u32`, and nodes are `sha256(0x01 || a || b)` of their children sorted so that `a <= b`.
+pub struct Campaign {
+    /// The distributor whose users the campaign pays.
+    pub distributor: Pubkey,
+    /// Identifies the campaign among the distributor's campaigns.
+    pub campaign_id: u64,
+    /// Root of the merkle tree of allocations.
+    pub merkle_root: [u8; 32],
+    /// Bump of this account's PDA. Stored to avoid deriving it everytime a signature
+    /// is required.
+    pub bump: u8,
+    /// Layout version of this account.
+    pub version: u8,
+    /// The number of leaves in the merkle tree.
+    pub max_claims: u32,
+    /// The total amount paid out so far.
+    pub total_claimed: u64,
+    /// Unix timestamp from which allocations can no longer be claimed and the campaign can be
+    /// closed. Zero for campaigns created before expiry was introduced, which never expire.
+    pub expires_ts: i64,
+    /// Zeroed space set aside so new fields can be added without reallocating.
+    pub reserved: [u8; 24],
+    /// One bit per leaf, set once the leaf's allocation has been claimed.
+    pub claimed: Vec<u8>,
+}
+
+impl Campaign {
+    /// The space taken by a campaign of `max_claims` leaves.
+    pub fn space(max_claims: u32) -> usize {
+        8 +    // anchor account discriminator
+        32 +   // distributor
+        8 +    // campaign_id
+        32 +   // merkle_root
+        1 +    // bump
+        1 +    // version
+        4 +    // max_claims
+        8 +    // total_claimed
+        8 +    // expires_ts
+        24 +   // reserved
+        4 + Self::bitmap_len(max_claims) // claimed
+    }
+
+    /// The length in bytes of the claimed bitmap for `max_claims` leaves.
+    pub fn bitmap_len(max_claims: u32) -> usize {
+        (max_claims as usize).saturating_add(7) / 8
+    }
+
+    /// The leaf committing to an allocation of `amount` to `user` at `index`.
+    pub fn leaf(index: u32, user: &Pubkey, amount: u64) -> [u8; 32] {
+        hashv(&[
+            &[0],
+            &index.to_le_bytes(),
+            user.as_ref(),
+            &amount.to_le_bytes(),
+        ])
+        .to_bytes()
+    }
+
+    /// The parent of two sibling nodes.
+    pub fn hash_nodes(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+        let (a, b) = if a <= b { (a, b) } else { (b, a) };
+        hashv(&[&[1], a, b]).to_bytes()
+    }
+
+    /// Whether `proof` shows `leaf` to be part of the campaign's merkle tree.
+    pub fn verify(&self, leaf: [u8; 32], proof: &[[u8; 32]]) -> bool {
+        let root = proof
+            .iter()
+            .fold(leaf, |node, sibling| Self::hash_nodes(&node, sibling));
+        root == self.merkle_root
+    }
+
+    /// Marks the allocation at `index` as claimed, failing if it already was.
+    pub fn set_claimed(&mut self, index: u32) -> Result<()> {
+        require!(index < self.max_claims, SavingsError::InvalidCampaignProof);
+        let byte = &mut self.claimed[index as usize / 8];
+        let bit = 1 << (index % 8);
+        require!(*byte & bit == 0, SavingsError::CampaignAlreadyClaimed);
+        *byte |= bit;
+        Ok(())
+    }
+}
+
+#[account]
+/// One page of a distributor's registry of savings-managers, which lets cranks find every vault
+/// under the distributor by walking pages `0..registry_pages` instead of scanning program
+/// accounts. This is a PDA unique to a single (distributor, page) pair.
+pub struct RegistryPage {
+    /// The distributor whose savings-managers are listed.
+    pub distributor: Pubkey,
+    /// The position of this page in the registry.
+    pub page: u32,
+    /// Bump of this account's PDA.
+    pub bump: u8,
+    /// Layout version of this account.
+    pub version: u8,
+    /// Zeroed space set aside so new fields can be added without reallocating.
+    pub reserved: [u8; 32],
+    /// The savings-managers listed on this page, in no particular order.
+    pub managers: Vec<Pubkey>,
+}
+
+impl RegistryPage {
+    pub const SPACE: usize = 8 +   // anchor account discriminator
+        32 +   // distributor
+        4 +    // page
+        1 +    // bump
+        1 +    // version
+        32 +   // reserved
+        4 + REGISTRY_PAGE_CAPACITY * 32; // managers
+
+    /// Lists `manager` on this page.
+    pub fn push(&mut self, manager: Pubkey) -> Result<()> {
+        require!(
+            self.managers.len() < REGISTRY_PAGE_CAPACITY,
+            SavingsError::RegistryPageFull
+        );
+        self.managers.push(manager);
+        Ok(())
+    }
+
+    /// Removes `manager` from this page, if it is listed.
+    pub fn remove(&mut self, manager: &Pubkey) {
+        if let Some(position) = self.managers.iter().position(|m| m == manager) {
+            self.managers.swap_remove(position);
+        }
+    }
+}
+
+#[account]
+/// A distributor's adapter to an external yield program, which idle interest vault funds can be
+/// lent to. This is a PDA unique to a single distributor. It is the only signer the yield program
+/// ever sees, and the authority of the yield vault, its associated token account, through which
+/// funds move to and from the yield program.
+pub struct YieldAdapter {
+    /// The distributor whose interest vault funds are lent out.
+    pub distributor: Pubkey,
+    /// Bump of this account's PDA.
+    pub bump: u8,
+    /// Layout version of this account.
+    pub version: u8,
+    /// The yield program, or `None` if no yield source is configured.
+    pub program: Option<Pubkey>,
+    /// Discriminator of the yield program's deposit instruction, which takes the amount to
+    /// deposit as its only argument.
+    pub deposit_ix: [u8; 8],
+    /// Discriminator of the yield program's redeem instruction, which takes the amount to redeem
+    /// as its only argument.
+    pub redeem_ix: [u8; 8],
+    /// The principal currently lent to the yield program.
+    pub allocated: u64,
+    /// Zeroed space set aside so new fields can be added without reallocating.
+    pub reserved: [u8; 64],
+}
+
+impl YieldAdapter {
+    pub const SPACE: usize = 8 +   // anchor account discriminator
+        32 +   // distributor
+        1 +    // bump
+        1 +    // version
+        33 +   // program
+        8 +    // deposit_ix
+        8 +    // redeem_ix
+        8 +    // allocated
+        64; // reserved
+}
+
+#[account]
+/// A benchmark interest rate published by an off-chain source. Distributors may follow a feed
+/// hosted by this program or by any other, as long as it has this layout:
+///
+/// | Offset | Size | Field                                                            |
+/// |--------|------|------------------------------------------------------------------|
+/// | 0      | 8    | Discriminator, the first 8 bytes of `sha256("account:RateFeed")` |
+/// | 8      | 2    | `rate_bps`, little-endian `u16`                                  |
+/// | 10     | 8    | `last_update_ts`, little-endian `i64`                            |
+/// | 18     | 32   | `publisher`                                                      |
+///
+/// Only these first 50 bytes are read. Anything after them is ignored, so feeds may carry further
+/// fields; feeds hosted by this program also keep a version and reserved space there.
+pub struct RateFeed {
+    /// The published monthly interest rate, in basis points.
+    pub rate_bps: u16,
+    /// The unix timestamp at which the rate was last published.
+    pub last_update_ts: i64,
+    /// The key allowed to publish rates to this feed.
+    pub publisher: Pubkey,
+    /// Layout version of this account.
+    pub version: u8,
+    /// Zeroed space set aside so new fields can be added without reallocating.
+    pub reserved: [u8; 32],
+}
+
+impl RateFeed {
+    pub const SPACE: usize = 8 +   // anchor account discriminator
+        2 +    // rate_bps
+        8 +    // last_update_ts
+        32 +   // publisher
+        1 +    // version
+        32; // reserved
+
+    /// The length of the layout that distributors rely on, documented on [`RateFeed`].
+    pub const PUBLISHED_LEN: usize = 8 + 2 + 8 + 32;
+
+    /// Reads the rate and last update timestamp out of a feed account's `data`, following only the
+    /// documented layout.
+    pub fn read_published(data: &[u8]) -> Result<(u16, i64)> {
+        if data.len() < 8 || data[..8] != Self::DISCRIMINATOR {
+            return Err(ErrorCode::AccountDiscriminatorMismatch.into());
+        }
+        if data.len() < Self::PUBLISHED_LEN {
+            return Err(ErrorCode::AccountDidNotDeserialize.into());
+        }
+        let rate_bps = u16::from_le_bytes(data[8..10].try_into().unwrap());
+        let last_update_ts = i64::from_le_bytes(data[10..18].try_into().unwrap());
+        Ok((rate_bps, last_update_ts))
+    }
+}
+
+#[account]
+/// The interest rates a distributor has paid over time, kept as a ring buffer of its most recent
+/// `RATE_HISTORY_CAPACITY` rate changes. This is a PDA unique to a single distributor.
+pub struct RateHistory {
+    /// The distributor whose rates are recorded.
+    pub distributor: Pubkey,
+    /// Bump of this account's PDA.
+    pub bump: u8,
+    /// Layout version of this account.
+    pub version: u8,
+    /// Index of the most recent entry in `entries`.
+    pub head: u8,
+    /// The number of entries in use.
+    pub len: u8,
+    /// The recorded rates, in the order they took effect modulo wrap-around.
+    pub entries: [RateEntry; RATE_HISTORY_CAPACITY],
+    /// The rate feed the distributor's rate follows, if any. Its rate is recorded here whenever
+    /// interest is deposited.
+    pub rate_feed: Option<Pubkey>,
+    /// The maximum age, in seconds, of a rate feed update that interest may be deposited with.
+    pub max_feed_staleness: i64,
+    /// The lowest rate, in basis points, taken from the rate feed.
+    pub min_rate_bps: u16,
+    /// The highest rate, in basis points, taken from the rate feed.
+    pub max_rate_bps: u16,
+    /// Zeroed space set aside so new fields can be added without reallocating.
+    pub reserved: [u8; 19],
+}
+
+impl RateHistory {
+    pub const SPACE: usize = 8 +   // anchor account discriminator
+        32 +   // distributor
+        1 +    // bump
+        1 +    // version
+        1 +    // head
+        1 +    // len
+        RATE_HISTORY_CAPACITY * RateEntry::SPACE + // entries
+        33 +   // rate_feed
+        8 +    // max_feed_staleness
+        2 +    // min_rate_bps
+        2 +    // max_rate_bps
+        19; // reserved
+
+    /// The monthly interest rate currently in effect, in basis points.
+    pub fn current_rate(&self) -> u16 {
+        self.entries[self.head as usize].rate_bps
+    }
+
+    /// Records a new rate effective from `effective_ts`, overwriting the oldest entry when full.
+    pub fn push(&mut self, rate_bps: u16, effective_ts: i64) {
+        if self.len > 0 {
+            self.head = ((self.head as usize + 1) % RATE_HISTORY_CAPACITY) as u8;
+        }
+        self.entries[self.head as usize] = RateEntry {
+            rate_bps,
+            effective_ts,
+        };
+        self.len = (self.len as usize + 1).min(RATE_HISTORY_CAPACITY) as u8;
+    }
+
+    /// The interest `balance` earns between `from` and `to`, with each second accruing at the
+    /// rate in effect at the time. Time before the oldest remembered entry accrues at that
+    /// entry's rate. Fails if the interest doesn't fit in a token amount.
+    pub fn accrued_interest(&self, balance: u64, from: i64, to: i64) -> Result<u64> {
+        let len = self.len as usize;
+        let oldest = (self.head as usize + RATE_HISTORY_CAPACITY + 1 - len) % RATE_HISTORY_CAPACITY;
+
+        // The sum of rate * seconds over the interval, in basis-point-seconds.
+        let mut weighted_rate: u128 = 0;
+        for i in 0..len {
+            let entry = &self.entries[(oldest + i) % RATE_HISTORY_CAPACITY];
+            let start = if i == 0 {
+                from
+            } else {
+                entry.effective_ts.max(from)
+            };
+            let end = if i + 1 < len {
+                self.entries[(oldest + i + 1) % RATE_HISTORY_CAPACITY]
+                    .effective_ts
+                    .min(to)
+            } else {
+                to
+            };
+            if end > start {
+                weighted_rate += entry.rate_bps as u128 * (end - start) as u128;
+            }
+        }
+
+        let interest =
+            balance as u128 * weighted_rate / (SECONDS_IN_MONTHS as u128 * MAX_BPS as u128);
+        u64::try_from(interest).map_err(|_| SavingsError::InterestOverflow.into())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+/// A monthly interest rate and the unix timestamp from which it applied.
+pub struct RateEntry {
+    pub rate_bps: u16,
+    pub effective_ts: i64,
+}
+
+impl RateEntry {
+    pub const SPACE: usize = 2 + // rate_bps
+        8; // effective_ts
+}
+
+#[account]
+/// A configuration change waiting out the state's timelock. This is a PDA unique to a (state, id)
+/// pair, so any number of changes can be pending at once.
+pub struct PendingConfigChange {
+    /// The state the change was queued under.
+    pub state: Pubkey,
+    /// The distributor the change applies to, or `None` if it applies to the state itself.
+    pub interest_distributor: Option<Pubkey>,
+    /// The sequential id of this change within its state.
+    pub id: u64,
+    /// The change to apply.
+    pub change: ConfigChange,
+    /// The unix timestamp at which the change was queued.
+    pub queued_ts: i64,
+    /// The unix timestamp from which the change can be executed.
+    pub eta: i64,
+    /// Bump of this account's PDA.
+    pub bump: u8,
+    /// Layout version of this account.
+    pub version: u8,
+    /// Zeroed space set aside so new fields can be added without reallocating.
+    pub reserved: [u8; 32],
+}
+
+impl PendingConfigChange {
+    pub const SPACE: usize = 8 +   // anchor account discriminator
+        32 +   // state
+        33 +   // interest_distributor
+        8 +    // id
+        64 +   // change, padded so larger variants can be added
+        8 +    // queued_ts
+        8 +    // eta
+        1 +    // bump
+        1 +    // version
+        32; // reserved
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+/// An admin configuration change, applied through the timelocked config-change queue.
+pub enum ConfigChange {
+    /// How long, in seconds, users of a distributor must wait between requesting a withdrawal and
+    /// executing it. A notice period of zero allows immediate withdrawals via `user_withdraw`.
+    WithdrawalNoticePeriod { notice_period: i64 },
+    /// The share of their savings, in basis points, that users of a distributor forfeit to its
+    /// interest vault when they cancel a savings goal before reaching it.
+    GoalCancellationPenalty { penalty_bps: u16 },
+    /// A distributor's referral program. Referrers earn `bonus_bps` of the interest paid to the
+    /// users they referred, on top of that interest, for `window` seconds after each referred vault
+    /// is created and up to `bonus_cap` tokens per referred vault. A zero window or cap means no
+    /// limit, and zero basis points disables referral bonuses.
+    ReferralProgram {
+        bonus_bps: u16,
+        bonus_cap: u64,
+        window: i64,
+    },
+    /// Whether users of a distributor need a valid allowlist entry to create vaults and deposit.
+    AllowlistRequired { required: bool },
+    /// A key that, alongside the state's authority, may add and remove allowlist entries.
+    AllowlistVerifier { verifier: Option<Pubkey> },
+    /// The minimum delay, in seconds, between queueing and executing later changes.
+    TimelockDelay { delay: i64 },
+    /// The checks a mint must pass for an interest vault to be created for it: having no freeze
+    /// authority, having at least `min_decimals` decimals, and being on the approved list.
+    MintPolicy {
+        reject_freeze_authority: bool,
+        min_decimals: u8,
+        require_approved_mint: bool,
+    },
+    /// The performance fee a distributor takes from each interest payment, in basis points of the
+    /// gross interest, and the token account it is paid to. The fee destination is required
+    /// unless the fee is zero.
+    PerformanceFee {
+        fee_bps: u16,
+        fee_destination: Option<Pubkey>,
+    },
+    /// The share of each withdrawal, in basis points, that users of a distributor pay into its
+    /// interest vault. Withdrawals made `waiver_period` seconds or more after the vault's last
+    /// deposit are free, unless the waiver period is zero.
+    WithdrawalFee { fee_bps: u16, waiver_period: i64 },
+    /// Rolling limits on withdrawals from a distributor's savings vaults: at most `user_limit`
+    /// tokens per user in any `USER_WITHDRAWAL_LIMIT_WINDOW`, and at most `outflow_limit` tokens
+    /// across all users in any `outflow_window` seconds. Zero limits disable the corresponding
+    /// check.
+    WithdrawalLimits {
+        user_limit: u64,
+        outflow_limit: u64,
+        outflow_window: i64,
+    },
+    /// The monthly interest rate, in basis points, a distributor pays on savings. Recorded in the
+    /// distributor's rate history, so interest already accrued is paid at the old rate.
+    InterestRate { rate_bps: u16 },
+    /// The rate feed a distributor's interest rate follows, or `None` to keep a fixed rate. Feed
+    /// rates are clamped to `min_rate_bps..=max_rate_bps`, and interest can't be deposited while
+    /// the feed's last update is more than `max_staleness` seconds old.
+    RateFeed {
+        feed: Option<Pubkey>,
+        max_staleness: i64,
+        min_rate_bps: u16,
+        max_rate_bps: u16,
+    },
+    /// The external program a distributor lends idle interest vault funds to, and the
+    /// discriminators of its deposit and redeem instructions. Can't be changed while funds are
+    /// allocated to the current program.
+    YieldSource {
+        program: Option<Pubkey>,
+        deposit_ix: [u8; 8],
+        redeem_ix: [u8; 8],
+    },
+}
+
+impl ConfigChange {
+    /// Whether this change applies to a distributor rather than to the state itself.
+    pub fn targets_distributor(&self) -> bool {
+        !matches!(
+            self,
+            ConfigChange::AllowlistVerifier { .. }
+                | ConfigChange::TimelockDelay { .. }
+                | ConfigChange::MintPolicy { .. }
+        )
+    }
+
+    /// Checks that the change's parameters are within bounds.
+    pub fn validate(&self) -> Result<()> {
+        match *self {
+            ConfigChange::WithdrawalNoticePeriod { notice_period } => {
+                require!(notice_period >= 0, SavingsError::InvalidNoticePeriod);
+            }
+            ConfigChange::GoalCancellationPenalty { penalty_bps } => {
+                require!(penalty_bps <= MAX_BPS, SavingsError::InvalidBasisPoints);
+            }
+            ConfigChange::ReferralProgram {
+                bonus_bps, window, ..
+            } => {
+                require!(bonus_bps <= MAX_BPS, SavingsError::InvalidBasisPoints);
+                require!(window >= 0, SavingsError::InvalidReferralWindow);
+            }
+            ConfigChange::TimelockDelay { delay } => {
+                require!(delay >= 0, SavingsError::InvalidTimelockDelay);
+            }
+            ConfigChange::InterestRate { rate_bps } => {
+                require!(rate_bps <= MAX_BPS, SavingsError::InvalidBasisPoints);
+            }
+            ConfigChange::RateFeed {
+                feed,
+                max_staleness,
+                min_rate_bps,
+                max_rate_bps,
+            } => {
+                require!(max_rate_bps <= MAX_BPS, SavingsError::InvalidBasisPoints);
+                require!(
+                    min_rate_bps <= max_rate_bps,
+                    SavingsError::InvalidRateFeedConfig
+                );
+                require!(
+                    feed.is_none() || max_staleness > 0,
+                    SavingsError::InvalidRateFeedConfig
+                );
+            }
+            ConfigChange::WithdrawalFee {
+                fee_bps,
+                waiver_period,
+            } => {
+                require!(fee_bps <= MAX_BPS, SavingsError::InvalidBasisPoints);
+                require!(waiver_period >= 0, SavingsError::InvalidWaiverPeriod);
+            }
+            ConfigChange::WithdrawalLimits {
+                outflow_limit,
+                outflow_window,
+                ..
+            } => {
+                require!(
+                    outflow_limit == 0 || outflow_window > 0,
+                    SavingsError::InvalidWithdrawalLimitWindow
+                );
+            }
+            ConfigChange::PerformanceFee {
+                fee_bps,
+                fee_destination,
+            } => {
+                require!(fee_bps <= MAX_BPS, SavingsError::InvalidBasisPoints);
+                require!(
+                    fee_bps == 0 || fee_destination.is_some(),
+                    SavingsError::MissingFeeDestination
+                );
+            }
+            ConfigChange::AllowlistRequired { .. }
+            | ConfigChange::AllowlistVerifier { .. }
+            | ConfigChange::YieldSource { .. }
+            | ConfigChange::MintPolicy { .. } => {}
+        }
+        Ok(())
+    }
+
+    /// Writes the change to `state`, or to `distributor` for distributor changes. Interest rate
+    /// changes are recorded in the distributor's `rate_history` as taking effect at `now`, rate
+    /// feed changes are written to the rate history, and yield source changes are written to its
+    /// `yield_adapter`.
+    pub fn apply(
+        &self,
+        state: &mut State,
+        distributor: Option<&mut InterestDistributor>,
+        rate_history: Option<&mut RateHistory>,
+        yield_adapter: Option<&mut YieldAdapter>,
+        now: i64,
+    ) -> Result<()> {
+        self.validate()?;
+
+        if let ConfigChange::YieldSource {
+            program,
+            deposit_ix,
+            redeem_ix,
+        } = *self
+        {
+            require!(
+                distributor.is_some(),
+                SavingsError::ConfigChangeTargetMismatch
+            );
+            let yield_adapter = yield_adapter.ok_or(SavingsError::MissingYieldAdapter)?;
+            require!(
+                yield_adapter.allocated == 0,
+                SavingsError::YieldFundsAllocated
+            );
+            yield_adapter.program = program;
+            yield_adapter.deposit_ix = deposit_ix;
+            yield_adapter.redeem_ix = redeem_ix;
+            return Ok(());
+        }
+
+        if let ConfigChange::InterestRate { rate_bps } = *self {
+            require!(
+                distributor.is_some(),
+                SavingsError::ConfigChangeTargetMismatch
+            );
+            let rate_history = rate_history.ok_or(SavingsError::MissingRateHistory)?;
+            rate_history.push(rate_bps, now);
+            return Ok(());
+        }
+
+        if let ConfigChange::RateFeed {
+            feed,
+            max_staleness,
+            min_rate_bps,
+            max_rate_bps,
+        } = *self
+        {
+            require!(
+                distributor.is_some(),
+                SavingsError::ConfigChangeTargetMismatch
+            );
+            let rate_history = rate_history.ok_or(SavingsError::MissingRateHistory)?;
+            rate_history.rate_feed = feed;
+            rate_history.max_feed_staleness = max_staleness;
+            rate_history.min_rate_bps = min_rate_bps;
+            rate_history.max_rate_bps = max_rate_bps;
+            return Ok(());
+        }
+
+        match (self.clone(), distributor) {
+            (ConfigChange::AllowlistVerifier { verifier }, None) => {
+                state.allowlist_verifier = verifier;
+            }
+            (ConfigChange::TimelockDelay { delay }, None) => {
+                state.config_timelock_delay = delay;
+            }
+            (
+                ConfigChange::MintPolicy {
+                    reject_freeze_authority,
+                    min_decimals,
+                    require_approved_mint,
+                },
+                None,
+            ) => {
+                state.reject_freeze_authority = reject_freeze_authority;
+                state.min_mint_decimals = min_decimals;
+                state.require_approved_mint = require_approved_mint;
+            }
+            (ConfigChange::WithdrawalNoticePeriod { notice_period }, Some(distributor)) => {
+                distributor.withdrawal_notice_period = notice_period;
+            }
+            (ConfigChange::GoalCancellationPenalty { penalty_bps }, Some(distributor)) => {
+                distributor.goal_cancellation_penalty_bps = penalty_bps;
+            }
+            (
+                ConfigChange::ReferralProgram {
+                    bonus_bps,
+                    bonus_cap,
+                    window,
+                },
+                Some(distributor),
+            ) => {
+                distributor.referral_bonus_bps = bonus_bps;
+                distributor.referral_bonus_cap = bonus_cap;
+                distributor.referral_window = window;
+            }
+            (ConfigChange::AllowlistRequired { required }, Some(distributor)) => {
+                distributor.allowlist_required = required;
+            }
+            (
+                ConfigChange::PerformanceFee {
+                    fee_bps,
+                    fee_destination,
+                },
+                Some(distributor),
+            ) => {
+                distributor.performance_fee_bps = fee_bps;
+                distributor.fee_destination = fee_destination;
+            }
+            (
+                ConfigChange::WithdrawalFee {
+                    fee_bps,
+                    waiver_period,
+                },
+                Some(distributor),
+            ) => {
+                distributor.withdrawal_fee_bps = fee_bps;
+                distributor.withdrawal_fee_waiver_period = waiver_period;
+            }
+            (
+                ConfigChange::WithdrawalLimits {
+                    user_limit,
+                    outflow_limit,
+                    outflow_window,
+                },
+                Some(distributor),
+            ) => {
+                distributor.user_withdrawal_limit = user_limit;
+                distributor.outflow_limit = outflow_limit;
+                distributor.outflow_window = outflow_window;
+            }
+            _ => return Err(SavingsError::ConfigChangeTargetMismatch.into()),
+        }
+
+        Ok(())
+    }
+}
+
+#[event]
+/// Emitted when a bonus campaign allocation is paid into a savings vault.
+pub struct CampaignClaimed {
+    pub campaign: Pubkey,
+    pub savings_manager: Pubkey,
+    pub index: u32,
+    pub amount: u64,
+}
+
+#[event]
+/// Emitted when an expired bonus campaign is closed and its unclaimed remainder swept back to the
+/// interest vault.
+pub struct CampaignClosed {
+    pub campaign: Pubkey,
+    pub total_claimed: u64,
+    pub swept: u64,
+}
+
+#[event]
+/// Emitted when a publisher updates a hosted rate feed.
+pub struct RatePublished {
+    pub rate_feed: Pubkey,
+    pub rate_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+/// Emitted when interest vault funds are allocated to or recalled from a yield source.
+pub struct YieldFundsMoved {
+    pub interest_distributor: Pubkey,
+    pub yield_program: Pubkey,
+    pub allocated: u64,
+    pub recalled: u64,
+    /// The principal still allocated to the yield source afterwards.
+    pub total_allocated: u64,
+}
+
+#[event]
+/// Emitted when a configuration change is queued.
+pub struct ConfigChangeQueued {
+    pub pending_change: Pubkey,
+    pub state: Pubkey,
+    pub interest_distributor: Option<Pubkey>,
+    pub change: ConfigChange,
+    /// The unix timestamp from which the change can be executed.
+    pub eta: i64,
+}
+
+#[event]
+/// Emitted when a queued configuration change is applied.
+pub struct ConfigChangeExecuted {
+    pub pending_change: Pubkey,
+    pub state: Pubkey,
+    pub interest_distributor: Option<Pubkey>,
+    pub change: ConfigChange,
+}
+
+#[event]
+/// Emitted when a queued configuration change is discarded.
+pub struct ConfigChangeCancelled {
+    pub pending_change: Pubkey,
+    pub state: Pubkey,
+    pub interest_distributor: Option<Pubkey>,
+    pub change: ConfigChange,
+}
+
+#[event]
+/// Emitted when a savings vault first reaches its savings goal.
+pub struct GoalReached {
+    pub savings_manager: Pubkey,
+    pub user: Pubkey,
+    /// The vault's balance when the goal was reached.
+    pub balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+/// Emitted when a referrer is paid a bonus on a referred user's interest.
+pub struct ReferralBonusPaid {
+    /// The savings-manager of the referred user.
+    pub savings_manager: Pubkey,
+    pub referrer: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+/// Emitted when a referral bonus is due but the referrer's savings vault is closed, was never
+/// opened, or is frozen. The bonus is not paid.
+pub struct ReferralBonusSkipped {
+    /// The savings-manager of the referred user.
+    pub savings_manager: Pubkey,
+    pub referrer: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+/// Emitted when a batched interest deposit passes over a frozen savings vault.
+pub struct FrozenSavingsManagerSkipped {
+    pub savings_manager: Pubkey,
+    pub user: Pubkey,
+}
+
+#[event]
+/// Emitted when the state's authority freezes a savings vault.
+pub struct SavingsManagerFrozen {
+    pub savings_manager: Pubkey,
+    pub user: Pubkey,
+    pub authority: Pubkey,
+    pub reason_code: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+/// Emitted when the state's authority unfreezes a savings vault.
+pub struct SavingsManagerUnfrozen {
+    pub savings_manager: Pubkey,
+    pub user: Pubkey,
+    pub authority: Pubkey,
+    pub reason_code: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+/// Emitted when someone deposits into another user's savings vault.
+pub struct ThirdPartyDeposit {
+    pub savings_manager: Pubkey,
+    /// The owner of the savings vault.
+    pub user: Pubkey,
+    pub depositor: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+/// Emitted when a user moves their position to a savings vault owned by another wallet.
+pub struct PositionTransferred {
+    pub old_savings_manager: Pubkey,
+    pub new_savings_manager: Pubkey,
+    pub old_user: Pubkey,
+    pub new_user: Pubkey,
+    /// The number of tokens moved to the new savings vault.
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+/// Emitted when tokens are withdrawn from a savings vault.
+pub struct Withdrawal {
+    pub savings_manager: Pubkey,
+    /// The owner or delegate who withdrew.
+    pub authority: Pubkey,
+    pub destination: Pubkey,
+    /// The amount withdrawn from the vault, including the fee.
+    pub amount: u64,
+    /// The withdrawal fee paid into the interest vault.
+    pub fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+/// Emitted when a performance fee is taken from an interest payment.
+pub struct PerformanceFeePaid {
+    pub interest_distributor: Pubkey,
+    pub savings_manager: Pubkey,
+    /// The interest payment before the fee.
+    pub gross_interest: u64,
+    pub fee: u64,
+    /// The total performance fees paid out by the distributor, including this one.
+    pub total_fees: u64,
+}
+
+#[error_code]
+pub enum SavingsError {
+    #[msg("not enough funds in vault token account")]
+    InadequateFunds,
+    #[msg("not enough time has elapsed since the last interest deposit")]
+    CrankTurnedTooSoon,
+    #[msg("did not specify any recipient for the interest transfer")]
+    ZeroRecipientsForInterestDeposit,
+    #[msg("account is already at the current layout version")]
+    AccountAlreadyMigrated,
+    #[msg("signer is neither the owner nor the delegate of the savings vault")]
+    UnauthorizedWithdrawal,
+    #[msg("withdrawal exceeds the delegate's remaining allowance for this period")]
+    DelegateAllowanceExceeded,
+    #[msg("delegate allowance period must be greater than zero")]
+    InvalidDelegatePeriod,
+    #[msg("withdrawal notice period cannot be negative")]
+    InvalidNoticePeriod,
+    #[msg("withdrawals from this vault must be requested before they are executed")]
+    WithdrawalNoticeRequired,
+    #[msg("withdrawal amount must be greater than zero")]
+    ZeroWithdrawalAmount,
+    #[msg("there is no pending withdrawal request")]
+    NoPendingWithdrawal,
+    #[msg("the withdrawal notice period has not elapsed")]
+    NoticePeriodNotElapsed,
+    #[msg("savings plan contribution must be greater than zero")]
+    ZeroSavingsPlanAmount,
+    #[msg("savings plan interval must be greater than zero")]
+    InvalidSavingsPlanInterval,
+    #[msg("the savings-manager is not approved to spend the plan's contribution from the source account")]
+    SavingsPlanNotApproved,
+    #[msg("basis points cannot exceed 10000")]
+    InvalidBasisPoints,
+    #[msg("a savings goal needs a target amount, a target date, or both")]
+    InvalidSavingsGoal,
+    #[msg("the current savings goal must be reached or cancelled before setting a new one")]
+    SavingsGoalAlreadyActive,
+    #[msg("the vault is locked until its savings goal is reached")]
+    SavingsGoalNotReached,
+    #[msg("referral window cannot be negative")]
+    InvalidReferralWindow,
+    #[msg("users cannot refer themselves")]
+    SelfReferral,
+    #[msg(
+        "a referral bonus is due but the referrer's savings-manager and vault were not provided"
+    )]
+    MissingReferrerSavingsVault,
+    #[msg("native SOL deposits and withdrawals are only supported for the native mint")]
+    NotNativeMint,
+    #[msg("signer is neither the state's authority nor its allowlist verifier")]
+    UnauthorizedVerifier,
+    #[msg("user is not on the allowlist for this distributor")]
+    NotAllowlisted,
+    #[msg("user's allowlist entry has expired")]
+    AllowlistEntryExpired,
+    #[msg("savings vault is frozen")]
+    SavingsManagerFrozen,
+    #[msg("savings vault is not frozen")]
+    SavingsManagerNotFrozen,
+    #[msg("timelock delay must not be negative")]
+    InvalidTimelockDelay,
+    #[msg("the config change's timelock has not elapsed yet")]
+    TimelockNotElapsed,
+    #[msg("config change does not apply to the given account")]
+    ConfigChangeTargetMismatch,
+    #[msg("interest rate changes require the distributor's rate history")]
+    MissingRateHistory,
+    #[msg("performance fee destination is missing or doesn't match the distributor's")]
+    MissingFeeDestination,
+    #[msg("withdrawal fee waiver period must not be negative")]
+    InvalidWaiverPeriod,
+    #[msg("the owner of this savings vault has opted out of third-party deposits")]
+    ThirdPartyDepositsDisabled,
+    #[msg("the pending withdrawal must be executed before the position can be transferred")]
+    PendingWithdrawalExists,
+    #[msg("receipt mint is missing or doesn't match the distributor's")]
+    MissingReceiptMint,
+    #[msg("yield source changes require the distributor's yield adapter")]
+    MissingYieldAdapter,
+    #[msg("the yield source can't be changed while funds are allocated to it")]
+    YieldFundsAllocated,
+    #[msg("yield program is not the one configured for this distributor")]
+    YieldSourceNotConfigured,
+    #[msg("rate feed is missing or doesn't match the distributor's")]
+    MissingRateFeed,
+    #[msg("the rate feed hasn't been updated recently enough")]
+    StaleRateFeed,
+    #[msg("rate feed bounds are inverted or its staleness limit is not positive")]
+    InvalidRateFeedConfig,
+    #[msg("mints with a freeze authority are not allowed")]
+    MintHasFreezeAuthority,
+    #[msg("mint has fewer decimals than the state allows")]
+    MintDecimalsTooLow,
+    #[msg("mint is not on the state's approved list")]
+    MintNotApproved,
+    #[msg("withdrawal exceeds the user's rolling withdrawal limit")]
+    UserWithdrawalLimitExceeded,
+    #[msg("withdrawal exceeds the distributor's rolling outflow limit")]
+    DistributorOutflowLimitExceeded,
+    #[msg("outflow window must be positive when an outflow limit is set")]
+    InvalidWithdrawalLimitWindow,
+    #[msg("campaign must allow between one and MAX_CAMPAIGN_CLAIMS claims")]
+    InvalidCampaignSize,
+    #[msg("merkle proof doesn't match the campaign's root")]
+    InvalidCampaignProof,
+    #[msg("this campaign allocation has already been claimed")]
+    CampaignAlreadyClaimed,
+    #[msg("campaign belongs to a different distributor than the savings vault")]
+    CampaignDistributorMismatch,
+    #[msg("campaign must expire in the future")]
+    InvalidCampaignExpiry,
+    #[msg("campaign has expired")]
+    CampaignExpired,
+    #[msg("campaign hasn't expired yet")]
+    CampaignNotExpired,
+    #[msg("registry page is full")]
+    RegistryPageFull,
+    #[msg("savings vault is listed on a different registry page")]
+    RegistryPageMismatch,
+    #[msg("savings vault is already listed in its distributor's registry")]
+    SavingsManagerAlreadyRegistered,
+    #[msg("accrued interest doesn't fit in a token amount")]
+    InterestOverflow,
 }
@@ -1,10 +1,17 @@
 use super::Result;
-use super::{instructions::*, pda};
-use solana_program_test::ProgramTestContext;
+use super::{instructions::*, pda, utils};
+use savings_program::{
+    ConfigChange, InterestDistributor, InterestPreview, RateHistory, RegistryPage, SavingsManager,
+    State,
+};
+use solana_program_test::{ProgramTestBanksClientExt, ProgramTestContext};
 use solana_sdk::account::Account;
-use solana_sdk::instruction::Instruction;
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::{AccountMeta, Instruction};
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
 use std::cell::RefCell;
 
 pub struct TestContext {
@@ -38,21 +45,213 @@ impl TestContext {
     }
 
     pub async fn create_interest_vault(&self, mint: &Pubkey) -> Result<()> {
-        let distributor = pda::derive_interest_distributor_pda(&self.state, mint).0;
+        self.create_product_interest_vault(mint, 0).await?;
+        Ok(())
+    }
+
+    pub async fn create_product_interest_vault(
+        &self,
+        mint: &Pubkey,
+        product_id: u16,
+    ) -> Result<Pubkey> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, product_id).0;
         let vault = pda::derive_interest_vault_ata(mint, &distributor);
+        let rate_history = pda::derive_rate_history_pda(&distributor).0;
+        let registry_page = pda::derive_registry_page_pda(&distributor, 0).0;
+        let approved_mint = self
+            .existing_account(&pda::derive_approved_mint_pda(&self.state, mint).0)
+            .await?;
 
         let (_, instruction) = create_interest_vault(
             &self.ctx.borrow().payer.pubkey(),
             &self.admin.pubkey(),
             &self.state,
             mint,
+            approved_mint.as_ref(),
             &distributor,
             &vault,
+            &rate_history,
+            &registry_page,
+            product_id,
+        );
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![&self.admin]))
+            .await?;
+
+        Ok(distributor)
+    }
+
+    pub async fn create_receipt_mint(&self, mint: &Pubkey) -> Result<Pubkey> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let receipt_mint = pda::derive_receipt_mint_pda(&distributor).0;
+        let receipt_pool = pda::derive_receipt_pool_pda(&distributor).0;
+
+        let (_, instruction) = create_receipt_mint(
+            &self.ctx.borrow().payer.pubkey(),
+            &self.admin.pubkey(),
+            &self.state,
+            mint,
+            &distributor,
+            &receipt_mint,
+            &receipt_pool,
+        );
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![&self.admin]))
+            .await?;
+
+        Ok(receipt_mint)
+    }
+
+    pub async fn create_yield_adapter(&self, mint: &Pubkey) -> Result<Pubkey> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let yield_adapter = pda::derive_yield_adapter_pda(&distributor).0;
+
+        let (_, instruction) = create_yield_adapter(
+            &self.ctx.borrow().payer.pubkey(),
+            &self.admin.pubkey(),
+            &self.state,
+            &distributor,
+            &yield_adapter,
+            mint,
+            &pda::derive_yield_vault_ata(mint, &yield_adapter),
+        );
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![&self.admin]))
+            .await?;
+
+        Ok(yield_adapter)
+    }
+
+    /// Allocates `amount` tokens to, or recalls them from, the distributor's yield source.
+    /// `remaining_accounts` are passed on to the yield program.
+    pub async fn manage_yield_funds(
+        &self,
+        mint: &Pubkey,
+        yield_program: &Pubkey,
+        remaining_accounts: Vec<AccountMeta>,
+        allocate: bool,
+        amount: u64,
+    ) -> Result<()> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let yield_adapter = pda::derive_yield_adapter_pda(&distributor).0;
+        let interest_vault = pda::derive_interest_vault_ata(mint, &distributor);
+
+        let (_, instruction) = manage_yield_funds(
+            &self.admin.pubkey(),
+            &self.state,
+            &distributor,
+            &yield_adapter,
+            &interest_vault,
+            &pda::derive_yield_vault_ata(mint, &yield_adapter),
+            yield_program,
+            remaining_accounts,
+            allocate,
+            amount,
+        );
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![&self.admin]))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn create_rate_feed(&self, publisher: &Pubkey) -> Result<Pubkey> {
+        let rate_feed = Keypair::new();
+
+        let (_, instruction) = create_rate_feed(
+            &self.ctx.borrow().payer.pubkey(),
+            &self.admin.pubkey(),
+            &self.state,
+            &rate_feed.pubkey(),
+            publisher,
+        );
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![&self.admin, &rate_feed]))
+            .await?;
+
+        Ok(rate_feed.pubkey())
+    }
+
+    pub async fn publish_rate(
+        &self,
+        publisher: &Keypair,
+        rate_feed: &Pubkey,
+        rate_bps: u16,
+    ) -> Result<()> {
+        let (_, instruction) = publish_rate(&publisher.pubkey(), rate_feed, rate_bps);
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![publisher]))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn approve_mint(&self, mint: &Pubkey) -> Result<()> {
+        let approved_mint = pda::derive_approved_mint_pda(&self.state, mint).0;
+        let (_, instruction) =
+            approve_mint(&self.admin.pubkey(), &self.state, mint, &approved_mint);
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![&self.admin]))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn revoke_mint_approval(&self, mint: &Pubkey) -> Result<()> {
+        let approved_mint = pda::derive_approved_mint_pda(&self.state, mint).0;
+        let (_, instruction) =
+            revoke_mint_approval(&self.admin.pubkey(), &self.state, &approved_mint);
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![&self.admin]))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn create_campaign(
+        &self,
+        mint: &Pubkey,
+        campaign_id: u64,
+        merkle_root: [u8; 32],
+        max_claims: u32,
+        expires_ts: i64,
+    ) -> Result<Pubkey> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let campaign = pda::derive_campaign_pda(&distributor, campaign_id).0;
+        let campaign_vault = pda::derive_campaign_vault_ata(mint, &campaign);
+
+        let (_, instruction) = create_campaign(
+            &self.ctx.borrow().payer.pubkey(),
+            &self.admin.pubkey(),
+            &self.state,
+            mint,
+            &distributor,
+            &campaign,
+            &campaign_vault,
+            campaign_id,
+            merkle_root,
+            max_claims,
+            expires_ts,
         );
 
         self.send_and_confirm_tx(vec![instruction], Some(vec![&self.admin]))
             .await?;
 
+        Ok(campaign)
+    }
+
+    pub async fn close_campaign(&self, mint: &Pubkey, campaign: &Pubkey) -> Result<()> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let campaign_vault = pda::derive_campaign_vault_ata(mint, campaign);
+        let interest_vault = pda::derive_interest_vault_ata(mint, &distributor);
+
+        let (_, instruction) = close_campaign(
+            &self.admin.pubkey(),
+            &self.state,
+            &distributor,
+            campaign,
+            &campaign_vault,
+            &interest_vault,
+        );
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![&self.admin]))
+            .await?;
         Ok(())
     }
 
@@ -63,7 +262,7 @@ impl TestContext {
         token_account_address: &Pubkey,
         amount: u64,
     ) -> Result<()> {
-        let distributor = pda::derive_interest_distributor_pda(&self.state, mint).0;
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
         let vault = pda::derive_interest_vault_ata(mint, &distributor);
 
         let (_, instruction) = deposit_to_interest_vault(
@@ -87,7 +286,7 @@ impl TestContext {
         destination_account: &Pubkey,
         amount: u64,
     ) -> Result<()> {
-        let distributor = pda::derive_interest_distributor_pda(&self.state, mint).0;
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
         let vault = pda::derive_interest_vault_ata(mint, &distributor);
 
         let (_, instruction) = withdraw_from_interest_vault(
@@ -104,10 +303,289 @@ impl TestContext {
         Ok(())
     }
 
-    pub async fn user_create_vault(&self, user: &Keypair, mint: &Pubkey) -> Result<()> {
-        let distributor = pda::derive_interest_distributor_pda(&self.state, mint).0;
-        let manager = pda::derive_savings_manager_pda(&user.pubkey(), &distributor).0;
+    /// Queues a configuration change for the distributor of `mint`, or for the state itself if
+    /// `mint` is `None`, returning the address of the pending change.
+    pub async fn queue_config_change(
+        &self,
+        mint: Option<&Pubkey>,
+        change: ConfigChange,
+    ) -> Result<Pubkey> {
+        let distributor =
+            mint.map(|mint| pda::derive_interest_distributor_pda(&self.state, mint, 0).0);
+        let state = self.get_deserialized_account::<State>(&self.state).await?;
+        let pending_change =
+            pda::derive_config_change_pda(&self.state, state.next_config_change_id).0;
+
+        let (_, instruction) = queue_config_change(
+            &self.admin.pubkey(),
+            &self.state,
+            distributor.as_ref(),
+            &pending_change,
+            change,
+        );
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![&self.admin]))
+            .await?;
+        Ok(pending_change)
+    }
+
+    pub async fn execute_config_change(
+        &self,
+        mint: Option<&Pubkey>,
+        pending_change: &Pubkey,
+    ) -> Result<()> {
+        let distributor =
+            mint.map(|mint| pda::derive_interest_distributor_pda(&self.state, mint, 0).0);
+        let rate_history = distributor
+            .as_ref()
+            .map(|distributor| pda::derive_rate_history_pda(distributor).0);
+        let yield_adapter = match distributor {
+            Some(distributor) => {
+                self.existing_account(&pda::derive_yield_adapter_pda(&distributor).0)
+                    .await?
+            }
+            None => None,
+        };
+
+        let (_, instruction) = execute_config_change(
+            &self.admin.pubkey(),
+            &self.state,
+            distributor.as_ref(),
+            rate_history.as_ref(),
+            yield_adapter.as_ref(),
+            pending_change,
+        );
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![&self.admin]))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn cancel_config_change(&self, pending_change: &Pubkey) -> Result<()> {
+        let (_, instruction) =
+            cancel_config_change(&self.admin.pubkey(), &self.state, pending_change);
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![&self.admin]))
+            .await?;
+        Ok(())
+    }
+
+    /// Lowers the state's timelock delay to zero, waiting out the current delay, so that later
+    /// configuration changes can be applied immediately.
+    pub async fn clear_timelock_delay(&self) -> Result<()> {
+        let delay = self
+            .get_deserialized_account::<State>(&self.state)
+            .await?
+            .config_timelock_delay;
+        let pending_change = self
+            .queue_config_change(None, ConfigChange::TimelockDelay { delay: 0 })
+            .await?;
+        self.advance_clock(delay).await?;
+        self.execute_config_change(None, &pending_change).await
+    }
+
+    /// Queues and immediately executes a configuration change. Only succeeds while the state's
+    /// timelock delay is zero.
+    pub async fn apply_config_change(
+        &self,
+        mint: Option<&Pubkey>,
+        change: ConfigChange,
+    ) -> Result<()> {
+        let pending_change = self.queue_config_change(mint, change).await?;
+        self.execute_config_change(mint, &pending_change).await
+    }
+
+    pub async fn set_withdrawal_notice_period(
+        &self,
+        mint: &Pubkey,
+        notice_period: i64,
+    ) -> Result<()> {
+        self.apply_config_change(
+            Some(mint),
+            ConfigChange::WithdrawalNoticePeriod { notice_period },
+        )
+        .await
+    }
+
+    pub async fn set_goal_cancellation_penalty(
+        &self,
+        mint: &Pubkey,
+        penalty_bps: u16,
+    ) -> Result<()> {
+        self.apply_config_change(
+            Some(mint),
+            ConfigChange::GoalCancellationPenalty { penalty_bps },
+        )
+        .await
+    }
+
+    pub async fn set_referral_program(
+        &self,
+        mint: &Pubkey,
+        bonus_bps: u16,
+        bonus_cap: u64,
+        window: i64,
+    ) -> Result<()> {
+        self.apply_config_change(
+            Some(mint),
+            ConfigChange::ReferralProgram {
+                bonus_bps,
+                bonus_cap,
+                window,
+            },
+        )
+        .await
+    }
+
+    pub async fn set_yield_source(
+        &self,
+        mint: &Pubkey,
+        program: Option<&Pubkey>,
+        deposit_ix: [u8; 8],
+        redeem_ix: [u8; 8],
+    ) -> Result<()> {
+        self.apply_config_change(
+            Some(mint),
+            ConfigChange::YieldSource {
+                program: program.copied(),
+                deposit_ix,
+                redeem_ix,
+            },
+        )
+        .await
+    }
+
+    pub async fn set_rate_feed(
+        &self,
+        mint: &Pubkey,
+        feed: Option<&Pubkey>,
+        max_staleness: i64,
+        min_rate_bps: u16,
+        max_rate_bps: u16,
+    ) -> Result<()> {
+        self.apply_config_change(
+            Some(mint),
+            ConfigChange::RateFeed {
+                feed: feed.copied(),
+                max_staleness,
+                min_rate_bps,
+                max_rate_bps,
+            },
+        )
+        .await
+    }
+
+    pub async fn set_mint_policy(
+        &self,
+        reject_freeze_authority: bool,
+        min_decimals: u8,
+        require_approved_mint: bool,
+    ) -> Result<()> {
+        self.apply_config_change(
+            None,
+            ConfigChange::MintPolicy {
+                reject_freeze_authority,
+                min_decimals,
+                require_approved_mint,
+            },
+        )
+        .await
+    }
+
+    pub async fn set_interest_rate(&self, mint: &Pubkey, rate_bps: u16) -> Result<()> {
+        self.apply_config_change(Some(mint), ConfigChange::InterestRate { rate_bps })
+            .await
+    }
+
+    pub async fn set_performance_fee(
+        &self,
+        mint: &Pubkey,
+        fee_bps: u16,
+        fee_destination: Option<&Pubkey>,
+    ) -> Result<()> {
+        self.apply_config_change(
+            Some(mint),
+            ConfigChange::PerformanceFee {
+                fee_bps,
+                fee_destination: fee_destination.copied(),
+            },
+        )
+        .await
+    }
+
+    pub async fn set_withdrawal_fee(
+        &self,
+        mint: &Pubkey,
+        fee_bps: u16,
+        waiver_period: i64,
+    ) -> Result<()> {
+        self.apply_config_change(
+            Some(mint),
+            ConfigChange::WithdrawalFee {
+                fee_bps,
+                waiver_period,
+            },
+        )
+        .await
+    }
+
+    pub async fn set_withdrawal_limits(
+        &self,
+        mint: &Pubkey,
+        user_limit: u64,
+        outflow_limit: u64,
+        outflow_window: i64,
+    ) -> Result<()> {
+        self.apply_config_change(
+            Some(mint),
+            ConfigChange::WithdrawalLimits {
+                user_limit,
+                outflow_limit,
+                outflow_window,
+            },
+        )
+        .await
+    }
+
+    pub async fn set_allowlist_required(&self, mint: &Pubkey, required: bool) -> Result<()> {
+        self.apply_config_change(Some(mint), ConfigChange::AllowlistRequired { required })
+            .await
+    }
+
+    pub async fn set_allowlist_verifier(&self, verifier: Option<&Pubkey>) -> Result<()> {
+        self.apply_config_change(
+            None,
+            ConfigChange::AllowlistVerifier {
+                verifier: verifier.copied(),
+            },
+        )
+        .await
+    }
+
+    pub async fn user_create_vault(
+        &self,
+        user: &Keypair,
+        mint: &Pubkey,
+        referrer: Option<&Pubkey>,
+    ) -> Result<()> {
+        self.user_create_sub_account(user, mint, 0, [0; 32], referrer)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn user_create_sub_account(
+        &self,
+        user: &Keypair,
+        mint: &Pubkey,
+        index: u16,
+        label: [u8; 32],
+        referrer: Option<&Pubkey>,
+    ) -> Result<Pubkey> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let manager = pda::derive_savings_manager_pda(&user.pubkey(), &distributor, index).0;
         let vault = pda::derive_savings_vault_ata(mint, &manager);
+        let registry_page = self.last_registry_page(&distributor).await?;
+        let allowlist_entry = self.allowlist_entry(&user.pubkey()).await?;
 
         let (_, instruction) = user_create_vault(
             &self.ctx.borrow().payer.pubkey(),
@@ -116,69 +594,840 @@ impl TestContext {
             &distributor,
             &manager,
             &vault,
+            &registry_page,
+            allowlist_entry.as_ref(),
+            referrer,
+            index,
+            label,
         );
 
         self.send_and_confirm_tx(vec![instruction], Some(vec![user]))
             .await?;
-        Ok(())
+        Ok(manager)
     }
 
-    pub async fn user_deposit(
+    pub async fn user_transfer_position(
         &self,
         user: &Keypair,
+        new_user: &Keypair,
         mint: &Pubkey,
-        token_account: &Pubkey,
-        amount: u64,
     ) -> Result<()> {
-        let distributor = pda::derive_interest_distributor_pda(&self.state, mint).0;
-        let manager = pda::derive_savings_manager_pda(&user.pubkey(), &distributor).0;
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let manager = pda::derive_savings_manager_pda(&user.pubkey(), &distributor, 0).0;
         let vault = pda::derive_savings_vault_ata(mint, &manager);
+        let new_manager = pda::derive_savings_manager_pda(&new_user.pubkey(), &distributor, 0).0;
+        let new_vault = pda::derive_savings_vault_ata(mint, &new_manager);
+        let registry_page = self
+            .get_deserialized_account::<SavingsManager>(&manager)
+            .await?
+            .registry_page;
+        let registry_page = pda::derive_registry_page_pda(&distributor, registry_page).0;
+        let allowlist_entry = self.allowlist_entry(&new_user.pubkey()).await?;
 
-        let (_, instruction) =
-            user_deposit(&user.pubkey(), token_account, &manager, &vault, amount);
+        let (_, instruction) = user_transfer_position(
+            &self.ctx.borrow().payer.pubkey(),
+            &user.pubkey(),
+            &new_user.pubkey(),
+            mint,
+            &distributor,
+            &manager,
+            &vault,
+            &new_manager,
+            &new_vault,
+            &registry_page,
+            allowlist_entry.as_ref(),
+        );
 
-        self.send_and_confirm_tx(vec![instruction], Some(vec![user]))
+        self.send_and_confirm_tx(vec![instruction], Some(vec![user, new_user]))
             .await?;
         Ok(())
     }
 
-    pub async fn user_withdraw(
+    pub async fn user_deposit(
         &self,
         user: &Keypair,
         mint: &Pubkey,
         token_account: &Pubkey,
         amount: u64,
     ) -> Result<()> {
-        let distributor = pda::derive_interest_distributor_pda(&self.state, mint).0;
-        let manager = pda::derive_savings_manager_pda(&user.pubkey(), &distributor).0;
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let manager = pda::derive_savings_manager_pda(&user.pubkey(), &distributor, 0).0;
         let vault = pda::derive_savings_vault_ata(mint, &manager);
 
-        let (_, instruction) =
-            user_withdraw(&user.pubkey(), &manager, &vault, token_account, amount);
+        let allowlist_entry = self.allowlist_entry(&user.pubkey()).await?;
+        let receipt_mint = self
+            .get_deserialized_account::<InterestDistributor>(&distributor)
+            .await?
+            .receipt_mint;
+        let receipt_token_account = receipt_mint.map(|receipt_mint| {
+            anchor_spl::associated_token::get_associated_token_address(
+                &user.pubkey(),
+                &receipt_mint,
+            )
+        });
+        let receipt_pool = receipt_mint.map(|_| pda::derive_receipt_pool_pda(&distributor).0);
+
+        let (_, instruction) = user_deposit(
+            &user.pubkey(),
+            token_account,
+            &manager,
+            &distributor,
+            allowlist_entry.as_ref(),
+            &vault,
+            receipt_mint.as_ref(),
+            receipt_token_account.as_ref(),
+            receipt_pool.as_ref(),
+            amount,
+        );
 
         self.send_and_confirm_tx(vec![instruction], Some(vec![user]))
             .await?;
         Ok(())
     }
 
-    pub async fn deposit_interest(&self, user: &Pubkey, mint: &Pubkey) -> Result<()> {
-        let distributor = pda::derive_interest_distributor_pda(&self.state, mint).0;
-        let interest_vault = pda::derive_interest_vault_ata(mint, &distributor);
-        let manager = pda::derive_savings_manager_pda(user, &distributor).0;
-        let savings_vault = pda::derive_savings_vault_ata(mint, &manager);
+    pub async fn deposit_for(
+        &self,
+        depositor: &Keypair,
+        token_account: &Pubkey,
+        user: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let manager = pda::derive_savings_manager_pda(user, &distributor, 0).0;
+        let vault = pda::derive_savings_vault_ata(mint, &manager);
+        let allowlist_entry = self.allowlist_entry(user).await?;
 
-        let (_, instruction) = deposit_interest(
-            user,
+        let (_, instruction) = deposit_for(
+            &depositor.pubkey(),
+            token_account,
             &manager,
-            &savings_vault,
             &distributor,
-            &interest_vault,
+            allowlist_entry.as_ref(),
+            &vault,
+            amount,
         );
 
-        self.send_and_confirm_tx(vec![instruction], None).await?;
+        self.send_and_confirm_tx(vec![instruction], Some(vec![depositor]))
+            .await?;
         Ok(())
     }
 
+    pub async fn user_set_third_party_deposits(
+        &self,
+        user: &Keypair,
+        mint: &Pubkey,
+        allowed: bool,
+    ) -> Result<()> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let manager = pda::derive_savings_manager_pda(&user.pubkey(), &distributor, 0).0;
+
+        let (_, instruction) = user_set_third_party_deposits(&user.pubkey(), &manager, allowed);
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![user]))
+            .await?;
+        Ok(())
+    }
+
+    /// Redeems `amount` of the holder's receipts from the distributor's receipt pool.
+    pub async fn redeem_receipts(
+        &self,
+        holder: &Keypair,
+        mint: &Pubkey,
+        destination: &Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let receipt_pool = pda::derive_receipt_pool_pda(&distributor).0;
+        let receipt_mint = pda::derive_receipt_mint_pda(&distributor).0;
+        let holder_receipt_account = anchor_spl::associated_token::get_associated_token_address(
+            &holder.pubkey(),
+            &receipt_mint,
+        );
+
+        let (_, instruction) = redeem_receipts(
+            &holder.pubkey(),
+            &distributor,
+            &receipt_mint,
+            &holder_receipt_account,
+            &receipt_pool,
+            destination,
+            amount,
+        );
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![holder]))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn user_withdraw(
+        &self,
+        user: &Keypair,
+        mint: &Pubkey,
+        token_account: &Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let manager = pda::derive_savings_manager_pda(&user.pubkey(), &distributor, 0).0;
+        let vault = pda::derive_savings_vault_ata(mint, &manager);
+        let interest_vault = pda::derive_interest_vault_ata(mint, &distributor);
+
+        let (_, instruction) = user_withdraw(
+            &user.pubkey(),
+            &manager,
+            &distributor,
+            &vault,
+            &interest_vault,
+            token_account,
+            amount,
+        );
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![user]))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn user_deposit_native(&self, user: &Keypair, amount: u64) -> Result<()> {
+        let mint = anchor_spl::token::spl_token::native_mint::ID;
+        let distributor = pda::derive_interest_distributor_pda(&self.state, &mint, 0).0;
+        let manager = pda::derive_savings_manager_pda(&user.pubkey(), &distributor, 0).0;
+        let vault = pda::derive_savings_vault_ata(&mint, &manager);
+
+        let allowlist_entry = self.allowlist_entry(&user.pubkey()).await?;
+
+        let (_, instruction) = user_deposit_native(
+            &user.pubkey(),
+            &manager,
+            &distributor,
+            allowlist_entry.as_ref(),
+            &vault,
+            amount,
+        );
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![user]))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn user_withdraw_native(&self, user: &Keypair, amount: u64) -> Result<()> {
+        let mint = anchor_spl::token::spl_token::native_mint::ID;
+        let distributor = pda::derive_interest_distributor_pda(&self.state, &mint, 0).0;
+        let manager = pda::derive_savings_manager_pda(&user.pubkey(), &distributor, 0).0;
+        let vault = pda::derive_savings_vault_ata(&mint, &manager);
+        let interest_vault = pda::derive_interest_vault_ata(&mint, &distributor);
+        let unwrap_account = pda::derive_native_withdrawal_pda(&manager).0;
+
+        let (_, instruction) = user_withdraw_native(
+            &user.pubkey(),
+            &manager,
+            &distributor,
+            &vault,
+            &interest_vault,
+            &unwrap_account,
+            amount,
+        );
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![user]))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn request_withdrawal(
+        &self,
+        user: &Keypair,
+        mint: &Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let manager = pda::derive_savings_manager_pda(&user.pubkey(), &distributor, 0).0;
+        let vault = pda::derive_savings_vault_ata(mint, &manager);
+
+        let (_, instruction) = request_withdrawal(&user.pubkey(), &manager, &vault, amount);
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![user]))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn execute_withdrawal(
+        &self,
+        user: &Keypair,
+        mint: &Pubkey,
+        token_account: &Pubkey,
+    ) -> Result<()> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let manager = pda::derive_savings_manager_pda(&user.pubkey(), &distributor, 0).0;
+        let vault = pda::derive_savings_vault_ata(mint, &manager);
+        let interest_vault = pda::derive_interest_vault_ata(mint, &distributor);
+
+        let (_, instruction) = execute_withdrawal(
+            &user.pubkey(),
+            &manager,
+            &distributor,
+            &vault,
+            &interest_vault,
+            token_account,
+        );
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![user]))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn user_set_goal(
+        &self,
+        user: &Keypair,
+        mint: &Pubkey,
+        target_amount: u64,
+        target_ts: i64,
+    ) -> Result<()> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let manager = pda::derive_savings_manager_pda(&user.pubkey(), &distributor, 0).0;
+
+        let (_, instruction) = user_set_goal(&user.pubkey(), &manager, target_amount, target_ts);
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![user]))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn user_cancel_goal(&self, user: &Keypair, mint: &Pubkey) -> Result<()> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let interest_vault = pda::derive_interest_vault_ata(mint, &distributor);
+        let manager = pda::derive_savings_manager_pda(&user.pubkey(), &distributor, 0).0;
+        let savings_vault = pda::derive_savings_vault_ata(mint, &manager);
+
+        let (_, instruction) = user_cancel_goal(
+            &user.pubkey(),
+            &manager,
+            &distributor,
+            &savings_vault,
+            &interest_vault,
+        );
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![user]))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delegate_withdraw(
+        &self,
+        delegate: &Keypair,
+        user: &Pubkey,
+        mint: &Pubkey,
+        token_account: &Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let manager = pda::derive_savings_manager_pda(user, &distributor, 0).0;
+        let vault = pda::derive_savings_vault_ata(mint, &manager);
+        let interest_vault = pda::derive_interest_vault_ata(mint, &distributor);
+
+        let (_, instruction) = user_withdraw(
+            &delegate.pubkey(),
+            &manager,
+            &distributor,
+            &vault,
+            &interest_vault,
+            token_account,
+            amount,
+        );
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![delegate]))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn user_set_delegate(
+        &self,
+        user: &Keypair,
+        mint: &Pubkey,
+        delegate: &Pubkey,
+        allowance: u64,
+        period: i64,
+    ) -> Result<()> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let manager = pda::derive_savings_manager_pda(&user.pubkey(), &distributor, 0).0;
+
+        let (_, instruction) =
+            user_set_delegate(&user.pubkey(), &manager, delegate, allowance, period);
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![user]))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn user_revoke_delegate(&self, user: &Keypair, mint: &Pubkey) -> Result<()> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let manager = pda::derive_savings_manager_pda(&user.pubkey(), &distributor, 0).0;
+
+        let (_, instruction) = user_revoke_delegate(&user.pubkey(), &manager);
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![user]))
+            .await?;
+        Ok(())
+    }
+
+    /// Approves the user's savings-manager to pull up to `approved_amount` tokens from
+    /// `token_account`, and sets up a savings plan drawing from it.
+    pub async fn user_create_savings_plan(
+        &self,
+        user: &Keypair,
+        mint: &Pubkey,
+        token_account: &Pubkey,
+        approved_amount: u64,
+        amount: u64,
+        interval: i64,
+    ) -> Result<()> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let manager = pda::derive_savings_manager_pda(&user.pubkey(), &distributor, 0).0;
+        let plan = pda::derive_savings_plan_pda(&manager).0;
+
+        let approve = anchor_spl::token::spl_token::instruction::approve(
+            &anchor_spl::token::ID,
+            token_account,
+            &manager,
+            &user.pubkey(),
+            &[],
+            approved_amount,
+        )?;
+        let (_, instruction) = user_create_savings_plan(
+            &self.ctx.borrow().payer.pubkey(),
+            &user.pubkey(),
+            &manager,
+            token_account,
+            &plan,
+            amount,
+            interval,
+        );
+
+        self.send_and_confirm_tx(vec![approve, instruction], Some(vec![user]))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn user_close_savings_plan(&self, user: &Keypair, mint: &Pubkey) -> Result<()> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let manager = pda::derive_savings_manager_pda(&user.pubkey(), &distributor, 0).0;
+        let plan = pda::derive_savings_plan_pda(&manager).0;
+
+        let (_, instruction) = user_close_savings_plan(&user.pubkey(), &plan);
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![user]))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn execute_savings_plan(
+        &self,
+        user: &Pubkey,
+        mint: &Pubkey,
+        source_token_account: &Pubkey,
+    ) -> Result<()> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let manager = pda::derive_savings_manager_pda(user, &distributor, 0).0;
+        let plan = pda::derive_savings_plan_pda(&manager).0;
+        let vault = pda::derive_savings_vault_ata(mint, &manager);
+
+        let allowlist_entry = self.allowlist_entry(user).await?;
+        let receipt_mint = self
+            .get_deserialized_account::<InterestDistributor>(&distributor)
+            .await?
+            .receipt_mint;
+        let receipt_token_account = receipt_mint.map(|receipt_mint| {
+            anchor_spl::associated_token::get_associated_token_address(user, &receipt_mint)
+        });
+        let receipt_pool = receipt_mint.map(|_| pda::derive_receipt_pool_pda(&distributor).0);
+
+        let (_, instruction) = execute_savings_plan(
+            &plan,
+            &manager,
+            source_token_account,
+            &vault,
+            &distributor,
+            allowlist_entry.as_ref(),
+            receipt_mint.as_ref(),
+            receipt_token_account.as_ref(),
+            receipt_pool.as_ref(),
+        );
+
+        self.send_and_confirm_tx(vec![instruction], None).await?;
+        Ok(())
+    }
+
+    pub async fn claim_campaign(
+        &self,
+        user: &Pubkey,
+        mint: &Pubkey,
+        campaign: &Pubkey,
+        index: u32,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let manager = pda::derive_savings_manager_pda(user, &distributor, 0).0;
+        let vault = pda::derive_savings_vault_ata(mint, &manager);
+        let campaign_vault = pda::derive_campaign_vault_ata(mint, campaign);
+        let allowlist_entry = self.allowlist_entry(user).await?;
+
+        let (_, instruction) = claim_campaign(
+            campaign,
+            &campaign_vault,
+            &manager,
+            &vault,
+            &distributor,
+            allowlist_entry.as_ref(),
+            index,
+            amount,
+            proof,
+        );
+
+        self.send_and_confirm_tx(vec![instruction], None).await?;
+        Ok(())
+    }
+
+    pub async fn preview_interest(&self, user: &Pubkey, mint: &Pubkey) -> Result<InterestPreview> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let interest_vault = pda::derive_interest_vault_ata(mint, &distributor);
+        let manager = pda::derive_savings_manager_pda(user, &distributor, 0).0;
+        let savings_vault = pda::derive_savings_vault_ata(mint, &manager);
+        let rate_history = pda::derive_rate_history_pda(&distributor).0;
+        let rate_feed = self
+            .get_deserialized_account::<RateHistory>(&rate_history)
+            .await?
+            .rate_feed;
+
+        let (_, instruction) = preview_interest(
+            &manager,
+            &savings_vault,
+            &distributor,
+            &interest_vault,
+            &rate_history,
+            rate_feed.as_ref(),
+        );
+
+        let (mut banks_client, tx) = {
+            let ctx = self.ctx.borrow();
+            let tx = Transaction::new_signed_with_payer(
+                &[instruction],
+                Some(&ctx.payer.pubkey()),
+                &[&ctx.payer],
+                ctx.last_blockhash,
+            );
+            (ctx.banks_client.clone(), tx)
+        };
+        utils::simulate_tx(&mut banks_client, tx).await
+    }
+
+    pub async fn deposit_interest(&self, user: &Pubkey, mint: &Pubkey) -> Result<()> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let interest_vault = pda::derive_interest_vault_ata(mint, &distributor);
+        let manager = pda::derive_savings_manager_pda(user, &distributor, 0).0;
+        let savings_vault = pda::derive_savings_vault_ata(mint, &manager);
+        let rate_history = pda::derive_rate_history_pda(&distributor).0;
+        let rate_feed = self
+            .get_deserialized_account::<RateHistory>(&rate_history)
+            .await?
+            .rate_feed;
+        let referrer = self.referrer_savings_accounts(&manager).await?;
+        let fee_destination = self
+            .get_deserialized_account::<InterestDistributor>(&distributor)
+            .await?
+            .fee_destination;
+
+        let (_, instruction) = deposit_interest(
+            user,
+            &manager,
+            &savings_vault,
+            &distributor,
+            &interest_vault,
+            &rate_history,
+            rate_feed.as_ref(),
+            referrer.as_ref().map(|(manager, _)| manager),
+            referrer.as_ref().map(|(_, vault)| vault),
+            fee_destination.as_ref(),
+        );
+
+        self.send_and_confirm_tx(vec![instruction], None).await?;
+        Ok(())
+    }
+
+    pub async fn deposit_interest_multiple(&self, users: &[Pubkey], mint: &Pubkey) -> Result<()> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let interest_vault = pda::derive_interest_vault_ata(mint, &distributor);
+
+        let mut remaining_accounts = Vec::with_capacity(users.len() * 5);
+        for user in users {
+            let manager = pda::derive_savings_manager_pda(user, &distributor, 0).0;
+            let savings_vault = pda::derive_savings_vault_ata(mint, &manager);
+            let (referrer_manager, referrer_vault) =
+                match self.referrer_savings_accounts(&manager).await? {
+                    Some((manager, vault)) => (
+                        AccountMeta::new_readonly(manager, false),
+                        AccountMeta::new(vault, false),
+                    ),
+                    None => (
+                        AccountMeta::new_readonly(savings_program::ID, false),
+                        AccountMeta::new_readonly(savings_program::ID, false),
+                    ),
+                };
+
+            remaining_accounts.extend([
+                AccountMeta::new_readonly(*user, false),
+                AccountMeta::new(manager, false),
+                AccountMeta::new(savings_vault, false),
+                referrer_manager,
+                referrer_vault,
+            ]);
+        }
+
+        let rate_history = pda::derive_rate_history_pda(&distributor).0;
+        let rate_feed = self
+            .get_deserialized_account::<RateHistory>(&rate_history)
+            .await?
+            .rate_feed;
+        let fee_destination = self
+            .get_deserialized_account::<InterestDistributor>(&distributor)
+            .await?
+            .fee_destination;
+        let (_, instruction) = deposit_interest_multiple(
+            &distributor,
+            &interest_vault,
+            &rate_history,
+            rate_feed.as_ref(),
+            fee_destination.as_ref(),
+            remaining_accounts,
+        );
+
+        self.send_and_confirm_tx(vec![instruction], None).await?;
+        Ok(())
+    }
+
+    /// The savings-manager and savings-vault that referral bonuses on a savings-manager's interest
+    /// are paid to.
+    async fn referrer_savings_accounts(
+        &self,
+        savings_manager: &Pubkey,
+    ) -> Result<Option<(Pubkey, Pubkey)>> {
+        let manager = self
+            .get_deserialized_account::<SavingsManager>(savings_manager)
+            .await?;
+        Ok(manager.referrer.map(|referrer| {
+            let referrer_manager =
+                pda::derive_savings_manager_pda(&referrer, &manager.distributor, 0).0;
+            (
+                referrer_manager,
+                pda::derive_savings_vault_ata(&manager.mint, &referrer_manager),
+            )
+        }))
+    }
+
+    pub async fn create_registry_page(&self, mint: &Pubkey) -> Result<Pubkey> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let page = self
+            .get_deserialized_account::<InterestDistributor>(&distributor)
+            .await?
+            .registry_pages;
+        let registry_page = pda::derive_registry_page_pda(&distributor, page).0;
+
+        let (_, instruction) = create_registry_page(
+            &self.ctx.borrow().payer.pubkey(),
+            &distributor,
+            &registry_page,
+        );
+
+        self.send_and_confirm_tx(vec![instruction], None).await?;
+        Ok(registry_page)
+    }
+
+    pub async fn register_savings_manager(
+        &self,
+        savings_manager: &Pubkey,
+        page: u32,
+    ) -> Result<()> {
+        let distributor = self
+            .get_deserialized_account::<SavingsManager>(savings_manager)
+            .await?
+            .distributor;
+        let registry_page = pda::derive_registry_page_pda(&distributor, page).0;
+
+        let (_, instruction) = register_savings_manager(savings_manager, &registry_page);
+
+        self.send_and_confirm_tx(vec![instruction], None).await?;
+        Ok(())
+    }
+
+    /// Every savings-manager listed in the registry of `mint`'s distributor, page by page.
+    pub async fn registered_savings_managers(&self, mint: &Pubkey) -> Result<Vec<Pubkey>> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let pages = self
+            .get_deserialized_account::<InterestDistributor>(&distributor)
+            .await?
+            .registry_pages;
+
+        let mut managers = Vec::new();
+        for page in 0..pages {
+            let registry_page = pda::derive_registry_page_pda(&distributor, page).0;
+            let registry_page = self
+                .get_deserialized_account::<RegistryPage>(&registry_page)
+                .await?;
+            managers.extend(registry_page.managers);
+        }
+        Ok(managers)
+    }
+
+    /// The last page of a distributor's registry, which new vaults are listed on.
+    async fn last_registry_page(&self, distributor: &Pubkey) -> Result<Pubkey> {
+        let pages = self
+            .get_deserialized_account::<InterestDistributor>(distributor)
+            .await?
+            .registry_pages;
+        Ok(pda::derive_registry_page_pda(distributor, pages.saturating_sub(1)).0)
+    }
+
+    /// The user's allowlist entry, if one has been added.
+    async fn allowlist_entry(&self, user: &Pubkey) -> Result<Option<Pubkey>> {
+        self.existing_account(&pda::derive_allowlist_entry_pda(&self.state, user).0)
+            .await
+    }
+
+    /// Returns `address` if an account exists there.
+    async fn existing_account(&self, address: &Pubkey) -> Result<Option<Pubkey>> {
+        let mut banks_client = self.ctx.borrow().banks_client.clone();
+        Ok(banks_client.get_account(*address).await?.map(|_| *address))
+    }
+
+    pub async fn add_allowlist_entry(
+        &self,
+        verifier: &Keypair,
+        user: &Pubkey,
+        expires_at: i64,
+    ) -> Result<()> {
+        let entry = pda::derive_allowlist_entry_pda(&self.state, user).0;
+
+        let (_, instruction) =
+            add_allowlist_entry(&verifier.pubkey(), &self.state, &entry, user, expires_at);
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![verifier]))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn remove_allowlist_entry(&self, verifier: &Keypair, user: &Pubkey) -> Result<()> {
+        let entry = pda::derive_allowlist_entry_pda(&self.state, user).0;
+
+        let (_, instruction) = remove_allowlist_entry(&verifier.pubkey(), &self.state, &entry);
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![verifier]))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn freeze_savings_manager(
+        &self,
+        user: &Pubkey,
+        mint: &Pubkey,
+        reason_code: u16,
+    ) -> Result<()> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let manager = pda::derive_savings_manager_pda(user, &distributor, 0).0;
+
+        let (_, instruction) = freeze_savings_manager(
+            &self.admin.pubkey(),
+            &self.state,
+            &distributor,
+            &manager,
+            reason_code,
+        );
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![&self.admin]))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn unfreeze_savings_manager(
+        &self,
+        user: &Pubkey,
+        mint: &Pubkey,
+        reason_code: u16,
+    ) -> Result<()> {
+        let distributor = pda::derive_interest_distributor_pda(&self.state, mint, 0).0;
+        let manager = pda::derive_savings_manager_pda(user, &distributor, 0).0;
+
+        let (_, instruction) = unfreeze_savings_manager(
+            &self.admin.pubkey(),
+            &self.state,
+            &distributor,
+            &manager,
+            reason_code,
+        );
+
+        self.send_and_confirm_tx(vec![instruction], Some(vec![&self.admin]))
+            .await?;
+        Ok(())
+    }
+
+    /// Creates a new user funded with SOL for fees, and an associated token account holding
+    /// `amount` freshly minted tokens.
+    pub async fn create_token_account(&self, owner: &Pubkey, mint: &Pubkey) -> Result<Pubkey> {
+        let payer = self.ctx.borrow().payer.pubkey();
+        let (ata, create_ata_ix) = utils::create_associated_token_account(&payer, owner, mint);
+
+        self.send_and_confirm_tx(vec![create_ata_ix], None).await?;
+
+        Ok(ata)
+    }
+
+    pub async fn create_funded_user(
+        &self,
+        mint: &Pubkey,
+        mint_authority: &Keypair,
+        amount: u64,
+    ) -> Result<(Keypair, Pubkey)> {
+        let user = Keypair::new();
+        let payer = self.ctx.borrow().payer.pubkey();
+
+        let transfer_ix = system_instruction::transfer(&payer, &user.pubkey(), 1_000_000_000);
+        let (user_ata, create_ata_ix) =
+            utils::create_associated_token_account(&payer, &user.pubkey(), mint);
+        let mint_to = utils::mint_tokens(mint, &user_ata, &mint_authority.pubkey(), amount)?;
+
+        self.send_and_confirm_tx(
+            vec![transfer_ix, create_ata_ix, mint_to],
+            Some(vec![mint_authority]),
+        )
+        .await?;
+
+        Ok((user, user_ata))
+    }
+
+    /// Moves the cluster clock forward by `seconds`.
+    pub async fn advance_clock(&self, seconds: i64) -> Result<()> {
+        let mut banks_client = self.ctx.borrow().banks_client.clone();
+        let mut clock = banks_client.get_sysvar::<Clock>().await?;
+        clock.unix_timestamp += seconds;
+        self.ctx.borrow_mut().set_sysvar(&clock);
+        self.refresh_blockhash().await
+    }
+
+    /// Fetches a new blockhash, so that repeating an identical transaction is not treated as
+    /// a duplicate of the earlier one.
+    pub async fn refresh_blockhash(&self) -> Result<()> {
+        let mut banks_client = self.ctx.borrow().banks_client.clone();
+        let last_blockhash = self.ctx.borrow().last_blockhash;
+        let blockhash = banks_client
+            .get_new_latest_blockhash(&last_blockhash)
+            .await?;
+        self.ctx.borrow_mut().last_blockhash = blockhash;
+        Ok(())
+    }
+
+    pub async fn current_time(&self) -> Result<i64> {
+        let mut banks_client = self.ctx.borrow().banks_client.clone();
+        let clock = banks_client.get_sysvar::<Clock>().await?;
+        Ok(clock.unix_timestamp)
+    }
+
     pub async fn get_account(&self, address: &Pubkey) -> Result<Account> {
         let account = self
             .ctx
@@ -1,7 +1,9 @@
 use anchor_lang::{InstructionData, ToAccountMetas};
 use savings_program::accounts::*;
 use savings_program::instruction;
-use solana_sdk::{instruction::Instruction, pubkey::Pubkey, system_program};
+use savings_program::ConfigChange;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::{pubkey::Pubkey, system_program};
 
 pub fn initialize_state(
     initializer: &Pubkey,
@@ -28,27 +30,35 @@ pub fn initialize_state(
     (accounts, instruction)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn create_interest_vault(
     payer: &Pubkey,
     authority: &Pubkey,
     state: &Pubkey,
     mint: &Pubkey,
+    approved_mint: Option<&Pubkey>,
     distributor: &Pubkey,
     interest_vault: &Pubkey,
+    rate_history: &Pubkey,
+    registry_page: &Pubkey,
+    product_id: u16,
 ) -> (CreateInterestVaultForMint, Instruction) {
     let accounts = CreateInterestVaultForMint {
         authority: *authority,
         payer: *payer,
         state: *state,
         mint: *mint,
+        approved_mint: approved_mint.copied(),
         interest_distributor: *distributor,
         interest_vault: *interest_vault,
+        rate_history: *rate_history,
+        registry_page: *registry_page,
         system_program: system_program::id(),
         token_program: anchor_spl::token::ID,
         associated_token_program: anchor_spl::associated_token::ID,
     };
 
-    let data = instruction::CreateInterestVault {}.data();
+    let data = instruction::CreateInterestVault { product_id }.data();
 
     let instruction = Instruction {
         program_id: savings_program::ID,
@@ -58,6 +68,281 @@ pub fn create_interest_vault(
 
     (accounts, instruction)
 }
+
+pub fn create_receipt_mint(
+    payer: &Pubkey,
+    authority: &Pubkey,
+    state: &Pubkey,
+    mint: &Pubkey,
+    distributor: &Pubkey,
+    receipt_mint: &Pubkey,
+    receipt_pool: &Pubkey,
+) -> (CreateReceiptMint, Instruction) {
+    let accounts = CreateReceiptMint {
+        authority: *authority,
+        payer: *payer,
+        state: *state,
+        mint: *mint,
+        interest_distributor: *distributor,
+        receipt_mint: *receipt_mint,
+        receipt_pool: *receipt_pool,
+        system_program: system_program::id(),
+        token_program: anchor_spl::token::ID,
+    };
+
+    let data = instruction::CreateReceiptMint {}.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn create_yield_adapter(
+    payer: &Pubkey,
+    authority: &Pubkey,
+    state: &Pubkey,
+    distributor: &Pubkey,
+    yield_adapter: &Pubkey,
+    mint: &Pubkey,
+    yield_vault: &Pubkey,
+) -> (CreateYieldAdapter, Instruction) {
+    let accounts = CreateYieldAdapter {
+        authority: *authority,
+        payer: *payer,
+        state: *state,
+        interest_distributor: *distributor,
+        yield_adapter: *yield_adapter,
+        mint: *mint,
+        yield_vault: *yield_vault,
+        system_program: system_program::id(),
+        token_program: anchor_spl::token::ID,
+        associated_token_program: anchor_spl::associated_token::ID,
+    };
+
+    let data = instruction::CreateYieldAdapter {}.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn manage_yield_funds(
+    authority: &Pubkey,
+    state: &Pubkey,
+    distributor: &Pubkey,
+    yield_adapter: &Pubkey,
+    interest_vault: &Pubkey,
+    yield_vault: &Pubkey,
+    yield_program: &Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+    allocate: bool,
+    amount: u64,
+) -> (ManageYieldFunds, Instruction) {
+    let accounts = ManageYieldFunds {
+        authority: *authority,
+        state: *state,
+        interest_distributor: *distributor,
+        yield_adapter: *yield_adapter,
+        interest_vault: *interest_vault,
+        yield_vault: *yield_vault,
+        yield_program: *yield_program,
+        token_program: anchor_spl::token::ID,
+    };
+
+    let data = if allocate {
+        instruction::AllocateToYieldSource { amount }.data()
+    } else {
+        instruction::RecallFromYieldSource { amount }.data()
+    };
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: [accounts.to_account_metas(None), remaining_accounts].concat(),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn create_rate_feed(
+    payer: &Pubkey,
+    authority: &Pubkey,
+    state: &Pubkey,
+    rate_feed: &Pubkey,
+    publisher: &Pubkey,
+) -> (CreateRateFeed, Instruction) {
+    let accounts = CreateRateFeed {
+        authority: *authority,
+        payer: *payer,
+        state: *state,
+        rate_feed: *rate_feed,
+        system_program: system_program::id(),
+    };
+
+    let data = instruction::CreateRateFeed {
+        publisher: *publisher,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn publish_rate(
+    publisher: &Pubkey,
+    rate_feed: &Pubkey,
+    rate_bps: u16,
+) -> (PublishRate, Instruction) {
+    let accounts = PublishRate {
+        publisher: *publisher,
+        rate_feed: *rate_feed,
+    };
+
+    let data = instruction::PublishRate { rate_bps }.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn approve_mint(
+    authority: &Pubkey,
+    state: &Pubkey,
+    mint: &Pubkey,
+    approved_mint: &Pubkey,
+) -> (ApproveMint, Instruction) {
+    let accounts = ApproveMint {
+        authority: *authority,
+        state: *state,
+        mint: *mint,
+        approved_mint: *approved_mint,
+        system_program: system_program::id(),
+    };
+
+    let data = instruction::ApproveMint {}.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_campaign(
+    payer: &Pubkey,
+    authority: &Pubkey,
+    state: &Pubkey,
+    mint: &Pubkey,
+    distributor: &Pubkey,
+    campaign: &Pubkey,
+    campaign_vault: &Pubkey,
+    campaign_id: u64,
+    merkle_root: [u8; 32],
+    max_claims: u32,
+    expires_ts: i64,
+) -> (CreateCampaign, Instruction) {
+    let accounts = CreateCampaign {
+        authority: *authority,
+        payer: *payer,
+        state: *state,
+        mint: *mint,
+        interest_distributor: *distributor,
+        campaign: *campaign,
+        campaign_vault: *campaign_vault,
+        system_program: system_program::id(),
+        token_program: anchor_spl::token::ID,
+        associated_token_program: anchor_spl::associated_token::ID,
+    };
+
+    let data = instruction::CreateCampaign {
+        campaign_id,
+        merkle_root,
+        max_claims,
+        expires_ts,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn close_campaign(
+    authority: &Pubkey,
+    state: &Pubkey,
+    distributor: &Pubkey,
+    campaign: &Pubkey,
+    campaign_vault: &Pubkey,
+    interest_vault: &Pubkey,
+) -> (CloseCampaign, Instruction) {
+    let accounts = CloseCampaign {
+        authority: *authority,
+        state: *state,
+        interest_distributor: *distributor,
+        campaign: *campaign,
+        campaign_vault: *campaign_vault,
+        interest_vault: *interest_vault,
+        token_program: anchor_spl::token::ID,
+    };
+
+    let data = instruction::CloseCampaign {}.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn revoke_mint_approval(
+    authority: &Pubkey,
+    state: &Pubkey,
+    approved_mint: &Pubkey,
+) -> (RevokeMintApproval, Instruction) {
+    let accounts = RevokeMintApproval {
+        authority: *authority,
+        state: *state,
+        approved_mint: *approved_mint,
+    };
+
+    let data = instruction::RevokeMintApproval {}.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
 pub fn deposit_to_interest_vault(
     authority: &Pubkey,
     state: &Pubkey,
@@ -116,6 +401,7 @@ pub fn withdraw_from_interest_vault(
     (accounts, instruction)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn user_create_vault(
     payer: &Pubkey,
     user: &Pubkey,
@@ -123,6 +409,11 @@ pub fn user_create_vault(
     distributor: &Pubkey,
     savings_manager: &Pubkey,
     savings_vault: &Pubkey,
+    registry_page: &Pubkey,
+    allowlist_entry: Option<&Pubkey>,
+    referrer: Option<&Pubkey>,
+    index: u16,
+    label: [u8; 32],
 ) -> (UserCreateVault, Instruction) {
     let accounts = UserCreateVault {
         payer: *payer,
@@ -131,12 +422,61 @@ pub fn user_create_vault(
         interest_distributor: *distributor,
         savings_manager: *savings_manager,
         savings_vault: *savings_vault,
+        registry_page: *registry_page,
+        allowlist_entry: allowlist_entry.copied(),
+        system_program: system_program::ID,
+        token_program: anchor_spl::token::ID,
+        associated_token_program: anchor_spl::associated_token::ID,
+    };
+
+    let data = instruction::UserCreateVault {
+        referrer: referrer.copied(),
+        index,
+        label,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn user_transfer_position(
+    payer: &Pubkey,
+    user: &Pubkey,
+    new_user: &Pubkey,
+    mint: &Pubkey,
+    distributor: &Pubkey,
+    savings_manager: &Pubkey,
+    savings_vault: &Pubkey,
+    new_savings_manager: &Pubkey,
+    new_savings_vault: &Pubkey,
+    registry_page: &Pubkey,
+    allowlist_entry: Option<&Pubkey>,
+) -> (UserTransferPosition, Instruction) {
+    let accounts = UserTransferPosition {
+        payer: *payer,
+        user: *user,
+        new_user: *new_user,
+        interest_distributor: *distributor,
+        savings_manager: *savings_manager,
+        savings_vault: *savings_vault,
+        mint: *mint,
+        new_savings_manager: *new_savings_manager,
+        new_savings_vault: *new_savings_vault,
+        registry_page: *registry_page,
+        allowlist_entry: allowlist_entry.copied(),
         system_program: system_program::ID,
         token_program: anchor_spl::token::ID,
         associated_token_program: anchor_spl::associated_token::ID,
     };
 
-    let data = instruction::UserCreateVault {}.data();
+    let data = instruction::UserTransferPosition {}.data();
 
     let instruction = Instruction {
         program_id: savings_program::ID,
@@ -147,18 +487,29 @@ pub fn user_create_vault(
     (accounts, instruction)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn user_deposit(
     user: &Pubkey,
     user_token_account: &Pubkey,
     savings_manager: &Pubkey,
+    interest_distributor: &Pubkey,
+    allowlist_entry: Option<&Pubkey>,
     savings_vault: &Pubkey,
+    receipt_mint: Option<&Pubkey>,
+    receipt_token_account: Option<&Pubkey>,
+    receipt_pool: Option<&Pubkey>,
     amount: u64,
 ) -> (UserDeposit, Instruction) {
     let accounts = UserDeposit {
         user: *user,
         user_token_account: *user_token_account,
         savings_manager: *savings_manager,
+        interest_distributor: *interest_distributor,
+        allowlist_entry: allowlist_entry.copied(),
         savings_vault: *savings_vault,
+        receipt_mint: receipt_mint.copied(),
+        receipt_token_account: receipt_token_account.copied(),
+        receipt_pool: receipt_pool.copied(),
         token_program: anchor_spl::token::ID,
     };
 
@@ -173,17 +524,51 @@ pub fn user_deposit(
     (accounts, instruction)
 }
 
+pub fn redeem_receipts(
+    holder: &Pubkey,
+    interest_distributor: &Pubkey,
+    receipt_mint: &Pubkey,
+    holder_receipt_account: &Pubkey,
+    receipt_pool: &Pubkey,
+    destination_token_account: &Pubkey,
+    amount: u64,
+) -> (RedeemReceipts, Instruction) {
+    let accounts = RedeemReceipts {
+        holder: *holder,
+        interest_distributor: *interest_distributor,
+        receipt_mint: *receipt_mint,
+        holder_receipt_account: *holder_receipt_account,
+        receipt_pool: *receipt_pool,
+        destination_token_account: *destination_token_account,
+        token_program: anchor_spl::token::ID,
+    };
+
+    let data = instruction::RedeemReceipts { amount }.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
 pub fn user_withdraw(
-    user: &Pubkey,
+    authority: &Pubkey,
     savings_manager: &Pubkey,
+    interest_distributor: &Pubkey,
     savings_vault: &Pubkey,
+    interest_vault: &Pubkey,
     destination_token_account: &Pubkey,
     amount: u64,
 ) -> (UserWithdraw, Instruction) {
     let accounts = UserWithdraw {
-        user: *user,
+        authority: *authority,
         savings_manager: *savings_manager,
+        interest_distributor: *interest_distributor,
         savings_vault: *savings_vault,
+        interest_vault: *interest_vault,
         destination_token_account: *destination_token_account,
         token_program: anchor_spl::token::ID,
     };
@@ -199,23 +584,781 @@ pub fn user_withdraw(
     (accounts, instruction)
 }
 
-pub fn deposit_interest(
+pub fn user_deposit_native(
     user: &Pubkey,
-    user_savings_manager: &Pubkey,
-    user_savings_vault: &Pubkey,
+    savings_manager: &Pubkey,
     interest_distributor: &Pubkey,
-    interest_vault: &Pubkey,
-) -> (DepositInterestToUser, Instruction) {
-    let accounts = DepositInterestToUser {
+    allowlist_entry: Option<&Pubkey>,
+    savings_vault: &Pubkey,
+    amount: u64,
+) -> (UserDepositNative, Instruction) {
+    let accounts = UserDepositNative {
         user: *user,
-        user_savings_manager: *user_savings_manager,
-        user_savings_vault: *user_savings_vault,
+        savings_manager: *savings_manager,
+        interest_distributor: *interest_distributor,
+        allowlist_entry: allowlist_entry.copied(),
+        savings_vault: *savings_vault,
+        system_program: system_program::ID,
+        token_program: anchor_spl::token::ID,
+    };
+
+    let data = instruction::UserDepositNative { amount }.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn user_withdraw_native(
+    authority: &Pubkey,
+    savings_manager: &Pubkey,
+    interest_distributor: &Pubkey,
+    savings_vault: &Pubkey,
+    interest_vault: &Pubkey,
+    unwrap_account: &Pubkey,
+    amount: u64,
+) -> (UserWithdrawNative, Instruction) {
+    let accounts = UserWithdrawNative {
+        authority: *authority,
+        savings_manager: *savings_manager,
+        interest_distributor: *interest_distributor,
+        savings_vault: *savings_vault,
+        interest_vault: *interest_vault,
+        native_mint: anchor_spl::token::spl_token::native_mint::ID,
+        unwrap_account: *unwrap_account,
+        system_program: system_program::ID,
+        token_program: anchor_spl::token::ID,
+    };
+
+    let data = instruction::UserWithdrawNative { amount }.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn request_withdrawal(
+    authority: &Pubkey,
+    savings_manager: &Pubkey,
+    savings_vault: &Pubkey,
+    amount: u64,
+) -> (RequestWithdrawal, Instruction) {
+    let accounts = RequestWithdrawal {
+        authority: *authority,
+        savings_manager: *savings_manager,
+        savings_vault: *savings_vault,
+    };
+
+    let data = instruction::RequestWithdrawal { amount }.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn execute_withdrawal(
+    authority: &Pubkey,
+    savings_manager: &Pubkey,
+    interest_distributor: &Pubkey,
+    savings_vault: &Pubkey,
+    interest_vault: &Pubkey,
+    destination_token_account: &Pubkey,
+) -> (ExecuteWithdrawal, Instruction) {
+    let accounts = ExecuteWithdrawal {
+        authority: *authority,
+        savings_manager: *savings_manager,
+        interest_distributor: *interest_distributor,
+        savings_vault: *savings_vault,
+        interest_vault: *interest_vault,
+        destination_token_account: *destination_token_account,
+        token_program: anchor_spl::token::ID,
+    };
+
+    let data = instruction::ExecuteWithdrawal {}.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn user_set_goal(
+    user: &Pubkey,
+    savings_manager: &Pubkey,
+    target_amount: u64,
+    target_ts: i64,
+) -> (UserSetGoal, Instruction) {
+    let accounts = UserSetGoal {
+        user: *user,
+        savings_manager: *savings_manager,
+    };
+
+    let data = instruction::UserSetGoal {
+        target_amount,
+        target_ts,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn user_cancel_goal(
+    user: &Pubkey,
+    savings_manager: &Pubkey,
+    interest_distributor: &Pubkey,
+    savings_vault: &Pubkey,
+    interest_vault: &Pubkey,
+) -> (UserCancelGoal, Instruction) {
+    let accounts = UserCancelGoal {
+        user: *user,
+        savings_manager: *savings_manager,
+        interest_distributor: *interest_distributor,
+        savings_vault: *savings_vault,
+        interest_vault: *interest_vault,
+        token_program: anchor_spl::token::ID,
+    };
+
+    let data = instruction::UserCancelGoal {}.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn user_set_delegate(
+    user: &Pubkey,
+    savings_manager: &Pubkey,
+    delegate: &Pubkey,
+    allowance: u64,
+    period: i64,
+) -> (UserManageDelegate, Instruction) {
+    let accounts = UserManageDelegate {
+        user: *user,
+        savings_manager: *savings_manager,
+    };
+
+    let data = instruction::UserSetDelegate {
+        delegate: *delegate,
+        allowance,
+        period,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn user_revoke_delegate(
+    user: &Pubkey,
+    savings_manager: &Pubkey,
+) -> (UserManageDelegate, Instruction) {
+    let accounts = UserManageDelegate {
+        user: *user,
+        savings_manager: *savings_manager,
+    };
+
+    let data = instruction::UserRevokeDelegate {}.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn user_create_savings_plan(
+    payer: &Pubkey,
+    user: &Pubkey,
+    savings_manager: &Pubkey,
+    source_token_account: &Pubkey,
+    savings_plan: &Pubkey,
+    amount: u64,
+    interval: i64,
+) -> (UserCreateSavingsPlan, Instruction) {
+    let accounts = UserCreateSavingsPlan {
+        payer: *payer,
+        user: *user,
+        savings_manager: *savings_manager,
+        source_token_account: *source_token_account,
+        savings_plan: *savings_plan,
+        system_program: system_program::ID,
+    };
+
+    let data = instruction::UserCreateSavingsPlan { amount, interval }.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn user_close_savings_plan(
+    user: &Pubkey,
+    savings_plan: &Pubkey,
+) -> (UserCloseSavingsPlan, Instruction) {
+    let accounts = UserCloseSavingsPlan {
+        user: *user,
+        savings_plan: *savings_plan,
+    };
+
+    let data = instruction::UserCloseSavingsPlan {}.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_savings_plan(
+    savings_plan: &Pubkey,
+    savings_manager: &Pubkey,
+    source_token_account: &Pubkey,
+    savings_vault: &Pubkey,
+    interest_distributor: &Pubkey,
+    allowlist_entry: Option<&Pubkey>,
+    receipt_mint: Option<&Pubkey>,
+    receipt_token_account: Option<&Pubkey>,
+    receipt_pool: Option<&Pubkey>,
+) -> (ExecuteSavingsPlan, Instruction) {
+    let accounts = ExecuteSavingsPlan {
+        savings_plan: *savings_plan,
+        savings_manager: *savings_manager,
+        source_token_account: *source_token_account,
+        savings_vault: *savings_vault,
+        interest_distributor: *interest_distributor,
+        allowlist_entry: allowlist_entry.copied(),
+        receipt_mint: receipt_mint.copied(),
+        receipt_token_account: receipt_token_account.copied(),
+        receipt_pool: receipt_pool.copied(),
+        token_program: anchor_spl::token::ID,
+    };
+
+    let data = instruction::ExecuteSavingsPlan {}.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn claim_campaign(
+    campaign: &Pubkey,
+    campaign_vault: &Pubkey,
+    savings_manager: &Pubkey,
+    savings_vault: &Pubkey,
+    interest_distributor: &Pubkey,
+    allowlist_entry: Option<&Pubkey>,
+    index: u32,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> (ClaimCampaign, Instruction) {
+    let accounts = ClaimCampaign {
+        campaign: *campaign,
+        campaign_vault: *campaign_vault,
+        savings_manager: *savings_manager,
+        savings_vault: *savings_vault,
+        interest_distributor: *interest_distributor,
+        allowlist_entry: allowlist_entry.copied(),
+        token_program: anchor_spl::token::ID,
+    };
+
+    let data = instruction::ClaimCampaign {
+        index,
+        amount,
+        proof,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn preview_interest(
+    savings_manager: &Pubkey,
+    savings_vault: &Pubkey,
+    interest_distributor: &Pubkey,
+    interest_vault: &Pubkey,
+    rate_history: &Pubkey,
+    rate_feed: Option<&Pubkey>,
+) -> (PreviewInterest, Instruction) {
+    let accounts = PreviewInterest {
+        savings_manager: *savings_manager,
+        savings_vault: *savings_vault,
+        interest_distributor: *interest_distributor,
+        interest_vault: *interest_vault,
+        rate_history: *rate_history,
+        rate_feed: rate_feed.copied(),
+    };
+
+    let data = instruction::PreviewInterest {}.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_interest(
+    user: &Pubkey,
+    user_savings_manager: &Pubkey,
+    user_savings_vault: &Pubkey,
+    interest_distributor: &Pubkey,
+    interest_vault: &Pubkey,
+    rate_history: &Pubkey,
+    rate_feed: Option<&Pubkey>,
+    referrer_savings_manager: Option<&Pubkey>,
+    referrer_savings_vault: Option<&Pubkey>,
+    fee_destination: Option<&Pubkey>,
+) -> (DepositInterestToUser, Instruction) {
+    let accounts = DepositInterestToUser {
+        user: *user,
+        user_savings_manager: *user_savings_manager,
+        user_savings_vault: *user_savings_vault,
+        interest_distributor: *interest_distributor,
+        interest_vault: *interest_vault,
+        rate_history: *rate_history,
+        rate_feed: rate_feed.copied(),
+        referrer_savings_manager: referrer_savings_manager.copied(),
+        referrer_savings_vault: referrer_savings_vault.copied(),
+        fee_destination: fee_destination.copied(),
+        token_program: anchor_spl::token::ID,
+    };
+
+    let data = instruction::DepositInterest {}.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn migrate_state(payer: &Pubkey, state: &Pubkey) -> (MigrateState, Instruction) {
+    let accounts = MigrateState {
+        payer: *payer,
+        state: *state,
+        system_program: system_program::ID,
+    };
+
+    let data = instruction::MigrateState {}.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn migrate_interest_distributor(
+    payer: &Pubkey,
+    interest_distributor: &Pubkey,
+) -> (MigrateInterestDistributor, Instruction) {
+    let accounts = MigrateInterestDistributor {
+        payer: *payer,
+        interest_distributor: *interest_distributor,
+        system_program: system_program::ID,
+    };
+
+    let data = instruction::MigrateInterestDistributor {}.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn migrate_savings_manager(
+    payer: &Pubkey,
+    savings_manager: &Pubkey,
+) -> (MigrateSavingsManager, Instruction) {
+    let accounts = MigrateSavingsManager {
+        payer: *payer,
+        savings_manager: *savings_manager,
+        system_program: system_program::ID,
+    };
+
+    let data = instruction::MigrateSavingsManager {}.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn deposit_interest_multiple(
+    interest_distributor: &Pubkey,
+    interest_vault: &Pubkey,
+    rate_history: &Pubkey,
+    rate_feed: Option<&Pubkey>,
+    fee_destination: Option<&Pubkey>,
+    remaining_accounts: Vec<AccountMeta>,
+) -> (DepositInterestToMultipleUsers, Instruction) {
+    let accounts = DepositInterestToMultipleUsers {
         interest_distributor: *interest_distributor,
         interest_vault: *interest_vault,
+        rate_history: *rate_history,
+        rate_feed: rate_feed.copied(),
+        fee_destination: fee_destination.copied(),
         token_program: anchor_spl::token::ID,
     };
 
-    let data = instruction::DepositInterest {}.data();
+    let data = instruction::DepositInterestMultiple {}.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: [accounts.to_account_metas(None), remaining_accounts].concat(),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn add_allowlist_entry(
+    verifier: &Pubkey,
+    state: &Pubkey,
+    allowlist_entry: &Pubkey,
+    user: &Pubkey,
+    expires_at: i64,
+) -> (AddAllowlistEntry, Instruction) {
+    let accounts = AddAllowlistEntry {
+        verifier: *verifier,
+        state: *state,
+        allowlist_entry: *allowlist_entry,
+        system_program: system_program::ID,
+    };
+
+    let data = instruction::AddAllowlistEntry {
+        user: *user,
+        expires_at,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn remove_allowlist_entry(
+    verifier: &Pubkey,
+    state: &Pubkey,
+    allowlist_entry: &Pubkey,
+) -> (RemoveAllowlistEntry, Instruction) {
+    let accounts = RemoveAllowlistEntry {
+        verifier: *verifier,
+        state: *state,
+        allowlist_entry: *allowlist_entry,
+    };
+
+    let data = instruction::RemoveAllowlistEntry {}.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn freeze_savings_manager(
+    authority: &Pubkey,
+    state: &Pubkey,
+    interest_distributor: &Pubkey,
+    savings_manager: &Pubkey,
+    reason_code: u16,
+) -> (UpdateSavingsManagerFreeze, Instruction) {
+    let accounts = UpdateSavingsManagerFreeze {
+        authority: *authority,
+        state: *state,
+        interest_distributor: *interest_distributor,
+        savings_manager: *savings_manager,
+    };
+
+    let data = instruction::FreezeSavingsManager { reason_code }.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn unfreeze_savings_manager(
+    authority: &Pubkey,
+    state: &Pubkey,
+    interest_distributor: &Pubkey,
+    savings_manager: &Pubkey,
+    reason_code: u16,
+) -> (UpdateSavingsManagerFreeze, Instruction) {
+    let accounts = UpdateSavingsManagerFreeze {
+        authority: *authority,
+        state: *state,
+        interest_distributor: *interest_distributor,
+        savings_manager: *savings_manager,
+    };
+
+    let data = instruction::UnfreezeSavingsManager { reason_code }.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn queue_config_change(
+    authority: &Pubkey,
+    state: &Pubkey,
+    interest_distributor: Option<&Pubkey>,
+    pending_change: &Pubkey,
+    change: ConfigChange,
+) -> (QueueConfigChange, Instruction) {
+    let accounts = QueueConfigChange {
+        authority: *authority,
+        state: *state,
+        interest_distributor: interest_distributor.copied(),
+        pending_change: *pending_change,
+        system_program: system_program::ID,
+    };
+
+    let data = instruction::QueueConfigChange { change }.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn execute_config_change(
+    authority: &Pubkey,
+    state: &Pubkey,
+    interest_distributor: Option<&Pubkey>,
+    rate_history: Option<&Pubkey>,
+    yield_adapter: Option<&Pubkey>,
+    pending_change: &Pubkey,
+) -> (ExecuteConfigChange, Instruction) {
+    let accounts = ExecuteConfigChange {
+        authority: *authority,
+        state: *state,
+        interest_distributor: interest_distributor.copied(),
+        rate_history: rate_history.copied(),
+        yield_adapter: yield_adapter.copied(),
+        pending_change: *pending_change,
+    };
+
+    let data = instruction::ExecuteConfigChange {}.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn cancel_config_change(
+    authority: &Pubkey,
+    state: &Pubkey,
+    pending_change: &Pubkey,
+) -> (CancelConfigChange, Instruction) {
+    let accounts = CancelConfigChange {
+        authority: *authority,
+        state: *state,
+        pending_change: *pending_change,
+    };
+
+    let data = instruction::CancelConfigChange {}.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn create_rate_history(
+    payer: &Pubkey,
+    interest_distributor: &Pubkey,
+    rate_history: &Pubkey,
+) -> (CreateRateHistory, Instruction) {
+    let accounts = CreateRateHistory {
+        payer: *payer,
+        interest_distributor: *interest_distributor,
+        rate_history: *rate_history,
+        system_program: system_program::ID,
+    };
+
+    let data = instruction::CreateRateHistory {}.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn create_registry_page(
+    payer: &Pubkey,
+    interest_distributor: &Pubkey,
+    registry_page: &Pubkey,
+) -> (CreateRegistryPage, Instruction) {
+    let accounts = CreateRegistryPage {
+        payer: *payer,
+        interest_distributor: *interest_distributor,
+        registry_page: *registry_page,
+        system_program: system_program::ID,
+    };
+
+    let data = instruction::CreateRegistryPage {}.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn register_savings_manager(
+    savings_manager: &Pubkey,
+    registry_page: &Pubkey,
+) -> (RegisterSavingsManager, Instruction) {
+    let accounts = RegisterSavingsManager {
+        savings_manager: *savings_manager,
+        registry_page: *registry_page,
+    };
+
+    let data = instruction::RegisterSavingsManager {}.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn deposit_for(
+    depositor: &Pubkey,
+    depositor_token_account: &Pubkey,
+    savings_manager: &Pubkey,
+    interest_distributor: &Pubkey,
+    allowlist_entry: Option<&Pubkey>,
+    savings_vault: &Pubkey,
+    amount: u64,
+) -> (DepositFor, Instruction) {
+    let accounts = DepositFor {
+        depositor: *depositor,
+        savings_manager: *savings_manager,
+        interest_distributor: *interest_distributor,
+        allowlist_entry: allowlist_entry.copied(),
+        depositor_token_account: *depositor_token_account,
+        savings_vault: *savings_vault,
+        token_program: anchor_spl::token::ID,
+    };
+
+    let data = instruction::DepositFor { amount }.data();
+
+    let instruction = Instruction {
+        program_id: savings_program::ID,
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (accounts, instruction)
+}
+
+pub fn user_set_third_party_deposits(
+    user: &Pubkey,
+    savings_manager: &Pubkey,
+    allowed: bool,
+) -> (UserSetThirdPartyDeposits, Instruction) {
+    let accounts = UserSetThirdPartyDeposits {
+        user: *user,
+        savings_manager: *savings_manager,
+    };
+
+    let data = instruction::UserSetThirdPartyDeposits { allowed }.data();
 
     let instruction = Instruction {
         program_id: savings_program::ID,
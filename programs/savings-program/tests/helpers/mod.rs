@@ -4,7 +4,9 @@ pub mod pda;
 pub mod utils;
 
 use solana_program_test::{processor, BanksClientError, ProgramTest};
+use solana_sdk::instruction::InstructionError;
 use solana_sdk::program_error::ProgramError;
+use solana_sdk::transaction::TransactionError;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -15,6 +17,7 @@ pub fn program_test() -> ProgramTest {
         processor!(savings_program::entry),
     );
     program_test.prefer_bpf(false);
+    program_test.add_program("mock_yield", mock_yield::ID, processor!(mock_yield::entry));
     program_test
 }
 
@@ -30,4 +33,20 @@ pub enum Error {
     AccountNotFound,
     #[error(transparent)]
     Signature(#[from] solana_sdk::signature::SignerError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// The custom program error code `result` failed with, if it failed with one.
+pub fn program_error_code<T>(result: &Result<T>) -> Option<u32> {
+    match result {
+        Err(Error::Client(BanksClientError::TransactionError(
+            TransactionError::InstructionError(_, InstructionError::Custom(code)),
+        )))
+        | Err(Error::Client(BanksClientError::SimulationError {
+            err: TransactionError::InstructionError(_, InstructionError::Custom(code)),
+            ..
+        })) => Some(*code),
+        _ => None,
+    }
 }
@@ -1,26 +1,133 @@
+use savings_program::{InterestDistributor, SavingsManager};
 use solana_sdk::pubkey::Pubkey;
 
 pub const SAVINGS_MANAGER_SEED_PREFIX: &[u8] = savings_program::SAVINGS_MANAGER_SEED_PREFIX;
 pub const INTEREST_DISTRIBUTOR_SEED_PREFIX: &[u8] =
     savings_program::INTEREST_DISTRIBUTOR_SEED_PREFIX;
+pub const SAVINGS_PLAN_SEED_PREFIX: &[u8] = savings_program::SAVINGS_PLAN_SEED_PREFIX;
+pub const NATIVE_WITHDRAWAL_SEED_PREFIX: &[u8] = savings_program::NATIVE_WITHDRAWAL_SEED_PREFIX;
+pub const ALLOWLIST_ENTRY_SEED_PREFIX: &[u8] = savings_program::ALLOWLIST_ENTRY_SEED_PREFIX;
+pub const CONFIG_CHANGE_SEED_PREFIX: &[u8] = savings_program::CONFIG_CHANGE_SEED_PREFIX;
+pub const RATE_HISTORY_SEED_PREFIX: &[u8] = savings_program::RATE_HISTORY_SEED_PREFIX;
+pub const RECEIPT_MINT_SEED_PREFIX: &[u8] = savings_program::RECEIPT_MINT_SEED_PREFIX;
+pub const RECEIPT_POOL_SEED_PREFIX: &[u8] = savings_program::RECEIPT_POOL_SEED_PREFIX;
+pub const YIELD_ADAPTER_SEED_PREFIX: &[u8] = savings_program::YIELD_ADAPTER_SEED_PREFIX;
+pub const APPROVED_MINT_SEED_PREFIX: &[u8] = savings_program::APPROVED_MINT_SEED_PREFIX;
+pub const CAMPAIGN_SEED_PREFIX: &[u8] = savings_program::CAMPAIGN_SEED_PREFIX;
+pub const REGISTRY_PAGE_SEED_PREFIX: &[u8] = savings_program::REGISTRY_PAGE_SEED_PREFIX;
 
-pub fn derive_savings_manager_pda(user: &Pubkey, distributor: &Pubkey) -> (Pubkey, u8) {
+pub fn derive_savings_manager_pda(user: &Pubkey, distributor: &Pubkey, index: u16) -> (Pubkey, u8) {
     Pubkey::find_program_address(
         &[
             SAVINGS_MANAGER_SEED_PREFIX,
             user.as_ref(),
             distributor.as_ref(),
+            &SavingsManager::index_seed(index),
         ],
         &savings_program::ID,
     )
 }
 
-pub fn derive_interest_distributor_pda(state: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+pub fn derive_interest_distributor_pda(
+    state: &Pubkey,
+    mint: &Pubkey,
+    product_id: u16,
+) -> (Pubkey, u8) {
     Pubkey::find_program_address(
         &[
             INTEREST_DISTRIBUTOR_SEED_PREFIX,
             state.as_ref(),
             mint.as_ref(),
+            &InterestDistributor::product_seed(product_id),
+        ],
+        &savings_program::ID,
+    )
+}
+
+pub fn derive_savings_plan_pda(savings_manager: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[SAVINGS_PLAN_SEED_PREFIX, savings_manager.as_ref()],
+        &savings_program::ID,
+    )
+}
+
+pub fn derive_native_withdrawal_pda(savings_manager: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[NATIVE_WITHDRAWAL_SEED_PREFIX, savings_manager.as_ref()],
+        &savings_program::ID,
+    )
+}
+
+pub fn derive_allowlist_entry_pda(state: &Pubkey, user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[ALLOWLIST_ENTRY_SEED_PREFIX, state.as_ref(), user.as_ref()],
+        &savings_program::ID,
+    )
+}
+
+pub fn derive_config_change_pda(state: &Pubkey, id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            CONFIG_CHANGE_SEED_PREFIX,
+            state.as_ref(),
+            id.to_le_bytes().as_ref(),
+        ],
+        &savings_program::ID,
+    )
+}
+
+pub fn derive_rate_history_pda(distributor: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[RATE_HISTORY_SEED_PREFIX, distributor.as_ref()],
+        &savings_program::ID,
+    )
+}
+
+pub fn derive_receipt_mint_pda(distributor: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[RECEIPT_MINT_SEED_PREFIX, distributor.as_ref()],
+        &savings_program::ID,
+    )
+}
+
+pub fn derive_receipt_pool_pda(distributor: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[RECEIPT_POOL_SEED_PREFIX, distributor.as_ref()],
+        &savings_program::ID,
+    )
+}
+
+pub fn derive_yield_adapter_pda(distributor: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[YIELD_ADAPTER_SEED_PREFIX, distributor.as_ref()],
+        &savings_program::ID,
+    )
+}
+
+pub fn derive_approved_mint_pda(state: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[APPROVED_MINT_SEED_PREFIX, state.as_ref(), mint.as_ref()],
+        &savings_program::ID,
+    )
+}
+
+pub fn derive_campaign_pda(distributor: &Pubkey, campaign_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            CAMPAIGN_SEED_PREFIX,
+            distributor.as_ref(),
+            &campaign_id.to_le_bytes(),
+        ],
+        &savings_program::ID,
+    )
+}
+
+pub fn derive_registry_page_pda(distributor: &Pubkey, page: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            REGISTRY_PAGE_SEED_PREFIX,
+            distributor.as_ref(),
+            &page.to_le_bytes(),
         ],
         &savings_program::ID,
     )
@@ -33,3 +140,11 @@ pub fn derive_savings_vault_ata(mint: &Pubkey, savings_manager: &Pubkey) -> Pubk
 pub fn derive_interest_vault_ata(mint: &Pubkey, distributor: &Pubkey) -> Pubkey {
     anchor_spl::associated_token::get_associated_token_address(distributor, mint)
 }
+
+pub fn derive_campaign_vault_ata(mint: &Pubkey, campaign: &Pubkey) -> Pubkey {
+    anchor_spl::associated_token::get_associated_token_address(campaign, mint)
+}
+
+pub fn derive_yield_vault_ata(mint: &Pubkey, yield_adapter: &Pubkey) -> Pubkey {
+    anchor_spl::associated_token::get_associated_token_address(yield_adapter, mint)
+}
@@ -1,7 +1,9 @@
 use super::Result;
+use anchor_lang::solana_program::program::MAX_RETURN_DATA;
+use anchor_lang::AnchorDeserialize;
 use anchor_spl::token::spl_token;
 use anchor_spl::token::Mint;
-use solana_program_test::ProgramTestContext;
+use solana_program_test::{BanksClient, BanksClientError, ProgramTestContext};
 use solana_sdk::instruction::Instruction;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::{Keypair, Signer};
@@ -13,6 +15,7 @@ pub fn create_token_mint(
     ctx: &mut ProgramTestContext,
     mint: &Keypair,
     authority: &Pubkey,
+    freeze_authority: Option<&Pubkey>,
     decimals: u8,
 ) -> Result<Vec<Instruction>> {
     let create_account = system_instruction::create_account(
@@ -26,7 +29,7 @@ pub fn create_token_mint(
         &spl_token::id(),
         &mint.pubkey(),
         authority,
-        None,
+        freeze_authority,
         decimals,
     )?;
 
@@ -83,3 +86,24 @@ pub async fn send_and_confirm_tx(
 
     Ok(())
 }
+
+/// Simulates `tx` without committing it and deserializes the data its last instruction set
+/// with `set_return_data`.
+pub async fn simulate_tx<T: AnchorDeserialize>(
+    banks_client: &mut BanksClient,
+    tx: Transaction,
+) -> Result<T> {
+    let simulation = banks_client.simulate_transaction(tx).await?;
+    if let Some(Err(err)) = simulation.result {
+        return Err(BanksClientError::TransactionError(err).into());
+    }
+
+    let mut data = simulation
+        .simulation_details
+        .and_then(|details| details.return_data)
+        .map(|return_data| return_data.data)
+        .unwrap_or_default();
+    // The runtime strips trailing zero bytes from return data, so pad them back before decoding.
+    data.resize(MAX_RETURN_DATA, 0);
+    Ok(T::deserialize(&mut data.as_ref())?)
+}
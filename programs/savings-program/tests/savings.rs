@@ -1,14 +1,130 @@
 #![allow(dead_code)]
 mod helpers;
 
+use anchor_lang::error::ErrorCode;
+use anchor_lang::Discriminator;
+use anchor_spl::token::spl_token::error::TokenError;
 use anchor_spl::token::TokenAccount;
-use helpers::{context, pda, program_test, utils};
+use helpers::{context, instructions, pda, program_test, utils};
 
-use savings_program::{InterestDistributor, SavingsManager, State};
+use savings_program::{
+    Campaign, ConfigChange, InterestDistributor, PendingConfigChange, RateFeed, RateHistory,
+    RegistryPage, SavingsError, SavingsManager, State, YieldAdapter, DEFAULT_CONFIG_TIMELOCK_DELAY,
+    DEFAULT_INTEREST_RATE_BPS, INTEREST_DISTRIBUTOR_SEED_PREFIX, SECONDS_IN_MONTHS,
+};
 use solana_program_test::tokio;
 use solana_sdk::clock::Clock;
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::{Keypair, Signer};
-use solana_sdk::system_instruction;
+use solana_sdk::system_instruction::{self, SystemError};
+
+/// A state with a funded interest vault for a fresh mint, and a user with a savings vault.
+struct Fixture {
+    ctx: context::TestContext,
+    mint: Keypair,
+    mint_authority: Keypair,
+    user: Keypair,
+    user_ata: Pubkey,
+}
+
+/// Asserts that `result` failed with the program error `error`.
+#[track_caller]
+fn assert_error<T: std::fmt::Debug>(result: helpers::Result<T>, error: impl Into<u32>) {
+    assert_eq!(
+        helpers::program_error_code(&result),
+        Some(error.into()),
+        "unexpected result: {result:?}"
+    );
+}
+
+/// Sets up a [`Fixture`] whose interest vault holds `interest_funds` tokens, and whose user has
+/// deposited `user_savings` of their 1000 tokens.
+async fn setup_fixture(interest_funds: u64, user_savings: u64) -> Fixture {
+    let mut ctx = program_test().start_with_context().await;
+    let mint = Keypair::new();
+    let mint_authority = Keypair::new();
+    let admin = Keypair::new();
+    let state = Keypair::new();
+
+    let create_mint =
+        utils::create_token_mint(&mut ctx, &mint, &mint_authority.pubkey(), None, 0).unwrap();
+    utils::send_and_confirm_tx(&mut ctx, create_mint, Some(vec![&mint]))
+        .await
+        .unwrap();
+    let transfer_ix =
+        system_instruction::transfer(&ctx.payer.pubkey(), &admin.pubkey(), 1_000_000_000);
+    utils::send_and_confirm_tx(&mut ctx, vec![transfer_ix], None)
+        .await
+        .unwrap();
+
+    let ctx = context::TestContext::initialize_state(ctx, &admin, &state)
+        .await
+        .unwrap();
+    ctx.clear_timelock_delay().await.unwrap();
+    ctx.create_interest_vault(&mint.pubkey()).await.unwrap();
+    if interest_funds > 0 {
+        let (funder, funder_ata) = ctx
+            .create_funded_user(&mint.pubkey(), &mint_authority, interest_funds)
+            .await
+            .unwrap();
+        ctx.deposit_to_interest_vault(&mint.pubkey(), &funder, &funder_ata, interest_funds)
+            .await
+            .unwrap();
+    }
+
+    let (user, user_ata) = ctx
+        .create_funded_user(&mint.pubkey(), &mint_authority, 1000)
+        .await
+        .unwrap();
+    ctx.user_create_vault(&user, &mint.pubkey(), None)
+        .await
+        .unwrap();
+    if user_savings > 0 {
+        ctx.user_deposit(&user, &mint.pubkey(), &user_ata, user_savings)
+            .await
+            .unwrap();
+    }
+
+    Fixture {
+        ctx,
+        mint,
+        mint_authority,
+        user,
+        user_ata,
+    }
+}
+
+impl Fixture {
+    fn interest_vault(&self) -> Pubkey {
+        let distributor =
+            pda::derive_interest_distributor_pda(&self.ctx.state, &self.mint.pubkey(), 0).0;
+        pda::derive_interest_vault_ata(&self.mint.pubkey(), &distributor)
+    }
+
+    fn savings_manager(&self) -> Pubkey {
+        let distributor =
+            pda::derive_interest_distributor_pda(&self.ctx.state, &self.mint.pubkey(), 0).0;
+        pda::derive_savings_manager_pda(&self.user.pubkey(), &distributor, 0).0
+    }
+
+    async fn savings_balance(&self) -> u64 {
+        let vault = pda::derive_savings_vault_ata(&self.mint.pubkey(), &self.savings_manager());
+        self.ctx
+            .get_deserialized_account::<TokenAccount>(&vault)
+            .await
+            .unwrap()
+            .amount
+    }
+
+    async fn token_balance(&self, token_account: &Pubkey) -> u64 {
+        self.ctx
+            .get_deserialized_account::<TokenAccount>(token_account)
+            .await
+            .unwrap()
+            .amount
+    }
+}
 
 #[tokio::test]
 async fn test_all_actions() {
@@ -18,7 +134,7 @@ async fn test_all_actions() {
 
     // Initialize a test token mint.
     let create_mint =
-        utils::create_token_mint(&mut ctx, &mint, &mint_authority.pubkey(), 0).unwrap();
+        utils::create_token_mint(&mut ctx, &mint, &mint_authority.pubkey(), None, 0).unwrap();
     utils::send_and_confirm_tx(&mut ctx, create_mint, Some(vec![&mint]))
         .await
         .unwrap();
@@ -60,11 +176,16 @@ async fn test_all_actions() {
         .await
         .unwrap();
     assert_eq!(state_account.authority, admin.pubkey());
+    assert_eq!(
+        state_account.config_timelock_delay,
+        DEFAULT_CONFIG_TIMELOCK_DELAY
+    );
+    ctx.clear_timelock_delay().await.unwrap();
 
     // Register an interest-vault for a particular mint, allowing users to save tokens of that mint.
     ctx.create_interest_vault(&mint.pubkey()).await.unwrap();
     let (distributor, d_bump) =
-        pda::derive_interest_distributor_pda(&state.pubkey(), &mint.pubkey());
+        pda::derive_interest_distributor_pda(&state.pubkey(), &mint.pubkey(), 0);
     let interest_vault = pda::derive_interest_vault_ata(&mint.pubkey(), &distributor);
 
     let distributor_account = ctx
@@ -139,8 +260,11 @@ async fn test_all_actions() {
         .await
         .unwrap()
         .unix_timestamp;
-    ctx.user_create_vault(&user, &mint.pubkey()).await.unwrap();
-    let (savings_manager, sm_bump) = pda::derive_savings_manager_pda(&user.pubkey(), &distributor);
+    ctx.user_create_vault(&user, &mint.pubkey(), None)
+        .await
+        .unwrap();
+    let (savings_manager, sm_bump) =
+        pda::derive_savings_manager_pda(&user.pubkey(), &distributor, 0);
     let savings_vault = pda::derive_savings_vault_ata(&mint.pubkey(), &savings_manager);
 
     let sm_account = ctx
@@ -183,7 +307,7 @@ async fn test_all_actions() {
 
     // Attempting to deposit interest now should error:
     let result = ctx.deposit_interest(&user.pubkey(), &mint.pubkey()).await;
-    assert!(result.is_err());
+    assert_error(result, SavingsError::CrankTurnedTooSoon);
 
     // Fast-forward time to a month after. A month is (30 * 24 * 60 * 60) seconds so we add that
     // to create our artificial clock.
@@ -219,3 +343,2012 @@ async fn test_all_actions() {
         .unwrap();
     assert!(savings_manager.last_interest_deposit_ts == forwarded_time); */
 }
+
+#[tokio::test]
+async fn test_migrate_legacy_accounts() {
+    use anchor_lang::Discriminator;
+    use savings_program::ACCOUNT_VERSION;
+    use solana_sdk::account::Account;
+    use solana_sdk::rent::Rent;
+
+    fn legacy_account(data: Vec<u8>) -> Account {
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: savings_program::ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    let authority = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let user = Pubkey::new_unique();
+    let state = Pubkey::new_unique();
+    let (distributor, d_bump) = pda::derive_interest_distributor_pda(&state, &mint, 0);
+    let (savings_manager, sm_bump) = pda::derive_savings_manager_pda(&user, &distributor, 0);
+
+    // Lay out the accounts exactly as they were written before versioning was introduced.
+    let legacy_state = [State::DISCRIMINATOR.as_ref(), authority.as_ref()].concat();
+    let legacy_distributor = [
+        InterestDistributor::DISCRIMINATOR.as_ref(),
+        state.as_ref(),
+        mint.as_ref(),
+        &[d_bump],
+    ]
+    .concat();
+    let legacy_manager = [
+        SavingsManager::DISCRIMINATOR.as_ref(),
+        user.as_ref(),
+        mint.as_ref(),
+        distributor.as_ref(),
+        &1_700_000_000i64.to_le_bytes(),
+        &[sm_bump],
+    ]
+    .concat();
+
+    let mut program_test = program_test();
+    program_test.add_account(state, legacy_account(legacy_state));
+    program_test.add_account(distributor, legacy_account(legacy_distributor));
+    program_test.add_account(savings_manager, legacy_account(legacy_manager));
+    let ctx = program_test.start_with_context().await;
+    let ctx = context::TestContext {
+        ctx: std::cell::RefCell::new(ctx),
+        admin: Keypair::new(),
+        state,
+    };
+    let payer = ctx.ctx.borrow().payer.pubkey();
+
+    // Legacy accounts are too short to be read under the current layout.
+    assert!(ctx.get_deserialized_account::<State>(&state).await.is_err());
+
+    let (_, migrate_state) = instructions::migrate_state(&payer, &state);
+    let (_, migrate_distributor) = instructions::migrate_interest_distributor(&payer, &distributor);
+    let (_, migrate_manager) = instructions::migrate_savings_manager(&payer, &savings_manager);
+    ctx.send_and_confirm_tx(
+        vec![migrate_state, migrate_distributor, migrate_manager],
+        None,
+    )
+    .await
+    .unwrap();
+
+    let state_account = ctx.get_deserialized_account::<State>(&state).await.unwrap();
+    assert_eq!(state_account.authority, authority);
+    assert_eq!(state_account.version, ACCOUNT_VERSION);
+    assert_eq!(
+        state_account.config_timelock_delay,
+        DEFAULT_CONFIG_TIMELOCK_DELAY
+    );
+    assert_eq!(
+        ctx.get_account(&state).await.unwrap().data.len(),
+        State::SPACE
+    );
+
+    let distributor_account = ctx
+        .get_deserialized_account::<InterestDistributor>(&distributor)
+        .await
+        .unwrap();
+    assert_eq!(distributor_account.state, state);
+    assert_eq!(distributor_account.mint, mint);
+    assert_eq!(distributor_account.bump, d_bump);
+    assert_eq!(distributor_account.version, ACCOUNT_VERSION);
+
+    let sm_account = ctx
+        .get_deserialized_account::<SavingsManager>(&savings_manager)
+        .await
+        .unwrap();
+    assert_eq!(sm_account.user, user);
+    assert_eq!(sm_account.distributor, distributor);
+    assert_eq!(sm_account.last_interest_deposit_ts, 1_700_000_000);
+    assert_eq!(sm_account.bump, sm_bump);
+    assert_eq!(sm_account.version, ACCOUNT_VERSION);
+
+    // Distributors that predate rate histories get one paying the default rate all along.
+    let rate_history = pda::derive_rate_history_pda(&distributor).0;
+    let (_, create_rate_history) =
+        instructions::create_rate_history(&payer, &distributor, &rate_history);
+    ctx.send_and_confirm_tx(vec![create_rate_history], None)
+        .await
+        .unwrap();
+    let rate_history_account = ctx
+        .get_deserialized_account::<RateHistory>(&rate_history)
+        .await
+        .unwrap();
+    assert_eq!(rate_history_account.distributor, distributor);
+    assert_eq!(rate_history_account.len, 1);
+    assert_eq!(
+        rate_history_account.current_rate(),
+        DEFAULT_INTEREST_RATE_BPS
+    );
+    assert_eq!(rate_history_account.entries[0].effective_ts, 0);
+
+    // Migrating an account that is already current is rejected.
+    let (_, migrate_state) = instructions::migrate_state(&payer, &state);
+    let result = ctx.send_and_confirm_tx(vec![migrate_state], None).await;
+    assert_error(result, SavingsError::AccountAlreadyMigrated);
+}
+
+#[tokio::test]
+async fn test_delegate_withdrawals() {
+    const DAY: i64 = 24 * 60 * 60;
+    let f = setup_fixture(0, 900).await;
+    let mint = f.mint.pubkey();
+    let (delegate, delegate_ata) = f
+        .ctx
+        .create_funded_user(&mint, &f.mint_authority, 0)
+        .await
+        .unwrap();
+
+    // A stranger cannot withdraw before the user appoints a delegate.
+    let result = f
+        .ctx
+        .delegate_withdraw(&delegate, &f.user.pubkey(), &mint, &delegate_ata, 10)
+        .await;
+    assert_error(result, SavingsError::UnauthorizedWithdrawal);
+
+    f.ctx
+        .user_set_delegate(&f.user, &mint, &delegate.pubkey(), 100, DAY)
+        .await
+        .unwrap();
+    let sm_account = f
+        .ctx
+        .get_deserialized_account::<SavingsManager>(&f.savings_manager())
+        .await
+        .unwrap();
+    assert_eq!(sm_account.delegate, Some(delegate.pubkey()));
+    assert_eq!(sm_account.delegate_allowance, 100);
+
+    // The delegate can withdraw up to its allowance within a period, but not beyond it.
+    f.ctx
+        .delegate_withdraw(&delegate, &f.user.pubkey(), &mint, &delegate_ata, 60)
+        .await
+        .unwrap();
+    let result = f
+        .ctx
+        .delegate_withdraw(&delegate, &f.user.pubkey(), &mint, &delegate_ata, 50)
+        .await;
+    assert_error(result, SavingsError::DelegateAllowanceExceeded);
+    f.ctx
+        .delegate_withdraw(&delegate, &f.user.pubkey(), &mint, &delegate_ata, 40)
+        .await
+        .unwrap();
+    assert_eq!(f.token_balance(&delegate_ata).await, 100);
+
+    // The owner is not bound by the delegate's allowance.
+    f.ctx
+        .user_withdraw(&f.user, &mint, &f.user_ata, 200)
+        .await
+        .unwrap();
+    assert_eq!(f.savings_balance().await, 600);
+
+    // The allowance is restored once the period rolls over.
+    f.ctx.advance_clock(DAY).await.unwrap();
+    f.ctx
+        .delegate_withdraw(&delegate, &f.user.pubkey(), &mint, &delegate_ata, 100)
+        .await
+        .unwrap();
+    assert_eq!(f.savings_balance().await, 500);
+
+    // Once revoked, the delegate can no longer withdraw.
+    f.ctx.user_revoke_delegate(&f.user, &mint).await.unwrap();
+    f.ctx.advance_clock(DAY).await.unwrap();
+    let result = f
+        .ctx
+        .delegate_withdraw(&delegate, &f.user.pubkey(), &mint, &delegate_ata, 10)
+        .await;
+    assert_error(result, SavingsError::UnauthorizedWithdrawal);
+}
+
+#[tokio::test]
+async fn test_withdrawal_notice_period() {
+    const NOTICE: i64 = 7 * 24 * 60 * 60;
+    let f = setup_fixture(100, 500).await;
+    let mint = f.mint.pubkey();
+    f.ctx
+        .set_withdrawal_notice_period(&mint, NOTICE)
+        .await
+        .unwrap();
+
+    // Immediate withdrawals are no longer allowed.
+    let result = f.ctx.user_withdraw(&f.user, &mint, &f.user_ata, 100).await;
+    assert_error(result, SavingsError::WithdrawalNoticeRequired);
+
+    // Nothing can be executed before a request is made, and requests can't exceed the balance.
+    let result = f.ctx.execute_withdrawal(&f.user, &mint, &f.user_ata).await;
+    assert_error(result, SavingsError::NoPendingWithdrawal);
+    let result = f.ctx.request_withdrawal(&f.user, &mint, 501).await;
+    assert_error(result, SavingsError::InadequateFunds);
+
+    let requested_at = f.ctx.current_time().await.unwrap();
+    f.ctx.request_withdrawal(&f.user, &mint, 200).await.unwrap();
+    let sm_account = f
+        .ctx
+        .get_deserialized_account::<SavingsManager>(&f.savings_manager())
+        .await
+        .unwrap();
+    assert_eq!(sm_account.pending_withdrawal_amount, 200);
+    assert_eq!(sm_account.pending_withdrawal_ts, requested_at);
+
+    // The request can't be executed until the notice period has passed.
+    f.ctx.advance_clock(NOTICE - 1).await.unwrap();
+    let result = f.ctx.execute_withdrawal(&f.user, &mint, &f.user_ata).await;
+    assert_error(result, SavingsError::NoticePeriodNotElapsed);
+
+    // Interest is only paid on the balance that hasn't been requested: 1% of 300.
+    f.ctx.advance_clock(SECONDS_IN_MONTHS).await.unwrap();
+    f.ctx
+        .deposit_interest(&f.user.pubkey(), &mint)
+        .await
+        .unwrap();
+    assert_eq!(f.savings_balance().await, 503);
+
+    f.ctx
+        .execute_withdrawal(&f.user, &mint, &f.user_ata)
+        .await
+        .unwrap();
+    assert_eq!(f.savings_balance().await, 303);
+    assert_eq!(f.token_balance(&f.user_ata).await, 700);
+
+    // The request is cleared once executed.
+    f.ctx.refresh_blockhash().await.unwrap();
+    let result = f.ctx.execute_withdrawal(&f.user, &mint, &f.user_ata).await;
+    assert_error(result, SavingsError::NoPendingWithdrawal);
+}
+
+#[tokio::test]
+async fn test_recurring_savings_plan() {
+    use savings_program::SavingsPlan;
+    const WEEK: i64 = 7 * 24 * 60 * 60;
+    let f = setup_fixture(0, 0).await;
+    let mint = f.mint.pubkey();
+    let user = f.user.pubkey();
+
+    // Approve enough for two weekly contributions of 100 tokens.
+    f.ctx
+        .user_create_savings_plan(&f.user, &mint, &f.user_ata, 200, 100, WEEK)
+        .await
+        .unwrap();
+    let plan = pda::derive_savings_plan_pda(&f.savings_manager()).0;
+    let plan_account = f
+        .ctx
+        .get_deserialized_account::<SavingsPlan>(&plan)
+        .await
+        .unwrap();
+    assert_eq!(plan_account.user, user);
+    assert_eq!(plan_account.source_token_account, f.user_ata);
+    assert_eq!(plan_account.amount, 100);
+    let first_due = plan_account.next_contribution_ts;
+
+    // Nothing can be pulled before the first interval has passed.
+    let result = f.ctx.execute_savings_plan(&user, &mint, &f.user_ata).await;
+    assert_error(result, SavingsError::CrankTurnedTooSoon);
+
+    f.ctx.advance_clock(WEEK).await.unwrap();
+    f.ctx
+        .execute_savings_plan(&user, &mint, &f.user_ata)
+        .await
+        .unwrap();
+    assert_eq!(f.savings_balance().await, 100);
+    assert_eq!(f.token_balance(&f.user_ata).await, 900);
+
+    // Only one contribution is pulled per interval.
+    f.ctx.refresh_blockhash().await.unwrap();
+    let result = f.ctx.execute_savings_plan(&user, &mint, &f.user_ata).await;
+    assert_error(result, SavingsError::CrankTurnedTooSoon);
+
+    // Missed intervals are skipped rather than pulled all at once.
+    f.ctx.advance_clock(3 * WEEK).await.unwrap();
+    f.ctx
+        .execute_savings_plan(&user, &mint, &f.user_ata)
+        .await
+        .unwrap();
+    assert_eq!(f.savings_balance().await, 200);
+    let plan_account = f
+        .ctx
+        .get_deserialized_account::<SavingsPlan>(&plan)
+        .await
+        .unwrap();
+    assert_eq!(plan_account.next_contribution_ts, first_due + 4 * WEEK);
+
+    // Once the approved amount is used up, contributions stop.
+    f.ctx.advance_clock(WEEK).await.unwrap();
+    let result = f.ctx.execute_savings_plan(&user, &mint, &f.user_ata).await;
+    assert_error(result, SavingsError::SavingsPlanNotApproved);
+
+    f.ctx.user_close_savings_plan(&f.user, &mint).await.unwrap();
+    assert!(f.ctx.get_account(&plan).await.is_err());
+}
+
+#[tokio::test]
+async fn test_savings_goals() {
+    const YEAR: i64 = 365 * 24 * 60 * 60;
+    let f = setup_fixture(100, 500).await;
+    let mint = f.mint.pubkey();
+
+    // A goal needs at least one target.
+    let result = f.ctx.user_set_goal(&f.user, &mint, 0, 0).await;
+    assert_error(result, SavingsError::InvalidSavingsGoal);
+
+    // Lock the vault until it holds 600 tokens.
+    f.ctx.user_set_goal(&f.user, &mint, 600, 0).await.unwrap();
+    let result = f.ctx.user_withdraw(&f.user, &mint, &f.user_ata, 10).await;
+    assert_error(result, SavingsError::SavingsGoalNotReached);
+    let result = f.ctx.user_set_goal(&f.user, &mint, 1, 0).await;
+    assert_error(result, SavingsError::SavingsGoalAlreadyActive);
+
+    // A deposit that crosses the target reaches the goal, and the vault stays unlocked after.
+    f.ctx
+        .user_deposit(&f.user, &mint, &f.user_ata, 100)
+        .await
+        .unwrap();
+    let sm_account = f
+        .ctx
+        .get_deserialized_account::<SavingsManager>(&f.savings_manager())
+        .await
+        .unwrap();
+    assert!(sm_account.goal_reached);
+    f.ctx
+        .user_withdraw(&f.user, &mint, &f.user_ata, 200)
+        .await
+        .unwrap();
+    assert_eq!(f.savings_balance().await, 400);
+
+    // A date-based goal unlocks the vault once the date passes.
+    let now = f.ctx.current_time().await.unwrap();
+    f.ctx
+        .user_set_goal(&f.user, &mint, 0, now + YEAR)
+        .await
+        .unwrap();
+    let result = f.ctx.user_withdraw(&f.user, &mint, &f.user_ata, 100).await;
+    assert_error(result, SavingsError::SavingsGoalNotReached);
+    f.ctx.advance_clock(YEAR).await.unwrap();
+    f.ctx
+        .user_withdraw(&f.user, &mint, &f.user_ata, 100)
+        .await
+        .unwrap();
+    assert_eq!(f.savings_balance().await, 300);
+
+    // Cancelling an unreached goal forfeits the penalty to the interest vault.
+    f.ctx
+        .set_goal_cancellation_penalty(&mint, 1_000)
+        .await
+        .unwrap();
+    f.ctx
+        .user_set_goal(&f.user, &mint, 10_000, 0)
+        .await
+        .unwrap();
+    f.ctx.user_cancel_goal(&f.user, &mint).await.unwrap();
+    assert_eq!(f.savings_balance().await, 270);
+    assert_eq!(f.token_balance(&f.interest_vault()).await, 130);
+    f.ctx
+        .user_withdraw(&f.user, &mint, &f.user_ata, 270)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_referral_bonuses() {
+    let f = setup_fixture(100, 500).await;
+    let mint = f.mint.pubkey();
+    let referrer = f.user.pubkey();
+    let (referee, referee_ata) = f
+        .ctx
+        .create_funded_user(&mint, &f.mint_authority, 1000)
+        .await
+        .unwrap();
+
+    // Users can't refer themselves.
+    let result = f
+        .ctx
+        .user_create_vault(&referee, &mint, Some(&referee.pubkey()))
+        .await;
+    assert_error(result, SavingsError::SelfReferral);
+
+    f.ctx
+        .user_create_vault(&referee, &mint, Some(&referrer))
+        .await
+        .unwrap();
+    f.ctx
+        .user_deposit(&referee, &mint, &referee_ata, 1000)
+        .await
+        .unwrap();
+    f.ctx
+        .set_referral_program(&mint, 5_000, 8, 0)
+        .await
+        .unwrap();
+
+    let distributor = pda::derive_interest_distributor_pda(&f.ctx.state, &mint, 0).0;
+    let referee_manager = pda::derive_savings_manager_pda(&referee.pubkey(), &distributor, 0).0;
+    let referee_vault = pda::derive_savings_vault_ata(&mint, &referee_manager);
+
+    // The referrer earns half of the referee's interest, on top of it.
+    f.ctx.advance_clock(SECONDS_IN_MONTHS).await.unwrap();
+    f.ctx
+        .deposit_interest(&referee.pubkey(), &mint)
+        .await
+        .unwrap();
+    assert_eq!(f.token_balance(&referee_vault).await, 1010);
+    assert_eq!(f.savings_balance().await, 505);
+    assert_eq!(f.token_balance(&f.interest_vault()).await, 85);
+
+    // Bonuses stop at the per-referee cap, including when cranked in batches.
+    f.ctx.advance_clock(SECONDS_IN_MONTHS).await.unwrap();
+    f.ctx
+        .deposit_interest_multiple(&[referee.pubkey()], &mint)
+        .await
+        .unwrap();
+    assert_eq!(f.token_balance(&referee_vault).await, 1020);
+    assert_eq!(f.savings_balance().await, 508);
+    let sm_account = f
+        .ctx
+        .get_deserialized_account::<SavingsManager>(&referee_manager)
+        .await
+        .unwrap();
+    assert_eq!(sm_account.referrer, Some(referrer));
+    assert_eq!(sm_account.referral_bonus_paid, 8);
+
+    // Once the referral window has passed, no bonus is paid even without a cap.
+    f.ctx
+        .set_referral_program(&mint, 5_000, 0, SECONDS_IN_MONTHS)
+        .await
+        .unwrap();
+    f.ctx.advance_clock(SECONDS_IN_MONTHS).await.unwrap();
+    f.ctx
+        .deposit_interest(&referee.pubkey(), &mint)
+        .await
+        .unwrap();
+    assert_eq!(f.token_balance(&referee_vault).await, 1030);
+    assert_eq!(f.savings_balance().await, 508);
+}
+
+#[tokio::test]
+async fn test_referral_bonus_unavailable_referrer() {
+    let f = setup_fixture(100, 500).await;
+    let mint = f.mint.pubkey();
+    let distributor = pda::derive_interest_distributor_pda(&f.ctx.state, &mint, 0).0;
+
+    // One referee names a wallet that never opened a savings vault, the other names the user.
+    let mut referee_vaults = vec![];
+    let mut referees = vec![];
+    for referrer in [Keypair::new().pubkey(), f.user.pubkey()] {
+        let (referee, referee_ata) = f
+            .ctx
+            .create_funded_user(&mint, &f.mint_authority, 1000)
+            .await
+            .unwrap();
+        f.ctx
+            .user_create_vault(&referee, &mint, Some(&referrer))
+            .await
+            .unwrap();
+        f.ctx
+            .user_deposit(&referee, &mint, &referee_ata, 1000)
+            .await
+            .unwrap();
+        let manager = pda::derive_savings_manager_pda(&referee.pubkey(), &distributor, 0).0;
+        referee_vaults.push(pda::derive_savings_vault_ata(&mint, &manager));
+        referees.push(referee.pubkey());
+    }
+    let [orphan, referee] = referees[..] else {
+        unreachable!()
+    };
+    f.ctx
+        .set_referral_program(&mint, 5_000, 0, 0)
+        .await
+        .unwrap();
+    f.ctx
+        .freeze_savings_manager(&f.user.pubkey(), &mint, 1)
+        .await
+        .unwrap();
+
+    // Referees still earn their interest when the referrer's vault is missing or frozen, but no
+    // bonus is paid.
+    f.ctx.advance_clock(SECONDS_IN_MONTHS).await.unwrap();
+    f.ctx.deposit_interest(&orphan, &mint).await.unwrap();
+    f.ctx.deposit_interest(&referee, &mint).await.unwrap();
+    f.ctx.advance_clock(SECONDS_IN_MONTHS).await.unwrap();
+    f.ctx
+        .deposit_interest_multiple(&[orphan, referee], &mint)
+        .await
+        .unwrap();
+    assert_eq!(f.token_balance(&referee_vaults[0]).await, 1020);
+    assert_eq!(f.token_balance(&referee_vaults[1]).await, 1020);
+    assert_eq!(f.savings_balance().await, 500);
+    assert_eq!(f.token_balance(&f.interest_vault()).await, 60);
+
+    // Bonuses resume once the referrer is unfrozen.
+    f.ctx
+        .unfreeze_savings_manager(&f.user.pubkey(), &mint, 1)
+        .await
+        .unwrap();
+    f.ctx.advance_clock(SECONDS_IN_MONTHS).await.unwrap();
+    f.ctx.deposit_interest(&referee, &mint).await.unwrap();
+    assert_eq!(f.token_balance(&referee_vaults[1]).await, 1030);
+    assert_eq!(f.savings_balance().await, 505);
+
+    // Once the referrer moves their position away, their old vault is closed and bonuses stop.
+    let new_owner = Keypair::new();
+    f.ctx
+        .user_transfer_position(&f.user, &new_owner, &mint)
+        .await
+        .unwrap();
+    f.ctx.advance_clock(SECONDS_IN_MONTHS).await.unwrap();
+    f.ctx.deposit_interest(&referee, &mint).await.unwrap();
+    f.ctx
+        .deposit_interest_multiple(&[orphan], &mint)
+        .await
+        .unwrap();
+    assert_eq!(f.token_balance(&referee_vaults[0]).await, 1040);
+    assert_eq!(f.token_balance(&referee_vaults[1]).await, 1040);
+    let new_manager = pda::derive_savings_manager_pda(&new_owner.pubkey(), &distributor, 0).0;
+    let new_vault = pda::derive_savings_vault_ata(&mint, &new_manager);
+    assert_eq!(f.token_balance(&new_vault).await, 505);
+    assert_eq!(f.token_balance(&f.interest_vault()).await, 15);
+}
+
+#[tokio::test]
+async fn test_native_sol_savings() {
+    use anchor_spl::token::spl_token::native_mint;
+    const SOL: u64 = 1_000_000_000;
+
+    let mut ctx = program_test().start_with_context().await;
+    let admin = Keypair::new();
+    let state = Keypair::new();
+    let user = Keypair::new();
+    let transfer_ixs = vec![
+        system_instruction::transfer(&ctx.payer.pubkey(), &admin.pubkey(), SOL),
+        system_instruction::transfer(&ctx.payer.pubkey(), &user.pubkey(), 10 * SOL),
+    ];
+    utils::send_and_confirm_tx(&mut ctx, transfer_ixs, None)
+        .await
+        .unwrap();
+
+    let ctx = context::TestContext::initialize_state(ctx, &admin, &state)
+        .await
+        .unwrap();
+    ctx.clear_timelock_delay().await.unwrap();
+    ctx.create_interest_vault(&native_mint::ID).await.unwrap();
+    ctx.user_create_vault(&user, &native_mint::ID, None)
+        .await
+        .unwrap();
+
+    let distributor = pda::derive_interest_distributor_pda(&state.pubkey(), &native_mint::ID, 0).0;
+    let manager = pda::derive_savings_manager_pda(&user.pubkey(), &distributor, 0).0;
+    let savings_vault = pda::derive_savings_vault_ata(&native_mint::ID, &manager);
+
+    // Lamports are wrapped straight into the savings vault.
+    ctx.user_deposit_native(&user, 4 * SOL).await.unwrap();
+    let vault_account = ctx
+        .get_deserialized_account::<TokenAccount>(&savings_vault)
+        .await
+        .unwrap();
+    assert_eq!(vault_account.amount, 4 * SOL);
+
+    // Withdrawals are unwrapped back into the user's lamports, and the temporary account is closed.
+    let lamports_before = ctx.get_account(&user.pubkey()).await.unwrap().lamports;
+    ctx.user_withdraw_native(&user, SOL).await.unwrap();
+    let lamports_after = ctx.get_account(&user.pubkey()).await.unwrap().lamports;
+    assert_eq!(lamports_after - lamports_before, SOL);
+    let vault_account = ctx
+        .get_deserialized_account::<TokenAccount>(&savings_vault)
+        .await
+        .unwrap();
+    assert_eq!(vault_account.amount, 3 * SOL);
+    let unwrap_account = pda::derive_native_withdrawal_pda(&manager).0;
+    assert!(ctx.get_account(&unwrap_account).await.is_err());
+
+    // A second withdrawal can reuse the temporary account's address.
+    ctx.refresh_blockhash().await.unwrap();
+    ctx.user_withdraw_native(&user, SOL).await.unwrap();
+    let vault_account = ctx
+        .get_deserialized_account::<TokenAccount>(&savings_vault)
+        .await
+        .unwrap();
+    assert_eq!(vault_account.amount, 2 * SOL);
+
+    // Native instructions are rejected for vaults of other mints.
+    let f = setup_fixture(0, 100).await;
+    let (_, instruction) = instructions::user_deposit_native(
+        &f.user.pubkey(),
+        &f.savings_manager(),
+        &pda::derive_interest_distributor_pda(&f.ctx.state, &f.mint.pubkey(), 0).0,
+        None,
+        &pda::derive_savings_vault_ata(&f.mint.pubkey(), &f.savings_manager()),
+        SOL,
+    );
+    let result = f
+        .ctx
+        .send_and_confirm_tx(vec![instruction], Some(vec![&f.user]))
+        .await;
+    assert_error(result, SavingsError::NotNativeMint);
+}
+
+#[tokio::test]
+async fn test_allowlist_gating() {
+    let f = setup_fixture(0, 100).await;
+    let mint = f.mint.pubkey();
+    let (applicant, applicant_ata) = f
+        .ctx
+        .create_funded_user(&mint, &f.mint_authority, 1000)
+        .await
+        .unwrap();
+    let (verifier, _) = f
+        .ctx
+        .create_funded_user(&mint, &f.mint_authority, 0)
+        .await
+        .unwrap();
+
+    // The user has a savings plan and a bonus campaign allocation waiting to be paid.
+    let user = f.user.pubkey();
+    f.ctx
+        .user_create_savings_plan(&f.user, &mint, &f.user_ata, 50, 50, 60)
+        .await
+        .unwrap();
+    let leaf = Campaign::leaf(0, &user, 20);
+    let expires_ts = f.ctx.current_time().await.unwrap() + 7 * 24 * 60 * 60;
+    let campaign = f
+        .ctx
+        .create_campaign(&mint, 1, leaf, 1, expires_ts)
+        .await
+        .unwrap();
+    let campaign_vault = pda::derive_campaign_vault_ata(&mint, &campaign);
+    let fund = utils::mint_tokens(&mint, &campaign_vault, &f.mint_authority.pubkey(), 20).unwrap();
+    f.ctx
+        .send_and_confirm_tx(vec![fund], Some(vec![&f.mint_authority]))
+        .await
+        .unwrap();
+    f.ctx.advance_clock(60).await.unwrap();
+
+    // Once gated, unlisted users can neither open vaults nor deposit into existing ones, whether
+    // directly, through a savings plan or by claiming a campaign allocation.
+    f.ctx.set_allowlist_required(&mint, true).await.unwrap();
+    let result = f.ctx.user_create_vault(&applicant, &mint, None).await;
+    assert_error(result, SavingsError::NotAllowlisted);
+    let result = f.ctx.user_deposit(&f.user, &mint, &f.user_ata, 50).await;
+    assert_error(result, SavingsError::NotAllowlisted);
+    let result = f.ctx.execute_savings_plan(&user, &mint, &f.user_ata).await;
+    assert_error(result, SavingsError::NotAllowlisted);
+    let result = f
+        .ctx
+        .claim_campaign(&user, &mint, &campaign, 0, 20, vec![])
+        .await;
+    assert_error(result, SavingsError::NotAllowlisted);
+
+    // Only the authority or the designated verifier may add entries.
+    let expires_at = f.ctx.current_time().await.unwrap() + 24 * 60 * 60;
+    let result = f
+        .ctx
+        .add_allowlist_entry(&verifier, &applicant.pubkey(), expires_at)
+        .await;
+    assert_error(result, SavingsError::UnauthorizedVerifier);
+    f.ctx
+        .set_allowlist_verifier(Some(&verifier.pubkey()))
+        .await
+        .unwrap();
+    f.ctx.refresh_blockhash().await.unwrap();
+    f.ctx
+        .add_allowlist_entry(&verifier, &applicant.pubkey(), expires_at)
+        .await
+        .unwrap();
+
+    f.ctx
+        .user_create_vault(&applicant, &mint, None)
+        .await
+        .unwrap();
+    f.ctx
+        .user_deposit(&applicant, &mint, &applicant_ata, 500)
+        .await
+        .unwrap();
+
+    // Expired entries no longer grant access, but withdrawals are unaffected.
+    f.ctx.advance_clock(24 * 60 * 60).await.unwrap();
+    let result = f
+        .ctx
+        .user_deposit(&applicant, &mint, &applicant_ata, 500)
+        .await;
+    assert_error(result, SavingsError::AllowlistEntryExpired);
+    f.ctx
+        .user_withdraw(&applicant, &mint, &applicant_ata, 500)
+        .await
+        .unwrap();
+
+    f.ctx
+        .remove_allowlist_entry(&verifier, &applicant.pubkey())
+        .await
+        .unwrap();
+    let entry = pda::derive_allowlist_entry_pda(&f.ctx.state, &applicant.pubkey()).0;
+    assert!(f.ctx.get_account(&entry).await.is_err());
+
+    // Lifting the gate reopens deposits to everyone.
+    f.ctx.set_allowlist_required(&mint, false).await.unwrap();
+    f.ctx
+        .user_deposit(&applicant, &mint, &applicant_ata, 500)
+        .await
+        .unwrap();
+    f.ctx
+        .execute_savings_plan(&user, &mint, &f.user_ata)
+        .await
+        .unwrap();
+    f.ctx
+        .claim_campaign(&user, &mint, &campaign, 0, 20, vec![])
+        .await
+        .unwrap();
+    assert_eq!(f.savings_balance().await, 170);
+}
+
+#[tokio::test]
+async fn test_compliance_freeze() {
+    let f = setup_fixture(100, 500).await;
+    let mint = f.mint.pubkey();
+    let user = f.user.pubkey();
+    let (bystander, bystander_ata) = f
+        .ctx
+        .create_funded_user(&mint, &f.mint_authority, 1000)
+        .await
+        .unwrap();
+    f.ctx
+        .user_create_vault(&bystander, &mint, None)
+        .await
+        .unwrap();
+    f.ctx
+        .user_deposit(&bystander, &mint, &bystander_ata, 1000)
+        .await
+        .unwrap();
+
+    f.ctx.freeze_savings_manager(&user, &mint, 7).await.unwrap();
+    let sm_account = f
+        .ctx
+        .get_deserialized_account::<SavingsManager>(&f.savings_manager())
+        .await
+        .unwrap();
+    assert!(sm_account.frozen);
+    assert_eq!(sm_account.freeze_reason_code, 7);
+
+    // Freezing twice is rejected.
+    f.ctx.refresh_blockhash().await.unwrap();
+    let result = f.ctx.freeze_savings_manager(&user, &mint, 7).await;
+    assert_error(result, SavingsError::SavingsManagerFrozen);
+
+    // The frozen vault can't be deposited to, withdrawn from, or paid interest.
+    let result = f.ctx.user_deposit(&f.user, &mint, &f.user_ata, 100).await;
+    assert_error(result, SavingsError::SavingsManagerFrozen);
+    let result = f.ctx.user_withdraw(&f.user, &mint, &f.user_ata, 100).await;
+    assert_error(result, SavingsError::SavingsManagerFrozen);
+    let result = f.ctx.request_withdrawal(&f.user, &mint, 100).await;
+    assert_error(result, SavingsError::SavingsManagerFrozen);
+    f.ctx.advance_clock(SECONDS_IN_MONTHS).await.unwrap();
+    let result = f.ctx.deposit_interest(&user, &mint).await;
+    assert_error(result, SavingsError::SavingsManagerFrozen);
+
+    // Other users are unaffected, and batches pass over the frozen vault.
+    f.ctx
+        .deposit_interest_multiple(&[user, bystander.pubkey()], &mint)
+        .await
+        .unwrap();
+    assert_eq!(f.savings_balance().await, 500);
+    let distributor = pda::derive_interest_distributor_pda(&f.ctx.state, &mint, 0).0;
+    let bystander_manager = pda::derive_savings_manager_pda(&bystander.pubkey(), &distributor, 0).0;
+    let bystander_vault = pda::derive_savings_vault_ata(&mint, &bystander_manager);
+    assert_eq!(f.token_balance(&bystander_vault).await, 1010);
+    f.ctx
+        .user_withdraw(&bystander, &mint, &bystander_ata, 100)
+        .await
+        .unwrap();
+
+    // Unfreezing restores normal operation.
+    f.ctx
+        .unfreeze_savings_manager(&user, &mint, 8)
+        .await
+        .unwrap();
+    f.ctx.refresh_blockhash().await.unwrap();
+    f.ctx.deposit_interest(&user, &mint).await.unwrap();
+    assert_eq!(f.savings_balance().await, 505);
+    f.ctx.refresh_blockhash().await.unwrap();
+    f.ctx
+        .user_withdraw(&f.user, &mint, &f.user_ata, 100)
+        .await
+        .unwrap();
+    assert_eq!(f.savings_balance().await, 405);
+
+    f.ctx.refresh_blockhash().await.unwrap();
+    let result = f.ctx.unfreeze_savings_manager(&user, &mint, 8).await;
+    assert_error(result, SavingsError::SavingsManagerNotFrozen);
+}
+
+#[tokio::test]
+async fn test_timelocked_config_changes() {
+    const DAY: i64 = 24 * 60 * 60;
+    let f = setup_fixture(0, 100).await;
+    let mint = f.mint.pubkey();
+    let distributor = pda::derive_interest_distributor_pda(&f.ctx.state, &mint, 0).0;
+
+    f.ctx
+        .apply_config_change(None, ConfigChange::TimelockDelay { delay: DAY })
+        .await
+        .unwrap();
+
+    // Changes must target the kind of account they configure.
+    let result = f
+        .ctx
+        .queue_config_change(Some(&mint), ConfigChange::TimelockDelay { delay: 0 })
+        .await;
+    assert_error(result, SavingsError::ConfigChangeTargetMismatch);
+    let result = f
+        .ctx
+        .queue_config_change(None, ConfigChange::AllowlistRequired { required: true })
+        .await;
+    assert_error(result, SavingsError::ConfigChangeTargetMismatch);
+
+    // Queued changes are visible on-chain but can't be executed before the delay has passed.
+    let queued_at = f.ctx.current_time().await.unwrap();
+    let change = ConfigChange::WithdrawalNoticePeriod { notice_period: DAY };
+    let pending_change = f
+        .ctx
+        .queue_config_change(Some(&mint), change.clone())
+        .await
+        .unwrap();
+    let pending = f
+        .ctx
+        .get_deserialized_account::<PendingConfigChange>(&pending_change)
+        .await
+        .unwrap();
+    assert_eq!(pending.change, change);
+    assert_eq!(pending.interest_distributor, Some(distributor));
+    assert_eq!(pending.eta, queued_at + DAY);
+    let result = f
+        .ctx
+        .execute_config_change(Some(&mint), &pending_change)
+        .await;
+    assert_error(result, SavingsError::TimelockNotElapsed);
+
+    // Lowering the delay is itself subject to the delay.
+    let delay_change = f
+        .ctx
+        .queue_config_change(None, ConfigChange::TimelockDelay { delay: 0 })
+        .await
+        .unwrap();
+    let result = f.ctx.execute_config_change(None, &delay_change).await;
+    assert_error(result, SavingsError::TimelockNotElapsed);
+
+    // Cancelled changes are discarded.
+    f.ctx.cancel_config_change(&delay_change).await.unwrap();
+    assert!(f.ctx.get_account(&delay_change).await.is_err());
+
+    f.ctx.advance_clock(DAY).await.unwrap();
+    let result = f.ctx.execute_config_change(None, &pending_change).await;
+    assert_error(result, SavingsError::ConfigChangeTargetMismatch);
+    f.ctx
+        .execute_config_change(Some(&mint), &pending_change)
+        .await
+        .unwrap();
+    assert!(f.ctx.get_account(&pending_change).await.is_err());
+    let distributor_account = f
+        .ctx
+        .get_deserialized_account::<InterestDistributor>(&distributor)
+        .await
+        .unwrap();
+    assert_eq!(distributor_account.withdrawal_notice_period, DAY);
+    let state_account = f
+        .ctx
+        .get_deserialized_account::<State>(&f.ctx.state)
+        .await
+        .unwrap();
+    assert_eq!(state_account.config_timelock_delay, DAY);
+    assert_eq!(state_account.next_config_change_id, 4);
+}
+
+#[tokio::test]
+async fn test_interest_rate_changes() {
+    let f = setup_fixture(1000, 1000).await;
+    let mint = f.mint.pubkey();
+    let distributor = pda::derive_interest_distributor_pda(&f.ctx.state, &mint, 0).0;
+    let rate_history = pda::derive_rate_history_pda(&distributor).0;
+
+    let result = f.ctx.set_interest_rate(&mint, 10_001).await;
+    assert_error(result, SavingsError::InvalidBasisPoints);
+
+    // Interest accrued before a rate change is paid at the old rate.
+    f.ctx.advance_clock(SECONDS_IN_MONTHS / 2).await.unwrap();
+    f.ctx.set_interest_rate(&mint, 300).await.unwrap();
+    f.ctx.advance_clock(SECONDS_IN_MONTHS / 2).await.unwrap();
+    f.ctx
+        .deposit_interest(&f.user.pubkey(), &mint)
+        .await
+        .unwrap();
+    assert_eq!(f.savings_balance().await, 1020);
+
+    let rate_history_account = f
+        .ctx
+        .get_deserialized_account::<RateHistory>(&rate_history)
+        .await
+        .unwrap();
+    assert_eq!(rate_history_account.len, 2);
+    assert_eq!(rate_history_account.current_rate(), 300);
+
+    // Interest accrues for every second since the last deposit, not just a month's worth.
+    f.ctx.advance_clock(2 * SECONDS_IN_MONTHS).await.unwrap();
+    f.ctx
+        .deposit_interest_multiple(&[f.user.pubkey()], &mint)
+        .await
+        .unwrap();
+    assert_eq!(f.savings_balance().await, 1081);
+}
+
+#[test]
+fn test_rate_history_wraps_around() {
+    use savings_program::{RateEntry, RATE_HISTORY_CAPACITY};
+
+    let mut history = RateHistory {
+        distributor: Pubkey::new_unique(),
+        bump: 0,
+        version: 0,
+        head: 0,
+        len: 0,
+        entries: [RateEntry::default(); RATE_HISTORY_CAPACITY],
+        rate_feed: None,
+        max_feed_staleness: 0,
+        min_rate_bps: 0,
+        max_rate_bps: 0,
+        reserved: [0; 19],
+    };
+    for i in 0..RATE_HISTORY_CAPACITY as i64 + 2 {
+        history.push(100 + i as u16, i * SECONDS_IN_MONTHS);
+    }
+    assert_eq!(history.len as usize, RATE_HISTORY_CAPACITY);
+    assert_eq!(
+        history.current_rate(),
+        100 + RATE_HISTORY_CAPACITY as u16 + 1
+    );
+
+    // A month at each of the two most recent rates.
+    let now = (RATE_HISTORY_CAPACITY as i64 + 2) * SECONDS_IN_MONTHS;
+    let interest = history
+        .accrued_interest(10_000, now - 2 * SECONDS_IN_MONTHS, now)
+        .unwrap();
+    assert_eq!(interest, 132 + 133);
+
+    // Time before the oldest remembered rate accrues at that rate.
+    let interest = history
+        .accrued_interest(10_000, 0, 3 * SECONDS_IN_MONTHS)
+        .unwrap();
+    assert_eq!(interest, 3 * 102);
+
+    // Interest past u64::MAX is an error rather than a truncated amount.
+    history.push(10_000, now);
+    let result = history.accrued_interest(u64::MAX, now, now + 2 * SECONDS_IN_MONTHS);
+    assert_eq!(
+        result.unwrap_err(),
+        anchor_lang::error::Error::from(SavingsError::InterestOverflow)
+    );
+}
+
+#[tokio::test]
+async fn test_performance_fee() {
+    let f = setup_fixture(1000, 1000).await;
+    let mint = f.mint.pubkey();
+    let distributor = pda::derive_interest_distributor_pda(&f.ctx.state, &mint, 0).0;
+    let (_, treasury) = f
+        .ctx
+        .create_funded_user(&mint, &f.mint_authority, 0)
+        .await
+        .unwrap();
+
+    // A fee needs somewhere to go.
+    let result = f.ctx.set_performance_fee(&mint, 2_000, None).await;
+    assert_error(result, SavingsError::MissingFeeDestination);
+    f.ctx
+        .set_performance_fee(&mint, 2_000, Some(&treasury))
+        .await
+        .unwrap();
+
+    // The fee is a share of the gross interest, the user receives the rest.
+    f.ctx.advance_clock(SECONDS_IN_MONTHS).await.unwrap();
+    f.ctx
+        .deposit_interest(&f.user.pubkey(), &mint)
+        .await
+        .unwrap();
+    assert_eq!(f.savings_balance().await, 1008);
+    assert_eq!(f.token_balance(&treasury).await, 2);
+    assert_eq!(f.token_balance(&f.interest_vault()).await, 990);
+
+    f.ctx.advance_clock(SECONDS_IN_MONTHS).await.unwrap();
+    f.ctx
+        .deposit_interest_multiple(&[f.user.pubkey()], &mint)
+        .await
+        .unwrap();
+    assert_eq!(f.savings_balance().await, 1016);
+    assert_eq!(f.token_balance(&treasury).await, 4);
+
+    let distributor_account = f
+        .ctx
+        .get_deserialized_account::<InterestDistributor>(&distributor)
+        .await
+        .unwrap();
+    assert_eq!(distributor_account.total_performance_fees, 4);
+
+    // Interest can't be paid out without the fee destination while a fee is due.
+    f.ctx.advance_clock(SECONDS_IN_MONTHS).await.unwrap();
+    let (_, instruction) = instructions::deposit_interest(
+        &f.user.pubkey(),
+        &f.savings_manager(),
+        &pda::derive_savings_vault_ata(&mint, &f.savings_manager()),
+        &distributor,
+        &f.interest_vault(),
+        &pda::derive_rate_history_pda(&distributor).0,
+        None,
+        None,
+        None,
+        None,
+    );
+    let result = f.ctx.send_and_confirm_tx(vec![instruction], None).await;
+    assert_error(result, SavingsError::MissingFeeDestination);
+}
+
+#[tokio::test]
+async fn test_withdrawal_fee() {
+    const WEEK: i64 = 7 * 24 * 60 * 60;
+    let f = setup_fixture(0, 1000).await;
+    let mint = f.mint.pubkey();
+
+    let result = f.ctx.set_withdrawal_fee(&mint, 100, -1).await;
+    assert_error(result, SavingsError::InvalidWaiverPeriod);
+    f.ctx.set_withdrawal_fee(&mint, 100, WEEK).await.unwrap();
+
+    // Early withdrawals pay the fee into the interest vault.
+    f.ctx
+        .user_withdraw(&f.user, &mint, &f.user_ata, 500)
+        .await
+        .unwrap();
+    assert_eq!(f.savings_balance().await, 500);
+    assert_eq!(f.token_balance(&f.user_ata).await, 495);
+    assert_eq!(f.token_balance(&f.interest_vault()).await, 5);
+
+    // The fee is waived once the holding period has passed since the last deposit.
+    f.ctx.advance_clock(WEEK).await.unwrap();
+    f.ctx
+        .user_withdraw(&f.user, &mint, &f.user_ata, 200)
+        .await
+        .unwrap();
+    assert_eq!(f.token_balance(&f.user_ata).await, 695);
+    assert_eq!(f.token_balance(&f.interest_vault()).await, 5);
+
+    // A new deposit restarts the holding period, including for noticed withdrawals.
+    f.ctx
+        .user_deposit(&f.user, &mint, &f.user_ata, 95)
+        .await
+        .unwrap();
+    f.ctx.set_withdrawal_notice_period(&mint, 60).await.unwrap();
+    f.ctx.request_withdrawal(&f.user, &mint, 300).await.unwrap();
+    f.ctx.advance_clock(60).await.unwrap();
+    f.ctx
+        .execute_withdrawal(&f.user, &mint, &f.user_ata)
+        .await
+        .unwrap();
+    assert_eq!(f.savings_balance().await, 95);
+    assert_eq!(f.token_balance(&f.user_ata).await, 897);
+    assert_eq!(f.token_balance(&f.interest_vault()).await, 8);
+}
+
+#[tokio::test]
+async fn test_withdrawal_limits() {
+    const DAY: i64 = 24 * 60 * 60;
+    let f = setup_fixture(0, 1000).await;
+    let mint = f.mint.pubkey();
+    let (other, other_ata) = f
+        .ctx
+        .create_funded_user(&mint, &f.mint_authority, 1000)
+        .await
+        .unwrap();
+    f.ctx.user_create_vault(&other, &mint, None).await.unwrap();
+    f.ctx
+        .user_deposit(&other, &mint, &other_ata, 500)
+        .await
+        .unwrap();
+
+    let result = f.ctx.set_withdrawal_limits(&mint, 300, 400, 0).await;
+    assert_error(result, SavingsError::InvalidWithdrawalLimitWindow);
+    f.ctx
+        .set_withdrawal_limits(&mint, 300, 400, DAY)
+        .await
+        .unwrap();
+
+    // Each user can withdraw up to their limit per day.
+    f.ctx
+        .user_withdraw(&f.user, &mint, &f.user_ata, 200)
+        .await
+        .unwrap();
+    let result = f.ctx.user_withdraw(&f.user, &mint, &f.user_ata, 150).await;
+    assert_error(result, SavingsError::UserWithdrawalLimitExceeded);
+    f.ctx
+        .user_withdraw(&f.user, &mint, &f.user_ata, 100)
+        .await
+        .unwrap();
+    assert_eq!(f.savings_balance().await, 700);
+
+    // The distributor's outflow is shared by all users, and deposits don't make room for more.
+    let result = f.ctx.user_withdraw(&other, &mint, &other_ata, 150).await;
+    assert_error(result, SavingsError::DistributorOutflowLimitExceeded);
+    f.ctx
+        .user_withdraw(&other, &mint, &other_ata, 100)
+        .await
+        .unwrap();
+    f.ctx
+        .user_deposit(&f.user, &mint, &f.user_ata, 50)
+        .await
+        .unwrap();
+    let result = f.ctx.user_withdraw(&other, &mint, &other_ata, 1).await;
+    assert_error(result, SavingsError::DistributorOutflowLimitExceeded);
+
+    // Withdrawals count against both limits until they are a full window old.
+    f.ctx.advance_clock(DAY - 60).await.unwrap();
+    let result = f.ctx.user_withdraw(&f.user, &mint, &f.user_ata, 1).await;
+    assert_error(result, SavingsError::UserWithdrawalLimitExceeded);
+    f.ctx.advance_clock(60 + DAY / 5).await.unwrap();
+    f.ctx
+        .user_withdraw(&f.user, &mint, &f.user_ata, 100)
+        .await
+        .unwrap();
+
+    // The windows roll rather than reset, so withdrawing up to the limits just before midnight
+    // leaves no room just after it.
+    let now = f.ctx.current_time().await.unwrap();
+    f.ctx
+        .advance_clock(3 * DAY - now.rem_euclid(DAY) - 1)
+        .await
+        .unwrap();
+    f.ctx
+        .user_withdraw(&f.user, &mint, &f.user_ata, 300)
+        .await
+        .unwrap();
+    f.ctx
+        .user_withdraw(&other, &mint, &other_ata, 100)
+        .await
+        .unwrap();
+    f.ctx.advance_clock(2).await.unwrap();
+    let result = f.ctx.user_withdraw(&f.user, &mint, &f.user_ata, 1).await;
+    assert_error(result, SavingsError::UserWithdrawalLimitExceeded);
+    let result = f.ctx.user_withdraw(&other, &mint, &other_ata, 1).await;
+    assert_error(result, SavingsError::DistributorOutflowLimitExceeded);
+    assert_eq!(f.savings_balance().await, 350);
+
+    // Noticed withdrawals over the limit stay pending until the limit leaves room for them.
+    f.ctx.set_withdrawal_notice_period(&mint, 60).await.unwrap();
+    f.ctx.request_withdrawal(&f.user, &mint, 250).await.unwrap();
+    f.ctx.advance_clock(60).await.unwrap();
+    let result = f.ctx.execute_withdrawal(&f.user, &mint, &f.user_ata).await;
+    assert_error(result, SavingsError::UserWithdrawalLimitExceeded);
+    let sm_account = f
+        .ctx
+        .get_deserialized_account::<SavingsManager>(&f.savings_manager())
+        .await
+        .unwrap();
+    assert_eq!(sm_account.pending_withdrawal_amount, 250);
+
+    f.ctx.advance_clock(DAY + DAY / 5).await.unwrap();
+    f.ctx
+        .execute_withdrawal(&f.user, &mint, &f.user_ata)
+        .await
+        .unwrap();
+    assert_eq!(f.savings_balance().await, 100);
+}
+
+#[tokio::test]
+async fn test_bonus_campaign() {
+    let f = setup_fixture(0, 100).await;
+    let mint = f.mint.pubkey();
+    let user = f.user.pubkey();
+    let (other, _) = f
+        .ctx
+        .create_funded_user(&mint, &f.mint_authority, 0)
+        .await
+        .unwrap();
+    f.ctx.user_create_vault(&other, &mint, None).await.unwrap();
+    let other = other.pubkey();
+
+    // A three-leaf tree: the first two leaves are paired, and the third is paired with their parent.
+    let leaves = [
+        Campaign::leaf(0, &user, 50),
+        Campaign::leaf(1, &other, 30),
+        Campaign::leaf(2, &user, 20),
+    ];
+    let parent = Campaign::hash_nodes(&leaves[0], &leaves[1]);
+    let root = Campaign::hash_nodes(&parent, &leaves[2]);
+
+    // Campaigns must expire in the future.
+    let now = f.ctx.current_time().await.unwrap();
+    let result = f.ctx.create_campaign(&mint, 7, root, 3, now).await;
+    assert_error(result, SavingsError::InvalidCampaignExpiry);
+    let campaign = f
+        .ctx
+        .create_campaign(&mint, 7, root, 3, now + 24 * 60 * 60)
+        .await
+        .unwrap();
+    let campaign_vault = pda::derive_campaign_vault_ata(&mint, &campaign);
+    let fund = utils::mint_tokens(&mint, &campaign_vault, &f.mint_authority.pubkey(), 100).unwrap();
+    f.ctx
+        .send_and_confirm_tx(vec![fund], Some(vec![&f.mint_authority]))
+        .await
+        .unwrap();
+
+    // Claims must match a leaf exactly.
+    let result = f
+        .ctx
+        .claim_campaign(&user, &mint, &campaign, 0, 60, vec![leaves[1], leaves[2]])
+        .await;
+    assert_error(result, SavingsError::InvalidCampaignProof);
+    let result = f
+        .ctx
+        .claim_campaign(&user, &mint, &campaign, 1, 30, vec![leaves[0], leaves[2]])
+        .await;
+    assert_error(result, SavingsError::InvalidCampaignProof);
+
+    f.ctx
+        .claim_campaign(&user, &mint, &campaign, 0, 50, vec![leaves[1], leaves[2]])
+        .await
+        .unwrap();
+    assert_eq!(f.savings_balance().await, 150);
+
+    // Each allocation can only be claimed once.
+    f.ctx.refresh_blockhash().await.unwrap();
+    let result = f
+        .ctx
+        .claim_campaign(&user, &mint, &campaign, 0, 50, vec![leaves[1], leaves[2]])
+        .await;
+    assert_error(result, SavingsError::CampaignAlreadyClaimed);
+
+    f.ctx
+        .claim_campaign(&other, &mint, &campaign, 1, 30, vec![leaves[0], leaves[2]])
+        .await
+        .unwrap();
+    let campaign_account = f
+        .ctx
+        .get_deserialized_account::<Campaign>(&campaign)
+        .await
+        .unwrap();
+    assert_eq!(campaign_account.total_claimed, 80);
+    assert_eq!(campaign_account.claimed, vec![0b011]);
+    assert_eq!(f.token_balance(&campaign_vault).await, 20);
+
+    // The campaign can't be closed while allocations can still be claimed.
+    let result = f.ctx.close_campaign(&mint, &campaign).await;
+    assert_error(result, SavingsError::CampaignNotExpired);
+
+    // Once expired, the remaining allocation can no longer be claimed, and closing the campaign
+    // sweeps what's left back to the interest vault.
+    f.ctx.advance_clock(24 * 60 * 60).await.unwrap();
+    let result = f
+        .ctx
+        .claim_campaign(&user, &mint, &campaign, 2, 20, vec![parent])
+        .await;
+    assert_error(result, SavingsError::CampaignExpired);
+    let interest_before = f.token_balance(&f.interest_vault()).await;
+    f.ctx.close_campaign(&mint, &campaign).await.unwrap();
+    assert_eq!(
+        f.token_balance(&f.interest_vault()).await,
+        interest_before + 20
+    );
+    assert!(f.ctx.get_account(&campaign).await.is_err());
+    assert!(f.ctx.get_account(&campaign_vault).await.is_err());
+}
+
+#[tokio::test]
+async fn test_multiple_products() {
+    let f = setup_fixture(0, 100).await;
+    let mint = f.mint.pubkey();
+    let state = f.ctx.state;
+    let admin = f.ctx.admin.pubkey();
+    let user = f.user.pubkey();
+
+    // The first product keeps the address distributors had before product ids.
+    let flexible = pda::derive_interest_distributor_pda(&state, &mint, 0).0;
+    let (legacy, _) = Pubkey::find_program_address(
+        &[
+            INTEREST_DISTRIBUTOR_SEED_PREFIX,
+            state.as_ref(),
+            mint.as_ref(),
+        ],
+        &savings_program::ID,
+    );
+    assert_eq!(flexible, legacy);
+
+    let locked = f.ctx.create_product_interest_vault(&mint, 1).await.unwrap();
+    assert_ne!(locked, flexible);
+    let distributor = f
+        .ctx
+        .get_deserialized_account::<InterestDistributor>(&locked)
+        .await
+        .unwrap();
+    assert_eq!(distributor.mint, mint);
+    assert_eq!(distributor.product_id, 1);
+    f.ctx.refresh_blockhash().await.unwrap();
+    let result = f.ctx.create_product_interest_vault(&mint, 1).await;
+    assert_error(result, SystemError::AccountAlreadyInUse as u32);
+
+    // Each product has its own interest vault, signed for with the product's seeds.
+    let locked_vault = pda::derive_interest_vault_ata(&mint, &locked);
+    let (_, fund) = instructions::deposit_to_interest_vault(
+        &admin,
+        &state,
+        &user,
+        &f.user_ata,
+        &locked,
+        &locked_vault,
+        200,
+    );
+    let (_, withdraw) = instructions::withdraw_from_interest_vault(
+        &admin,
+        &f.user_ata,
+        &state,
+        &locked,
+        &locked_vault,
+        50,
+    );
+    f.ctx
+        .send_and_confirm_tx(vec![fund, withdraw], Some(vec![&f.ctx.admin, &f.user]))
+        .await
+        .unwrap();
+    assert_eq!(f.token_balance(&locked_vault).await, 150);
+    assert_eq!(f.token_balance(&f.interest_vault()).await, 0);
+
+    // Users can hold a position in each product.
+    let manager = pda::derive_savings_manager_pda(&user, &locked, 0).0;
+    let (_, create_vault) = instructions::user_create_vault(
+        &user,
+        &user,
+        &mint,
+        &locked,
+        &manager,
+        &pda::derive_savings_vault_ata(&mint, &manager),
+        &pda::derive_registry_page_pda(&locked, 0).0,
+        None,
+        None,
+        0,
+        [0; 32],
+    );
+    f.ctx
+        .send_and_confirm_tx(vec![create_vault], Some(vec![&f.user]))
+        .await
+        .unwrap();
+    assert_ne!(manager, f.savings_manager());
+    let manager_account = f
+        .ctx
+        .get_deserialized_account::<SavingsManager>(&manager)
+        .await
+        .unwrap();
+    assert_eq!(manager_account.distributor, locked);
+}
+
+#[tokio::test]
+async fn test_sub_accounts() {
+    let f = setup_fixture(1000, 100).await;
+    let mint = f.mint.pubkey();
+    let user = f.user.pubkey();
+    let distributor = pda::derive_interest_distributor_pda(&f.ctx.state, &mint, 0).0;
+    let interest_vault = f.interest_vault();
+
+    let mut label = [0; 32];
+    label[..8].copy_from_slice(b"vacation");
+    let vacation = f
+        .ctx
+        .user_create_sub_account(&f.user, &mint, 1, label, None)
+        .await
+        .unwrap();
+    assert_ne!(vacation, f.savings_manager());
+    let manager = f
+        .ctx
+        .get_deserialized_account::<SavingsManager>(&vacation)
+        .await
+        .unwrap();
+    assert_eq!(manager.index, 1);
+    assert_eq!(manager.label, label);
+    f.ctx.refresh_blockhash().await.unwrap();
+    let result = f
+        .ctx
+        .user_create_sub_account(&f.user, &mint, 1, [0; 32], None)
+        .await;
+    assert_error(result, SystemError::AccountAlreadyInUse as u32);
+
+    // Each sub-account is a separate pot, signed for with its own seeds.
+    let vacation_vault = pda::derive_savings_vault_ata(&mint, &vacation);
+    let (_, deposit) = instructions::user_deposit(
+        &user,
+        &f.user_ata,
+        &vacation,
+        &distributor,
+        None,
+        &vacation_vault,
+        None,
+        None,
+        None,
+        300,
+    );
+    let (_, withdraw) = instructions::user_withdraw(
+        &user,
+        &vacation,
+        &distributor,
+        &vacation_vault,
+        &interest_vault,
+        &f.user_ata,
+        50,
+    );
+    f.ctx
+        .send_and_confirm_tx(vec![deposit, withdraw], Some(vec![&f.user]))
+        .await
+        .unwrap();
+    assert_eq!(f.token_balance(&vacation_vault).await, 250);
+    assert_eq!(f.savings_balance().await, 100);
+
+    // Sub-accounts earn interest independently.
+    f.ctx.advance_clock(SECONDS_IN_MONTHS).await.unwrap();
+    let (_, deposit_interest) = instructions::deposit_interest(
+        &user,
+        &vacation,
+        &vacation_vault,
+        &distributor,
+        &interest_vault,
+        &pda::derive_rate_history_pda(&distributor).0,
+        None,
+        None,
+        None,
+        None,
+    );
+    f.ctx
+        .send_and_confirm_tx(vec![deposit_interest], None)
+        .await
+        .unwrap();
+    assert!(f.token_balance(&vacation_vault).await > 250);
+    assert_eq!(f.savings_balance().await, 100);
+}
+
+#[tokio::test]
+async fn test_preview_interest() {
+    let f = setup_fixture(1000, 500).await;
+    let mint = f.mint.pubkey();
+    let user = f.user.pubkey();
+
+    let manager = f
+        .ctx
+        .get_deserialized_account::<SavingsManager>(&f.savings_manager())
+        .await
+        .unwrap();
+    let preview = f.ctx.preview_interest(&user, &mint).await.unwrap();
+    assert_eq!(
+        preview.next_payout_ts,
+        manager.last_interest_deposit_ts + SECONDS_IN_MONTHS
+    );
+    assert_eq!(preview.projected_interest, 5);
+    assert!(preview.interest_vault_sufficient);
+    assert_eq!(preview.current_rate_bps, DEFAULT_INTEREST_RATE_BPS);
+
+    // Once due, the preview matches what the crank pays out.
+    f.ctx.advance_clock(SECONDS_IN_MONTHS).await.unwrap();
+    let preview = f.ctx.preview_interest(&user, &mint).await.unwrap();
+    f.ctx.deposit_interest(&user, &mint).await.unwrap();
+    assert_eq!(f.savings_balance().await, 500 + preview.projected_interest);
+
+    // Previews flag payouts the interest vault can't cover.
+    let remaining = f.token_balance(&f.interest_vault()).await;
+    f.ctx
+        .withdraw_from_interest_vault(&mint, &f.user_ata, remaining)
+        .await
+        .unwrap();
+    let preview = f.ctx.preview_interest(&user, &mint).await.unwrap();
+    assert!(preview.projected_interest > 0);
+    assert!(!preview.interest_vault_sufficient);
+}
+
+#[tokio::test]
+async fn test_deposit_for() {
+    let f = setup_fixture(0, 100).await;
+    let mint = f.mint.pubkey();
+    let user = f.user.pubkey();
+    let (employer, employer_ata) = f
+        .ctx
+        .create_funded_user(&mint, &f.mint_authority, 1000)
+        .await
+        .unwrap();
+
+    // Anyone can pay into a user's savings vault from their own token account.
+    f.ctx
+        .deposit_for(&employer, &employer_ata, &user, &mint, 300)
+        .await
+        .unwrap();
+    assert_eq!(f.savings_balance().await, 400);
+    assert_eq!(f.token_balance(&employer_ata).await, 700);
+
+    // But not from someone else's.
+    let result = f
+        .ctx
+        .deposit_for(&employer, &f.user_ata, &user, &mint, 100)
+        .await;
+    assert_error(result, TokenError::OwnerMismatch as u32);
+
+    // Users who opt out only accept their own deposits.
+    f.ctx
+        .user_set_third_party_deposits(&f.user, &mint, false)
+        .await
+        .unwrap();
+    let result = f
+        .ctx
+        .deposit_for(&employer, &employer_ata, &user, &mint, 200)
+        .await;
+    assert_error(result, SavingsError::ThirdPartyDepositsDisabled);
+    f.ctx
+        .deposit_for(&f.user, &f.user_ata, &user, &mint, 100)
+        .await
+        .unwrap();
+    assert_eq!(f.savings_balance().await, 500);
+
+    f.ctx
+        .user_set_third_party_deposits(&f.user, &mint, true)
+        .await
+        .unwrap();
+    f.ctx.refresh_blockhash().await.unwrap();
+    f.ctx
+        .deposit_for(&employer, &employer_ata, &user, &mint, 200)
+        .await
+        .unwrap();
+    assert_eq!(f.savings_balance().await, 700);
+}
+
+#[tokio::test]
+async fn test_savings_manager_registry() {
+    let f = setup_fixture(0, 500).await;
+    let mint = f.mint.pubkey();
+    let distributor = pda::derive_interest_distributor_pda(&f.ctx.state, &mint, 0).0;
+
+    // New vaults are listed on the distributor's registry as they're created.
+    let sub_account = f
+        .ctx
+        .user_create_sub_account(&f.user, &mint, 1, [0; 32], None)
+        .await
+        .unwrap();
+    assert_eq!(
+        f.ctx.registered_savings_managers(&mint).await.unwrap(),
+        vec![f.savings_manager(), sub_account]
+    );
+    let result = f
+        .ctx
+        .register_savings_manager(&f.savings_manager(), 0)
+        .await;
+    assert_error(result, SavingsError::SavingsManagerAlreadyRegistered);
+
+    // Once another page is added, new vaults go there.
+    f.ctx.create_registry_page(&mint).await.unwrap();
+    let (other_user, _) = f
+        .ctx
+        .create_funded_user(&mint, &f.mint_authority, 0)
+        .await
+        .unwrap();
+    f.ctx
+        .user_create_vault(&other_user, &mint, None)
+        .await
+        .unwrap();
+    let other_manager = pda::derive_savings_manager_pda(&other_user.pubkey(), &distributor, 0).0;
+    let manager = f
+        .ctx
+        .get_deserialized_account::<SavingsManager>(&other_manager)
+        .await
+        .unwrap();
+    assert!(manager.registered);
+    assert_eq!(manager.registry_page, 1);
+    let distributor_account = f
+        .ctx
+        .get_deserialized_account::<InterestDistributor>(&distributor)
+        .await
+        .unwrap();
+    assert_eq!(distributor_account.registry_pages, 2);
+
+    // A transferred position's new vault takes the closed one's place.
+    let new_user = Keypair::new();
+    f.ctx
+        .user_transfer_position(&f.user, &new_user, &mint)
+        .await
+        .unwrap();
+    let new_manager = pda::derive_savings_manager_pda(&new_user.pubkey(), &distributor, 0).0;
+    let page = f
+        .ctx
+        .get_deserialized_account::<RegistryPage>(&pda::derive_registry_page_pda(&distributor, 0).0)
+        .await
+        .unwrap();
+    assert_eq!(page.managers, vec![sub_account, new_manager]);
+    assert_eq!(
+        f.ctx.registered_savings_managers(&mint).await.unwrap(),
+        vec![sub_account, new_manager, other_manager]
+    );
+}
+
+#[tokio::test]
+async fn test_transfer_position() {
+    let f = setup_fixture(0, 500).await;
+    let mint = f.mint.pubkey();
+    let distributor = pda::derive_interest_distributor_pda(&f.ctx.state, &mint, 0).0;
+    let (new_user, _) = f
+        .ctx
+        .create_funded_user(&mint, &f.mint_authority, 0)
+        .await
+        .unwrap();
+    f.ctx.user_set_goal(&f.user, &mint, 600, 0).await.unwrap();
+    f.ctx.advance_clock(1000).await.unwrap();
+    let old = f
+        .ctx
+        .get_deserialized_account::<SavingsManager>(&f.savings_manager())
+        .await
+        .unwrap();
+
+    // A pending withdrawal has to be settled first.
+    f.ctx.request_withdrawal(&f.user, &mint, 100).await.unwrap();
+    let result = f
+        .ctx
+        .user_transfer_position(&f.user, &new_user, &mint)
+        .await;
+    assert_error(result, SavingsError::PendingWithdrawalExists);
+    f.ctx.user_cancel_goal(&f.user, &mint).await.unwrap();
+    f.ctx
+        .execute_withdrawal(&f.user, &mint, &f.user_ata)
+        .await
+        .unwrap();
+    f.ctx.user_set_goal(&f.user, &mint, 600, 0).await.unwrap();
+
+    f.ctx.refresh_blockhash().await.unwrap();
+    f.ctx
+        .user_transfer_position(&f.user, &new_user, &mint)
+        .await
+        .unwrap();
+
+    // The old savings vault is closed.
+    assert!(f.ctx.get_account(&f.savings_manager()).await.is_err());
+    let old_vault = pda::derive_savings_vault_ata(&mint, &f.savings_manager());
+    assert!(f.ctx.get_account(&old_vault).await.is_err());
+
+    // And the new one holds the tokens with the same accrual timing and goal.
+    let new_manager_address =
+        pda::derive_savings_manager_pda(&new_user.pubkey(), &distributor, 0).0;
+    let new_manager = f
+        .ctx
+        .get_deserialized_account::<SavingsManager>(&new_manager_address)
+        .await
+        .unwrap();
+    assert_eq!(new_manager.user, new_user.pubkey());
+    assert_eq!(
+        new_manager.last_interest_deposit_ts,
+        old.last_interest_deposit_ts
+    );
+    assert_eq!(new_manager.goal_amount, 600);
+    let new_vault = pda::derive_savings_vault_ata(&mint, &new_manager_address);
+    assert_eq!(f.token_balance(&new_vault).await, 400);
+}
+
+#[tokio::test]
+async fn test_receipt_tokens() {
+    let f = setup_fixture(0, 0).await;
+    let mint = f.mint.pubkey();
+    let user = f.user.pubkey();
+    let distributor = pda::derive_interest_distributor_pda(&f.ctx.state, &mint, 0).0;
+    let receipt_pool = pda::derive_receipt_pool_pda(&distributor).0;
+    let receipt_mint = f.ctx.create_receipt_mint(&mint).await.unwrap();
+    let user_receipts = f
+        .ctx
+        .create_token_account(&user, &receipt_mint)
+        .await
+        .unwrap();
+
+    // Deposits go into the distributor's pool and mint receipts one-for-one.
+    f.ctx
+        .user_deposit(&f.user, &mint, &f.user_ata, 500)
+        .await
+        .unwrap();
+    assert_eq!(f.token_balance(&user_receipts).await, 500);
+    assert_eq!(f.token_balance(&receipt_pool).await, 500);
+    assert_eq!(f.savings_balance().await, 0);
+
+    // Tokens deposited without receipts stay in the user's savings vault.
+    f.ctx
+        .deposit_for(&f.user, &f.user_ata, &user, &mint, 100)
+        .await
+        .unwrap();
+    assert_eq!(f.savings_balance().await, 100);
+
+    // Savings plan contributions are deposits like any other.
+    f.ctx
+        .user_create_savings_plan(&f.user, &mint, &f.user_ata, 50, 50, 60)
+        .await
+        .unwrap();
+    f.ctx.advance_clock(60).await.unwrap();
+    f.ctx
+        .execute_savings_plan(&user, &mint, &f.user_ata)
+        .await
+        .unwrap();
+    assert_eq!(f.token_balance(&user_receipts).await, 550);
+    assert_eq!(f.token_balance(&receipt_pool).await, 550);
+    assert_eq!(f.savings_balance().await, 100);
+
+    // Whoever holds receipts redeems them from the pool, leaving every savings vault untouched.
+    let (buyer, buyer_ata) = f
+        .ctx
+        .create_funded_user(&mint, &f.mint_authority, 0)
+        .await
+        .unwrap();
+    let buyer_receipts = f
+        .ctx
+        .create_token_account(&buyer.pubkey(), &receipt_mint)
+        .await
+        .unwrap();
+    let transfer = anchor_spl::token::spl_token::instruction::transfer(
+        &anchor_spl::token::ID,
+        &user_receipts,
+        &buyer_receipts,
+        &user,
+        &[],
+        200,
+    )
+    .unwrap();
+    f.ctx
+        .send_and_confirm_tx(vec![transfer], Some(vec![&f.user]))
+        .await
+        .unwrap();
+
+    f.ctx
+        .redeem_receipts(&buyer, &mint, &buyer_ata, 200)
+        .await
+        .unwrap();
+    assert_eq!(f.token_balance(&buyer_ata).await, 200);
+    assert_eq!(f.token_balance(&buyer_receipts).await, 0);
+    assert_eq!(f.token_balance(&receipt_pool).await, 350);
+    assert_eq!(f.savings_balance().await, 100);
+    let manager = f
+        .ctx
+        .get_deserialized_account::<SavingsManager>(&f.savings_manager())
+        .await
+        .unwrap();
+    assert_eq!(manager.withdrawn_in_window, 0);
+
+    // Redemptions are limited to the receipts held.
+    let result = f.ctx.redeem_receipts(&buyer, &mint, &buyer_ata, 1).await;
+    assert_error(result, TokenError::InsufficientFunds as u32);
+
+    // The user's own savings remain theirs to withdraw in full.
+    f.ctx
+        .user_withdraw(&f.user, &mint, &f.user_ata, 100)
+        .await
+        .unwrap();
+    assert_eq!(f.savings_balance().await, 0);
+}
+
+#[tokio::test]
+async fn test_yield_source() {
+    let f = setup_fixture(1000, 0).await;
+    let mint = f.mint.pubkey();
+    let distributor = pda::derive_interest_distributor_pda(&f.ctx.state, &mint, 0).0;
+    let interest_vault = f.interest_vault();
+    let adapter_address = f.ctx.create_yield_adapter(&mint).await.unwrap();
+    let yield_vault = pda::derive_yield_vault_ata(&mint, &adapter_address);
+    let pool_accounts = |depositor: Pubkey, depositor_token_account: Pubkey| {
+        let pool = Pubkey::find_program_address(
+            &[mock_yield::POOL_SEED_PREFIX, depositor.as_ref()],
+            &mock_yield::ID,
+        )
+        .0;
+        let pool_vault = anchor_spl::associated_token::get_associated_token_address(&pool, &mint);
+        let accounts = vec![
+            AccountMeta::new_readonly(depositor, false),
+            AccountMeta::new(depositor_token_account, false),
+            AccountMeta::new_readonly(pool, false),
+            AccountMeta::new(pool_vault, false),
+            AccountMeta::new_readonly(anchor_spl::token::ID, false),
+        ];
+        (pool, accounts)
+    };
+    let (pool, yield_accounts) = pool_accounts(adapter_address, yield_vault);
+    let pool_vault = f.ctx.create_token_account(&pool, &mint).await.unwrap();
+
+    // Nothing can be allocated until a yield source is configured.
+    let result = f
+        .ctx
+        .manage_yield_funds(&mint, &mock_yield::ID, yield_accounts.clone(), true, 400)
+        .await;
+    assert_error(result, SavingsError::YieldSourceNotConfigured);
+
+    f.ctx
+        .set_yield_source(
+            &mint,
+            Some(&mock_yield::ID),
+            mock_yield::instruction::Deposit::DISCRIMINATOR,
+            mock_yield::instruction::Redeem::DISCRIMINATOR,
+        )
+        .await
+        .unwrap();
+    f.ctx.refresh_blockhash().await.unwrap();
+
+    // The yield program only ever acts for the adapter, never for the distributor itself.
+    let (distributor_pool, distributor_accounts) = pool_accounts(distributor, interest_vault);
+    f.ctx
+        .create_token_account(&distributor_pool, &mint)
+        .await
+        .unwrap();
+    let result = f
+        .ctx
+        .manage_yield_funds(&mint, &mock_yield::ID, distributor_accounts, true, 400)
+        .await;
+    assert_error(result, ErrorCode::AccountNotSigner);
+
+    f.ctx
+        .manage_yield_funds(&mint, &mock_yield::ID, yield_accounts.clone(), true, 400)
+        .await
+        .unwrap();
+    assert_eq!(f.token_balance(&interest_vault).await, 600);
+    assert_eq!(f.token_balance(&pool_vault).await, 400);
+    assert_eq!(f.token_balance(&yield_vault).await, 0);
+    let adapter = f
+        .ctx
+        .get_deserialized_account::<YieldAdapter>(&adapter_address)
+        .await
+        .unwrap();
+    assert_eq!(adapter.allocated, 400);
+
+    // The yield source can't be swapped out while it holds funds.
+    let result = f.ctx.set_yield_source(&mint, None, [0; 8], [0; 8]).await;
+    assert_error(result, SavingsError::YieldFundsAllocated);
+
+    // Recalling the principal plus the yield it earned clears the allocation.
+    let earn_yield =
+        utils::mint_tokens(&mint, &pool_vault, &f.mint_authority.pubkey(), 40).unwrap();
+    f.ctx
+        .send_and_confirm_tx(vec![earn_yield], Some(vec![&f.mint_authority]))
+        .await
+        .unwrap();
+    f.ctx
+        .manage_yield_funds(&mint, &mock_yield::ID, yield_accounts, false, 440)
+        .await
+        .unwrap();
+    assert_eq!(f.token_balance(&interest_vault).await, 1040);
+    assert_eq!(f.token_balance(&yield_vault).await, 0);
+    let adapter = f
+        .ctx
+        .get_deserialized_account::<YieldAdapter>(&adapter_address)
+        .await
+        .unwrap();
+    assert_eq!(adapter.allocated, 0);
+
+    f.ctx.refresh_blockhash().await.unwrap();
+    f.ctx
+        .set_yield_source(&mint, None, [0; 8], [0; 8])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_rate_feed() {
+    const DAY: i64 = 24 * 60 * 60;
+    let f = setup_fixture(1000, 1000).await;
+    let mint = f.mint.pubkey();
+    let user = f.user.pubkey();
+    let publisher = Keypair::new();
+    let feed = f.ctx.create_rate_feed(&publisher.pubkey()).await.unwrap();
+
+    let result = f.ctx.set_rate_feed(&mint, Some(&feed), DAY, 500, 50).await;
+    assert_error(result, SavingsError::InvalidRateFeedConfig);
+    f.ctx
+        .set_rate_feed(&mint, Some(&feed), DAY, 50, 500)
+        .await
+        .unwrap();
+
+    // Only the publisher can update the feed.
+    let result = f.ctx.publish_rate(&f.user, &feed, 200).await;
+    assert_error(result, ErrorCode::ConstraintHasOne);
+
+    // A new rate applies from the time it was published, clamped to the distributor's bounds.
+    f.ctx.advance_clock(SECONDS_IN_MONTHS).await.unwrap();
+    f.ctx.publish_rate(&publisher, &feed, 1000).await.unwrap();
+    f.ctx.deposit_interest(&user, &mint).await.unwrap();
+    assert_eq!(f.savings_balance().await, 1010);
+
+    // Interest can't be paid out against a stale rate.
+    f.ctx.advance_clock(SECONDS_IN_MONTHS).await.unwrap();
+    let result = f.ctx.deposit_interest(&user, &mint).await;
+    assert_error(result, SavingsError::StaleRateFeed);
+
+    f.ctx.publish_rate(&publisher, &feed, 20).await.unwrap();
+    f.ctx.refresh_blockhash().await.unwrap();
+    f.ctx.deposit_interest(&user, &mint).await.unwrap();
+    assert_eq!(f.savings_balance().await, 1060);
+
+    let distributor = pda::derive_interest_distributor_pda(&f.ctx.state, &mint, 0).0;
+    let rate_history = f
+        .ctx
+        .get_deserialized_account::<RateHistory>(&pda::derive_rate_history_pda(&distributor).0)
+        .await
+        .unwrap();
+    assert_eq!(rate_history.current_rate(), 50);
+
+    // A feed hosted by another program only needs the documented layout, byte for byte.
+    let external_feed = Pubkey::new_unique();
+    f.ctx
+        .set_rate_feed(&mint, Some(&external_feed), DAY, 50, 500)
+        .await
+        .unwrap();
+    f.ctx.advance_clock(SECONDS_IN_MONTHS).await.unwrap();
+    let now = f.ctx.current_time().await.unwrap();
+    let data = [
+        RateFeed::DISCRIMINATOR.as_ref(),
+        &300u16.to_le_bytes(),
+        &now.to_le_bytes(),
+        Pubkey::new_unique().as_ref(),
+    ]
+    .concat();
+    assert_eq!(data.len(), 50);
+    let account = solana_sdk::account::Account {
+        lamports: solana_sdk::rent::Rent::default().minimum_balance(data.len()),
+        data,
+        owner: Pubkey::new_unique(),
+        executable: false,
+        rent_epoch: 0,
+    };
+    f.ctx
+        .ctx
+        .borrow_mut()
+        .set_account(&external_feed, &account.into());
+    f.ctx.deposit_interest(&user, &mint).await.unwrap();
+    let rate_history = f
+        .ctx
+        .get_deserialized_account::<RateHistory>(&pda::derive_rate_history_pda(&distributor).0)
+        .await
+        .unwrap();
+    assert_eq!(rate_history.current_rate(), 300);
+    assert_eq!(rate_history.rate_feed, Some(external_feed));
+}
+
+#[tokio::test]
+async fn test_mint_policy() {
+    async fn create_mint(f: &Fixture, freeze_authority: Option<&Pubkey>, decimals: u8) -> Pubkey {
+        let mint = Keypair::new();
+        let instructions = utils::create_token_mint(
+            &mut f.ctx.ctx.borrow_mut(),
+            &mint,
+            &f.mint_authority.pubkey(),
+            freeze_authority,
+            decimals,
+        )
+        .unwrap();
+        f.ctx
+            .send_and_confirm_tx(instructions, Some(vec![&mint]))
+            .await
+            .unwrap();
+        mint.pubkey()
+    }
+
+    let f = setup_fixture(0, 0).await;
+    let freeze_authority = Keypair::new().pubkey();
+    f.ctx.set_mint_policy(true, 6, false).await.unwrap();
+
+    let freezable = create_mint(&f, Some(&freeze_authority), 6).await;
+    assert_error(
+        f.ctx.create_interest_vault(&freezable).await,
+        SavingsError::MintHasFreezeAuthority,
+    );
+    let imprecise = create_mint(&f, None, 2).await;
+    assert_error(
+        f.ctx.create_interest_vault(&imprecise).await,
+        SavingsError::MintDecimalsTooLow,
+    );
+    let compliant = create_mint(&f, None, 6).await;
+    f.ctx.create_interest_vault(&compliant).await.unwrap();
+
+    // Once approval is required, only mints on the list are accepted.
+    f.ctx.set_mint_policy(true, 6, true).await.unwrap();
+    let unlisted = create_mint(&f, None, 9).await;
+    assert_error(
+        f.ctx.create_interest_vault(&unlisted).await,
+        SavingsError::MintNotApproved,
+    );
+    f.ctx.approve_mint(&unlisted).await.unwrap();
+    f.ctx.refresh_blockhash().await.unwrap();
+    f.ctx.create_interest_vault(&unlisted).await.unwrap();
+
+    let revoked = create_mint(&f, None, 9).await;
+    f.ctx.approve_mint(&revoked).await.unwrap();
+    f.ctx.revoke_mint_approval(&revoked).await.unwrap();
+    assert_error(
+        f.ctx.create_interest_vault(&revoked).await,
+        SavingsError::MintNotApproved,
+    );
+}